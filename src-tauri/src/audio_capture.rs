@@ -1,3 +1,4 @@
+use crate::error::{AppError, AppResult};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use std::sync::mpsc::{channel, Sender};
 use std::thread;
@@ -12,7 +13,7 @@ impl AudioCapture {
         Self { stop_tx: None }
     }
 
-    pub fn start(&mut self, app: AppHandle) -> Result<(), String> {
+    pub fn start(&mut self, app: AppHandle) -> AppResult<()> {
         eprintln!("[AudioCapture] start() called");
 
         if self.stop_tx.is_some() {
@@ -27,21 +28,21 @@ impl AudioCapture {
 
         // spawn a thread to handle the stream
         thread::spawn(move || {
-            let init_result = (|| -> Result<cpal::Stream, String> {
+            let init_result = (|| -> AppResult<cpal::Stream> {
                 let host = cpal::default_host();
                 eprintln!("[AudioCapture] Host: {}", host.id().name());
 
                 let device = host
                     .default_output_device()
-                    .ok_or("No output device available")?;
+                    .ok_or_else(|| AppError::audio("No output device available"))?;
                 eprintln!(
                     "[AudioCapture] Device: {}",
                     device.name().unwrap_or("unknown".to_string())
                 );
 
-                let config = device
-                    .default_output_config()
-                    .map_err(|e| format!("Failed to get default output config: {}", e))?;
+                let config = device.default_output_config().map_err(|e| {
+                    AppError::audio(format!("Failed to get default output config: {}", e))
+                })?;
                 eprintln!("[AudioCapture] Config: {:?}", config);
 
                 let channels = config.channels();
@@ -80,14 +81,17 @@ impl AudioCapture {
                         )
                     }
                     sample_format => {
-                        return Err(format!("Unsupported sample format: {:?}", sample_format))
+                        return Err(AppError::audio(format!(
+                            "Unsupported sample format: {:?}",
+                            sample_format
+                        )))
                     }
                 }
-                .map_err(|e| format!("Failed to build input stream: {}", e))?;
+                .map_err(|e| AppError::audio(format!("Failed to build input stream: {}", e)))?;
 
                 stream
                     .play()
-                    .map_err(|e| format!("Failed to play stream: {}", e))?;
+                    .map_err(|e| AppError::audio(format!("Failed to play stream: {}", e)))?;
 
                 Ok(stream)
             })();
@@ -122,15 +126,17 @@ impl AudioCapture {
         // Wait for result from thread
         match res_rx.recv() {
             Ok(result) => result,
-            Err(_) => Err("Failed to receive initialization result from thread".to_string()),
+            Err(_) => Err(AppError::audio(
+                "Failed to receive initialization result from thread",
+            )),
         }
     }
 
-    pub fn stop(&mut self) -> Result<(), String> {
+    pub fn stop(&mut self) -> AppResult<()> {
         if let Some(tx) = self.stop_tx.take() {
             eprintln!("[AudioCapture] Sending stop signal...");
             tx.send(())
-                .map_err(|e| format!("Failed to send stop signal: {}", e))?;
+                .map_err(|e| AppError::audio(format!("Failed to send stop signal: {}", e)))?;
             eprintln!("[AudioCapture] Stop signal sent");
         }
         Ok(())
@@ -0,0 +1,344 @@
+use crate::db_pool::DbPool;
+use crate::error::{AppError, AppResult};
+use crate::migrations;
+use crate::models::{BackupInfo, BackupSettings};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rusqlite::{Connection, OpenFlags};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const BACKUP_DIR_NAME: &str = "backups";
+
+const SETTINGS_FILE_NAME: &str = "settings.json";
+
+// Backups are named playlists-<UTC timestamp>-<kind>.db, so sorting by name
+// sorts by age and the listing needs nothing but the directory.
+const FILE_PREFIX: &str = "playlists-";
+const FILE_EXTENSION: &str = ".db";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+// How often the scheduler checks whether an automatic backup is due. Short
+// enough that changed settings take effect without a restart.
+const SCHEDULE_TICK: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupKind {
+    Manual,
+    Auto,
+    /// Taken right before a restore replaces the live database
+    PreRestore,
+}
+
+impl BackupKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BackupKind::Manual => "manual",
+            BackupKind::Auto => "auto",
+            BackupKind::PreRestore => "pre-restore",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "manual" => Some(BackupKind::Manual),
+            "auto" => Some(BackupKind::Auto),
+            "pre-restore" => Some(BackupKind::PreRestore),
+            _ => None,
+        }
+    }
+}
+
+/// Timestamped copies of the database in `<app data>/backups`, managed as
+/// Tauri state alongside the `DbPool` they copy.
+#[derive(Clone)]
+pub struct Backups {
+    db: DbPool,
+    dir: PathBuf,
+    settings: Arc<Mutex<BackupSettings>>,
+}
+
+impl Backups {
+    pub fn open(db: DbPool, dir: PathBuf) -> AppResult<Self> {
+        fs::create_dir_all(&dir).map_err(|e| AppError::io(dir.display().to_string(), e))?;
+
+        let settings_path = dir.join(SETTINGS_FILE_NAME);
+        let settings = match fs::read_to_string(&settings_path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                eprintln!(
+                    "Ignoring unreadable backup settings in {}: {}",
+                    settings_path.display(),
+                    e
+                );
+                BackupSettings::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BackupSettings::default(),
+            Err(e) => return Err(AppError::io(settings_path.display().to_string(), e)),
+        };
+
+        Ok(Backups {
+            db,
+            dir,
+            settings: Arc::new(Mutex::new(settings)),
+        })
+    }
+
+    pub fn settings(&self) -> AppResult<BackupSettings> {
+        Ok(self.settings.lock()?.clone())
+    }
+
+    pub fn set_settings(&self, settings: BackupSettings) -> AppResult<()> {
+        if settings.interval_hours == 0 {
+            return Err(AppError::validation(
+                "interval_hours",
+                "backup interval must be at least one hour",
+            ));
+        }
+        if settings.keep_last == 0 {
+            return Err(AppError::validation(
+                "keep_last",
+                "at least one backup must be kept",
+            ));
+        }
+
+        let path = self.dir.join(SETTINGS_FILE_NAME);
+        let json = serde_json::to_string_pretty(&settings)
+            .map_err(|e| AppError::internal(format!("failed to serialize settings: {}", e)))?;
+        fs::write(&path, json).map_err(|e| AppError::io(path.display().to_string(), e))?;
+
+        *self.settings.lock()? = settings;
+        Ok(())
+    }
+
+    /// Every backup in the backup directory, newest first.
+    pub fn list(&self) -> AppResult<Vec<BackupInfo>> {
+        let entries =
+            fs::read_dir(&self.dir).map_err(|e| AppError::io(self.dir.display().to_string(), e))?;
+
+        let mut backups = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let Some((created_at, kind)) = parse_file_name(&file_name) else {
+                continue;
+            };
+
+            backups.push(BackupInfo {
+                path: entry.path().to_string_lossy().to_string(),
+                file_name,
+                kind: kind.as_str().to_string(),
+                created_at: created_at.to_rfc3339(),
+                size_bytes: entry.metadata()?.len(),
+            });
+        }
+
+        backups.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+        Ok(backups)
+    }
+
+    pub async fn create(&self, kind: BackupKind) -> AppResult<BackupInfo> {
+        let file_name = format!(
+            "{}{}-{}{}",
+            FILE_PREFIX,
+            Utc::now().format(TIMESTAMP_FORMAT),
+            kind.as_str(),
+            FILE_EXTENSION
+        );
+        let path = self.dir.join(&file_name);
+        if path.exists() {
+            return Err(AppError::conflict(format!(
+                "backup {} already exists",
+                file_name
+            )));
+        }
+
+        // Write under a temporary name so a half-written file never shows up
+        // in the listing or gets picked for a restore
+        let tmp = self.dir.join(format!("{}.tmp", file_name));
+        let result = {
+            let tmp = tmp.clone();
+            self.db
+                .read(move |db| {
+                    db.backup_to(&tmp)?;
+                    validate(&tmp)
+                })
+                .await
+        };
+        if let Err(e) = result {
+            let _ = fs::remove_file(&tmp);
+            return Err(e);
+        }
+
+        fs::rename(&tmp, &path).map_err(|e| AppError::io(path.display().to_string(), e))?;
+        self.info(&file_name)
+    }
+
+    /// Replace the live database with the backup at `path`, after checking
+    /// it and taking a pre-restore backup of the current state. Returns that
+    /// pre-restore backup so the restore can itself be undone.
+    pub async fn restore(&self, path: PathBuf) -> AppResult<BackupInfo> {
+        let checked = path.clone();
+        tokio::task::spawn_blocking(move || validate(&checked))
+            .await
+            .map_err(|e| AppError::internal(format!("backup validation failed: {}", e)))??;
+
+        let safety = self.create(BackupKind::PreRestore).await?;
+        self.db.restore(path).await?;
+        Ok(safety)
+    }
+
+    pub fn delete(&self, file_name: &str) -> AppResult<()> {
+        // Only ever touch files this module created
+        if parse_file_name(file_name).is_none() {
+            return Err(AppError::validation(
+                "file_name",
+                format!("{} is not a backup", file_name),
+            ));
+        }
+
+        let path = self.dir.join(file_name);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(AppError::not_found("backup", file_name))
+            }
+            Err(e) => Err(AppError::io(path.display().to_string(), e)),
+        }
+    }
+
+    /// Apply the retention rules to automatic and pre-restore backups and
+    /// return how many files were deleted.
+    pub fn prune(&self) -> AppResult<usize> {
+        let settings = self.settings()?;
+        let backups = self.list()?;
+        let now = Utc::now();
+        let mut deleted = 0;
+
+        for kind in [BackupKind::Auto, BackupKind::PreRestore] {
+            let of_kind = backups.iter().filter(|b| b.kind == kind.as_str());
+
+            for (index, backup) in of_kind.enumerate() {
+                let too_many = index >= settings.keep_last as usize;
+                let too_old = index > 0
+                    && settings.max_age_days.is_some_and(|days| {
+                        DateTime::parse_from_rfc3339(&backup.created_at)
+                            .map(|t| {
+                                now - t.with_timezone(&Utc) > chrono::Duration::days(days.into())
+                            })
+                            .unwrap_or(false)
+                    });
+
+                if too_many || too_old {
+                    self.delete(&backup.file_name)?;
+                    deleted += 1;
+                }
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    /// Take automatic backups for as long as the app runs.
+    pub async fn run_schedule(self) {
+        loop {
+            if let Err(e) = self.backup_if_due().await {
+                eprintln!("Automatic backup failed: {}", e);
+            }
+            tokio::time::sleep(SCHEDULE_TICK).await;
+        }
+    }
+
+    async fn backup_if_due(&self) -> AppResult<()> {
+        let settings = self.settings()?;
+        if !settings.enabled {
+            return Ok(());
+        }
+
+        let last = self
+            .list()?
+            .into_iter()
+            .find(|b| b.kind == BackupKind::Auto.as_str())
+            .and_then(|b| DateTime::parse_from_rfc3339(&b.created_at).ok());
+        let interval = chrono::Duration::hours(settings.interval_hours.into());
+
+        let due = match last {
+            Some(last) => Utc::now() - last.with_timezone(&Utc) >= interval,
+            None => true,
+        };
+        if due {
+            let backup = self.create(BackupKind::Auto).await?;
+            println!("Created automatic backup {}", backup.file_name);
+        }
+
+        self.prune()?;
+        Ok(())
+    }
+
+    fn info(&self, file_name: &str) -> AppResult<BackupInfo> {
+        self.list()?
+            .into_iter()
+            .find(|b| b.file_name == file_name)
+            .ok_or_else(|| AppError::not_found("backup", file_name))
+    }
+}
+
+fn parse_file_name(file_name: &str) -> Option<(DateTime<Utc>, BackupKind)> {
+    let rest = file_name
+        .strip_prefix(FILE_PREFIX)?
+        .strip_suffix(FILE_EXTENSION)?;
+
+    // The timestamp is fixed-width: YYYYMMDD-HHMMSS
+    let timestamp = rest.get(..15)?;
+    let kind = BackupKind::parse(rest.get(15..)?.strip_prefix('-')?)?;
+    let created_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT)
+        .ok()?
+        .and_utc();
+
+    Some((created_at, kind))
+}
+
+/// Check that `path` holds an intact database this build can open, returning
+/// its schema version. Older schemas are fine; they are migrated on restore.
+pub fn validate(path: &Path) -> AppResult<i64> {
+    let invalid = |message: String| AppError::validation("backup", message);
+
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| invalid(format!("cannot open {}: {}", path.display(), e)))?;
+
+    let problems = conn
+        .prepare("PRAGMA integrity_check")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .map_err(|e| invalid(format!("{} is not a database: {}", path.display(), e)))?;
+    if problems != ["ok"] {
+        return Err(invalid(format!(
+            "integrity check failed: {}",
+            problems.join("; ")
+        )));
+    }
+
+    let version = migrations::user_version(&conn)?;
+    if version > migrations::latest_version() {
+        return Err(AppError::SchemaTooNew {
+            found: version,
+            supported: migrations::latest_version(),
+        });
+    }
+
+    let has_playlists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'playlists')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_playlists {
+        return Err(invalid(format!(
+            "{} is not a playlists database",
+            path.display()
+        )));
+    }
+
+    Ok(version)
+}
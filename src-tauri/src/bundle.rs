@@ -0,0 +1,168 @@
+use crate::error::{AppError, AppResult};
+use crate::models::{ExportedLike, PlaylistExport, PlaylistItem};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Identifies our files so importing some unrelated JSON fails loudly.
+pub const BUNDLE_FORMAT: &str = "yttv-playlists";
+
+/// Bump when the bundle layout changes in a way older builds can't read.
+/// Adding optional fields doesn't need a bump.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Portable JSON file holding one or more playlists.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Bundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub playlists: Vec<PlaylistExport>,
+    /// Only written by library exports
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub likes: Vec<ExportedLike>,
+}
+
+impl Bundle {
+    pub fn new(playlists: Vec<PlaylistExport>) -> Self {
+        Bundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            exported_at: Utc::now().to_rfc3339(),
+            playlists,
+            likes: Vec::new(),
+        }
+    }
+
+    pub fn read(path: &Path) -> AppResult<Self> {
+        let json =
+            fs::read_to_string(path).map_err(|e| AppError::io(path.display().to_string(), e))?;
+        let bundle: Bundle = serde_json::from_str(&json)
+            .map_err(|e| AppError::validation("bundle", format!("not a valid bundle: {}", e)))?;
+
+        if bundle.format != BUNDLE_FORMAT {
+            return Err(AppError::validation(
+                "bundle",
+                format!("unknown bundle format '{}'", bundle.format),
+            ));
+        }
+        if bundle.version > BUNDLE_VERSION {
+            return Err(AppError::validation(
+                "bundle",
+                format!(
+                    "bundle version {} is newer than the latest supported version {}",
+                    bundle.version, BUNDLE_VERSION
+                ),
+            ));
+        }
+
+        Ok(bundle)
+    }
+
+    pub fn write(&self, path: &Path) -> AppResult<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::internal(format!("failed to serialize bundle: {}", e)))?;
+        fs::write(path, json).map_err(|e| AppError::io(path.display().to_string(), e))
+    }
+}
+
+/// Render the local-file items of a playlist as an extended M3U playlist.
+/// `durations` maps video ids to their length in seconds where known.
+pub fn render_m3u8(
+    playlist_name: &str,
+    items: &[PlaylistItem],
+    durations: &HashMap<String, f64>,
+) -> (String, usize) {
+    let mut out = format!("#EXTM3U\n#PLAYLIST:{}\n", single_line(playlist_name));
+    let mut count = 0;
+
+    for item in items.iter().filter(|item| item.is_local) {
+        let duration = durations
+            .get(&item.video_id)
+            .map(|d| d.round() as i64)
+            .unwrap_or(-1);
+        let title = item.title.as_deref().unwrap_or(&item.video_url);
+
+        out.push_str(&format!(
+            "#EXTINF:{},{}\n{}\n",
+            duration,
+            single_line(title),
+            item.video_url
+        ));
+        count += 1;
+    }
+
+    (out, count)
+}
+
+// A newline inside a title would start a new M3U entry
+fn single_line(s: &str) -> String {
+    s.replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("bundle-{}-{}.json", name, std::process::id()))
+    }
+
+    fn read_json(name: &str, json: &serde_json::Value) -> AppResult<Bundle> {
+        let path = temp_path(name);
+        fs::write(&path, json.to_string()).unwrap();
+        let bundle = Bundle::read(&path);
+        fs::remove_file(&path).unwrap();
+        bundle
+    }
+
+    fn assert_rejected(result: AppResult<Bundle>, expected: &str) {
+        match result {
+            Err(AppError::Validation { message, .. }) => {
+                assert!(message.contains(expected), "{}", message)
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn written_bundles_read_back() {
+        let path = temp_path("round-trip");
+        Bundle::new(Vec::new()).write(&path).unwrap();
+        let bundle = Bundle::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(bundle.format, BUNDLE_FORMAT);
+        assert_eq!(bundle.version, BUNDLE_VERSION);
+        assert!(bundle.playlists.is_empty());
+    }
+
+    #[test]
+    fn read_checks_format_and_version() {
+        let bundle = |format: &str, version: u32| {
+            serde_json::json!({
+                "format": format,
+                "version": version,
+                "exported_at": "2024-01-01T00:00:00Z",
+                "playlists": [],
+            })
+        };
+
+        assert!(read_json("older", &bundle(BUNDLE_FORMAT, 0)).is_ok());
+        assert!(read_json("current", &bundle(BUNDLE_FORMAT, BUNDLE_VERSION)).is_ok());
+        assert_rejected(
+            read_json("newer", &bundle(BUNDLE_FORMAT, BUNDLE_VERSION + 1)),
+            "is newer than the latest supported version",
+        );
+        assert_rejected(
+            read_json("foreign", &bundle("something-else", BUNDLE_VERSION)),
+            "unknown bundle format 'something-else'",
+        );
+        assert_rejected(
+            read_json("invalid", &serde_json::json!({ "playlists": [] })),
+            "not a valid bundle",
+        );
+    }
+}
@@ -1,77 +1,249 @@
 use crate::audio_capture::AudioCapture;
-use crate::database::Database;
+use crate::backup::{BackupKind, Backups};
+use crate::bundle::{self, Bundle};
+use crate::db_pool::DbPool;
+use crate::error::{AppError, AppResult};
 use crate::models::*;
+use crate::queue::RepeatMode;
+use crate::sorting::SortField;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 
 // Playlist commands
 #[tauri::command]
-pub fn create_playlist(
-    db: State<Mutex<Database>>,
+pub async fn create_playlist(
+    db: State<'_, DbPool>,
     name: String,
     description: Option<String>,
-) -> Result<i64, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.create_playlist(&name, description.as_deref())
-        .map_err(|e| e.to_string())
+) -> AppResult<i64> {
+    db.write(move |db| db.create_playlist(&name, description.as_deref()))
+        .await
+}
+
+#[tauri::command]
+pub async fn create_smart_playlist(
+    db: State<'_, DbPool>,
+    name: String,
+    description: Option<String>,
+    rules: SmartRules,
+) -> AppResult<i64> {
+    db.write(move |db| db.create_smart_playlist(&name, description.as_deref(), &rules))
+        .await
+}
+
+#[tauri::command]
+pub async fn update_smart_playlist_rules(
+    db: State<'_, DbPool>,
+    id: i64,
+    rules: SmartRules,
+) -> AppResult<bool> {
+    db.write(move |db| db.update_smart_playlist_rules(id, &rules))
+        .await
 }
 
 #[tauri::command]
-pub fn get_all_playlists(db: State<Mutex<Database>>) -> Result<Vec<Playlist>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_all_playlists().map_err(|e| e.to_string())
+pub async fn get_all_playlists(db: State<'_, DbPool>) -> AppResult<Vec<Playlist>> {
+    db.read(|db| db.get_all_playlists()).await
 }
 
 #[tauri::command]
-pub fn get_all_playlist_metadata(
-    db: State<Mutex<Database>>,
-) -> Result<Vec<PlaylistMetadata>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_all_playlist_metadata().map_err(|e| e.to_string())
+pub async fn get_all_playlist_metadata(db: State<'_, DbPool>) -> AppResult<Vec<PlaylistMetadata>> {
+    db.read(|db| db.get_all_playlist_metadata()).await
 }
 
 #[tauri::command]
-pub fn get_playlist(db: State<Mutex<Database>>, id: i64) -> Result<Option<Playlist>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_playlist(id).map_err(|e| e.to_string())
+pub async fn get_playlist(db: State<'_, DbPool>, id: i64) -> AppResult<Option<Playlist>> {
+    db.read(move |db| db.get_playlist(id)).await
 }
 
 #[tauri::command]
-pub fn update_playlist(
-    db: State<Mutex<Database>>,
+pub async fn update_playlist(
+    db: State<'_, DbPool>,
     id: i64,
     name: Option<String>,
     description: Option<String>,
     custom_ascii: Option<String>,
     custom_thumbnail_url: Option<String>,
-) -> Result<bool, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.update_playlist(
-        id,
-        name.as_deref(),
-        description.as_deref(),
-        custom_ascii.as_deref(),
-        custom_thumbnail_url.as_deref(),
-    )
-    .map_err(|e| e.to_string())
+) -> AppResult<bool> {
+    db.write(move |db| {
+        db.update_playlist(
+            id,
+            name.as_deref(),
+            description.as_deref(),
+            custom_ascii.as_deref(),
+            custom_thumbnail_url.as_deref(),
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_playlist(db: State<'_, DbPool>, id: i64) -> AppResult<bool> {
+    db.write(move |db| db.delete_playlist(id)).await
+}
+
+#[tauri::command]
+pub async fn delete_playlist_by_name(db: State<'_, DbPool>, name: String) -> AppResult<bool> {
+    db.write(move |db| db.delete_playlist_by_name(&name)).await
 }
 
+// Playlist group commands
 #[tauri::command]
-pub fn delete_playlist(db: State<Mutex<Database>>, id: i64) -> Result<bool, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.delete_playlist(id).map_err(|e| e.to_string())
+pub async fn create_playlist_group(
+    db: State<'_, DbPool>,
+    name: String,
+    parent_id: Option<i64>,
+) -> AppResult<PlaylistGroup> {
+    db.write(move |db| db.create_playlist_group(&name, parent_id))
+        .await
 }
 
 #[tauri::command]
-pub fn delete_playlist_by_name(db: State<Mutex<Database>>, name: String) -> Result<bool, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.delete_playlist_by_name(&name).map_err(|e| e.to_string())
+pub async fn get_playlist_groups(db: State<'_, DbPool>) -> AppResult<Vec<PlaylistGroup>> {
+    db.read(|db| db.get_playlist_groups()).await
+}
+
+#[tauri::command]
+pub async fn rename_playlist_group(
+    db: State<'_, DbPool>,
+    id: i64,
+    name: String,
+) -> AppResult<bool> {
+    db.write(move |db| db.rename_playlist_group(id, &name))
+        .await
+}
+
+#[tauri::command]
+pub async fn move_playlist_group(
+    db: State<'_, DbPool>,
+    id: i64,
+    parent_id: Option<i64>,
+    position: Option<i32>,
+) -> AppResult<PlaylistGroup> {
+    db.write(move |db| db.move_playlist_group(id, parent_id, position))
+        .await
+}
+
+#[tauri::command]
+pub async fn delete_playlist_group(db: State<'_, DbPool>, id: i64) -> AppResult<bool> {
+    db.write(move |db| db.delete_playlist_group(id)).await
+}
+
+#[tauri::command]
+pub async fn move_playlist_to_group(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    group_id: Option<i64>,
+    position: Option<i32>,
+) -> AppResult<bool> {
+    db.write(move |db| db.move_playlist_to_group(playlist_id, group_id, position))
+        .await
+}
+
+// Tab commands
+#[tauri::command]
+pub async fn get_tabs(db: State<'_, DbPool>) -> AppResult<Vec<Tab>> {
+    db.read(|db| db.get_tabs()).await
+}
+
+#[tauri::command]
+pub async fn create_tab(db: State<'_, DbPool>, name: String) -> AppResult<Tab> {
+    db.write(move |db| db.create_tab(&name)).await
+}
+
+#[tauri::command]
+pub async fn rename_tab(db: State<'_, DbPool>, tab_id: i64, name: String) -> AppResult<bool> {
+    db.write(move |db| db.rename_tab(tab_id, &name)).await
+}
+
+#[tauri::command]
+pub async fn move_tab(db: State<'_, DbPool>, tab_id: i64, position: i32) -> AppResult<Tab> {
+    db.write(move |db| db.move_tab(tab_id, position)).await
+}
+
+#[tauri::command]
+pub async fn delete_tab(db: State<'_, DbPool>, tab_id: i64) -> AppResult<bool> {
+    db.write(move |db| db.delete_tab(tab_id)).await
+}
+
+#[tauri::command]
+pub async fn add_playlist_to_tab(
+    db: State<'_, DbPool>,
+    tab_id: i64,
+    playlist_id: i64,
+    position: Option<i32>,
+) -> AppResult<Tab> {
+    db.write(move |db| db.add_playlist_to_tab(tab_id, playlist_id, position))
+        .await
+}
+
+#[tauri::command]
+pub async fn remove_playlist_from_tab(
+    db: State<'_, DbPool>,
+    tab_id: i64,
+    playlist_id: i64,
+) -> AppResult<bool> {
+    db.write(move |db| db.remove_playlist_from_tab(tab_id, playlist_id))
+        .await
+}
+
+#[tauri::command]
+pub async fn get_tab_presets(db: State<'_, DbPool>) -> AppResult<Vec<TabPreset>> {
+    db.read(|db| db.get_tab_presets()).await
+}
+
+#[tauri::command]
+pub async fn create_tab_preset(
+    db: State<'_, DbPool>,
+    name: String,
+    tab_ids: Option<Vec<i64>>,
+) -> AppResult<TabPreset> {
+    db.write(move |db| db.create_tab_preset(&name, &tab_ids.unwrap_or_default()))
+        .await
+}
+
+#[tauri::command]
+pub async fn update_tab_preset(
+    db: State<'_, DbPool>,
+    preset_id: i64,
+    name: Option<String>,
+    tab_ids: Option<Vec<i64>>,
+) -> AppResult<TabPreset> {
+    db.write(move |db| db.update_tab_preset(preset_id, name.as_deref(), tab_ids.as_deref()))
+        .await
+}
+
+#[tauri::command]
+pub async fn move_tab_preset(
+    db: State<'_, DbPool>,
+    preset_id: i64,
+    position: i32,
+) -> AppResult<TabPreset> {
+    db.write(move |db| db.move_tab_preset(preset_id, position))
+        .await
+}
+
+#[tauri::command]
+pub async fn delete_tab_preset(db: State<'_, DbPool>, preset_id: i64) -> AppResult<bool> {
+    db.write(move |db| db.delete_tab_preset(preset_id)).await
+}
+
+#[tauri::command]
+pub async fn import_local_tabs(
+    db: State<'_, DbPool>,
+    tabs_json: String,
+    presets_json: Option<String>,
+) -> AppResult<TabImportReport> {
+    db.write(move |db| db.import_local_tabs(&tabs_json, presets_json.as_deref()))
+        .await
 }
 
 // Playlist item commands
 #[tauri::command]
-pub fn add_video_to_playlist(
-    db: State<Mutex<Database>>,
+pub async fn add_video_to_playlist(
+    app: AppHandle,
+    db: State<'_, DbPool>,
     playlist_id: i64,
     video_url: String,
     video_id: String,
@@ -81,275 +253,971 @@ pub fn add_video_to_playlist(
     author: Option<String>,
     view_count: Option<String>,
     published_at: Option<String>,
-) -> Result<i64, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.add_video_to_playlist(
-        playlist_id,
-        &video_url,
-        &video_id,
-        title.as_deref(),
-        thumbnail_url.as_deref(),
-        is_local.unwrap_or(false),
-        author.as_deref(),
-        view_count.as_deref(),
-        published_at.as_deref(),
-    )
-    .map_err(|e| e.to_string())
+) -> AppResult<i64> {
+    let result = db
+        .write(move |db| {
+            db.add_video_to_playlist(
+                playlist_id,
+                &video_url,
+                &video_id,
+                title.as_deref(),
+                thumbnail_url.as_deref(),
+                is_local.unwrap_or(false),
+                author.as_deref(),
+                view_count.as_deref(),
+                published_at.as_deref(),
+            )
+        })
+        .await?;
+    emit_queue_changes(&app, &db).await;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn add_videos_to_playlist(
+    app: AppHandle,
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    items: Vec<NewPlaylistItem>,
+    skip_duplicates: Option<bool>,
+) -> AppResult<Vec<BulkAddResult>> {
+    let result = db
+        .write(move |db| {
+            db.add_videos_to_playlist(playlist_id, &items, skip_duplicates.unwrap_or(false))
+        })
+        .await?;
+    emit_queue_changes(&app, &db).await;
+    Ok(result)
 }
 
 #[tauri::command]
-pub fn get_playlist_items(
-    db: State<Mutex<Database>>,
+pub async fn get_playlist_items(
+    db: State<'_, DbPool>,
     playlist_id: i64,
-) -> Result<Vec<PlaylistItem>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_playlist_items(playlist_id)
-        .map_err(|e| e.to_string())
+    view_id: Option<i64>,
+) -> AppResult<Vec<PlaylistItem>> {
+    db.read(move |db| match view_id {
+        Some(view_id) => db.get_playlist_view_items(playlist_id, view_id),
+        None => db.get_playlist_items(playlist_id),
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn get_playlists_for_video_ids(
-    db: State<Mutex<Database>>,
+pub async fn get_playlist_items_page(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    query: Option<ItemPageQuery>,
+) -> AppResult<ItemPage> {
+    db.read(move |db| db.get_playlist_items_page(playlist_id, &query.unwrap_or_default()))
+        .await
+}
+
+#[tauri::command]
+pub async fn get_playlists_for_video_ids(
+    db: State<'_, DbPool>,
     video_ids: Vec<String>,
-) -> Result<std::collections::HashMap<String, Vec<String>>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_playlists_for_video_ids(&video_ids)
-        .map_err(|e| e.to_string())
+) -> AppResult<std::collections::HashMap<String, Vec<String>>> {
+    db.read(move |db| db.get_playlists_for_video_ids(&video_ids))
+        .await
 }
 
 #[tauri::command]
-pub fn remove_video_from_playlist(
-    db: State<Mutex<Database>>,
+pub async fn remove_video_from_playlist(
+    app: AppHandle,
+    db: State<'_, DbPool>,
     playlist_id: i64,
     item_id: i64,
-) -> Result<bool, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.remove_video_from_playlist(playlist_id, item_id)
-        .map_err(|e| e.to_string())
+) -> AppResult<bool> {
+    let result = db
+        .write(move |db| db.remove_video_from_playlist(playlist_id, item_id))
+        .await?;
+    emit_queue_changes(&app, &db).await;
+    Ok(result)
 }
 
 #[tauri::command]
-pub fn reorder_playlist_item(
-    db: State<Mutex<Database>>,
+pub async fn reorder_playlist_item(
+    app: AppHandle,
+    db: State<'_, DbPool>,
     playlist_id: i64,
     item_id: i64,
     new_position: i32,
-) -> Result<bool, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.reorder_playlist_item(playlist_id, item_id, new_position)
-        .map_err(|e| e.to_string())
+) -> AppResult<bool> {
+    let result = db
+        .write(move |db| db.reorder_playlist_item(playlist_id, item_id, new_position))
+        .await?;
+    emit_queue_changes(&app, &db).await;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn move_items(
+    app: AppHandle,
+    db: State<'_, DbPool>,
+    item_ids: Vec<i64>,
+    target_playlist_id: i64,
+    target_position: Option<i32>,
+    carry_folders: Option<bool>,
+) -> AppResult<Vec<PlaylistItem>> {
+    let result = db
+        .write(move |db| {
+            db.move_items(
+                &item_ids,
+                target_playlist_id,
+                target_position,
+                carry_folders.unwrap_or(true),
+            )
+        })
+        .await?;
+    emit_queue_changes(&app, &db).await;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn copy_items(
+    app: AppHandle,
+    db: State<'_, DbPool>,
+    item_ids: Vec<i64>,
+    target_playlist_id: i64,
+    target_position: Option<i32>,
+    carry_folders: Option<bool>,
+) -> AppResult<Vec<PlaylistItem>> {
+    let result = db
+        .write(move |db| {
+            db.copy_items(
+                &item_ids,
+                target_playlist_id,
+                target_position,
+                carry_folders.unwrap_or(true),
+            )
+        })
+        .await?;
+    emit_queue_changes(&app, &db).await;
+    Ok(result)
+}
+
+// Sorting commands
+#[tauri::command]
+pub async fn sort_playlist_items(
+    app: AppHandle,
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    sort_by: SortField,
+    descending: Option<bool>,
+) -> AppResult<Vec<PlaylistItem>> {
+    let result = db
+        .write(move |db| db.sort_playlist_items(playlist_id, sort_by, descending.unwrap_or(false)))
+        .await?;
+    emit_queue_changes(&app, &db).await;
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn save_playlist_view(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    name: String,
+    sort_by: SortField,
+    descending: Option<bool>,
+) -> AppResult<PlaylistView> {
+    db.write(move |db| {
+        db.save_playlist_view(playlist_id, &name, sort_by, descending.unwrap_or(false))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_playlist_views(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+) -> AppResult<Vec<PlaylistView>> {
+    db.read(move |db| db.get_playlist_views(playlist_id)).await
+}
+
+#[tauri::command]
+pub async fn delete_playlist_view(db: State<'_, DbPool>, view_id: i64) -> AppResult<bool> {
+    db.write(move |db| db.delete_playlist_view(view_id)).await
+}
+
+#[tauri::command]
+pub async fn repair_playlist_positions(
+    db: State<'_, DbPool>,
+    playlist_id: Option<i64>,
+) -> AppResult<usize> {
+    db.write(move |db| db.repair_playlist_positions(playlist_id))
+        .await
+}
+
+// Folder commands
+#[tauri::command]
+pub async fn get_folders(db: State<'_, DbPool>) -> AppResult<Vec<Folder>> {
+    db.read(|db| db.get_folders()).await
+}
+
+#[tauri::command]
+pub async fn create_folder(
+    db: State<'_, DbPool>,
+    color_key: String,
+    name: String,
+    hex: String,
+) -> AppResult<Folder> {
+    db.write(move |db| db.create_folder(&color_key, &name, &hex))
+        .await
+}
+
+#[tauri::command]
+pub async fn update_folder(
+    db: State<'_, DbPool>,
+    color_key: String,
+    name: Option<String>,
+    hex: Option<String>,
+) -> AppResult<Folder> {
+    db.write(move |db| db.update_folder(&color_key, name.as_deref(), hex.as_deref()))
+        .await
+}
+
+#[tauri::command]
+pub async fn move_folder(
+    db: State<'_, DbPool>,
+    color_key: String,
+    position: i32,
+) -> AppResult<Folder> {
+    db.write(move |db| db.move_folder(&color_key, position))
+        .await
+}
+
+#[tauri::command]
+pub async fn delete_folder(db: State<'_, DbPool>, color_key: String) -> AppResult<bool> {
+    db.write(move |db| db.delete_folder(&color_key)).await
+}
+
+// Global folder commands
+#[tauri::command]
+pub async fn get_global_folders(db: State<'_, DbPool>) -> AppResult<Vec<GlobalFolder>> {
+    db.read(|db| db.get_global_folders()).await
+}
+
+#[tauri::command]
+pub async fn create_global_folder(
+    db: State<'_, DbPool>,
+    name: String,
+    description: Option<String>,
+    folder_color: Option<String>,
+) -> AppResult<GlobalFolder> {
+    db.write(move |db| {
+        db.create_global_folder(&name, description.as_deref(), folder_color.as_deref())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn update_global_folder(
+    db: State<'_, DbPool>,
+    global_folder_id: i64,
+    name: Option<String>,
+    description: Option<String>,
+    folder_color: Option<String>,
+) -> AppResult<GlobalFolder> {
+    db.write(move |db| {
+        db.update_global_folder(
+            global_folder_id,
+            name.as_deref(),
+            description.as_deref(),
+            folder_color.as_deref(),
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_global_folder(db: State<'_, DbPool>, global_folder_id: i64) -> AppResult<bool> {
+    db.write(move |db| db.delete_global_folder(global_folder_id))
+        .await
+}
+
+#[tauri::command]
+pub async fn get_global_folder_items(
+    db: State<'_, DbPool>,
+    global_folder_id: i64,
+) -> AppResult<Vec<PlaylistItem>> {
+    db.read(move |db| db.get_global_folder_items(global_folder_id))
+        .await
+}
+
+#[tauri::command]
+pub async fn add_items_to_global_folder(
+    db: State<'_, DbPool>,
+    global_folder_id: i64,
+    item_ids: Vec<i64>,
+) -> AppResult<GlobalFolder> {
+    db.write(move |db| db.add_items_to_global_folder(global_folder_id, &item_ids))
+        .await
+}
+
+#[tauri::command]
+pub async fn remove_items_from_global_folder(
+    db: State<'_, DbPool>,
+    global_folder_id: i64,
+    item_ids: Vec<i64>,
+) -> AppResult<GlobalFolder> {
+    db.write(move |db| db.remove_items_from_global_folder(global_folder_id, &item_ids))
+        .await
+}
+
+#[tauri::command]
+pub async fn get_global_folders_for_items(
+    db: State<'_, DbPool>,
+    item_ids: Vec<i64>,
+) -> AppResult<std::collections::HashMap<i64, Vec<i64>>> {
+    db.read(move |db| db.get_global_folders_for_items(&item_ids))
+        .await
 }
 
 // Folder assignment commands
 #[tauri::command]
-pub fn assign_video_to_folder(
-    db: State<Mutex<Database>>,
+pub async fn assign_video_to_folder(
+    db: State<'_, DbPool>,
     playlist_id: i64,
     item_id: i64,
     folder_color: String,
-) -> Result<i64, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.assign_video_to_folder(playlist_id, item_id, &folder_color)
-        .map_err(|e| e.to_string())
+) -> AppResult<i64> {
+    db.write(move |db| db.assign_video_to_folder(playlist_id, item_id, &folder_color))
+        .await
 }
 
 #[tauri::command]
-pub fn unassign_video_from_folder(
-    db: State<Mutex<Database>>,
+pub async fn unassign_video_from_folder(
+    db: State<'_, DbPool>,
     playlist_id: i64,
     item_id: i64,
     folder_color: String,
-) -> Result<bool, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.unassign_video_from_folder(playlist_id, item_id, &folder_color)
-        .map_err(|e| e.to_string())
+) -> AppResult<bool> {
+    db.write(move |db| db.unassign_video_from_folder(playlist_id, item_id, &folder_color))
+        .await
 }
 
 #[tauri::command]
-pub fn get_videos_in_folder(
-    db: State<Mutex<Database>>,
+pub async fn get_videos_in_folder(
+    db: State<'_, DbPool>,
     playlist_id: i64,
     folder_color: String,
-) -> Result<Vec<PlaylistItem>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_videos_in_folder(playlist_id, &folder_color)
-        .map_err(|e| e.to_string())
+) -> AppResult<Vec<PlaylistItem>> {
+    db.read(move |db| db.get_videos_in_folder(playlist_id, &folder_color))
+        .await
 }
 
 #[tauri::command]
-pub fn get_all_folder_assignments(
-    db: State<Mutex<Database>>,
+pub async fn get_all_folder_assignments(
+    db: State<'_, DbPool>,
     playlist_id: i64,
-) -> Result<std::collections::HashMap<String, Vec<String>>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_all_folder_assignments_for_playlist(playlist_id)
-        .map_err(|e| e.to_string())
+) -> AppResult<std::collections::HashMap<String, Vec<String>>> {
+    db.read(move |db| db.get_all_folder_assignments_for_playlist(playlist_id))
+        .await
 }
 
 #[tauri::command]
-pub fn get_video_folder_assignments(
-    db: State<Mutex<Database>>,
+pub async fn get_video_folder_assignments(
+    db: State<'_, DbPool>,
     playlist_id: i64,
     item_id: i64,
-) -> Result<Vec<String>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_video_folder_assignments(playlist_id, item_id)
-        .map_err(|e| e.to_string())
+) -> AppResult<Vec<String>> {
+    db.read(move |db| db.get_video_folder_assignments(playlist_id, item_id))
+        .await
+}
+
+#[tauri::command]
+pub async fn get_all_folders_with_videos(
+    db: State<'_, DbPool>,
+) -> AppResult<Vec<FolderWithVideos>> {
+    db.read(|db| db.get_all_folders_with_videos()).await
+}
+
+#[tauri::command]
+pub async fn get_folders_for_playlist(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+) -> AppResult<Vec<FolderWithVideos>> {
+    db.read(move |db| db.get_folders_for_playlist(playlist_id))
+        .await
+}
+
+// Folder bulk commands
+#[tauri::command]
+pub async fn move_folder_items(
+    app: AppHandle,
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    folder_color: String,
+    target_playlist_id: i64,
+    target_position: Option<i32>,
+) -> AppResult<Vec<PlaylistItem>> {
+    let result = db
+        .write(move |db| {
+            db.move_folder_items(
+                playlist_id,
+                &folder_color,
+                target_playlist_id,
+                target_position,
+            )
+        })
+        .await?;
+    emit_queue_changes(&app, &db).await;
+    Ok(result)
 }
 
 #[tauri::command]
-pub fn get_all_folders_with_videos(
-    db: State<Mutex<Database>>,
-) -> Result<Vec<FolderWithVideos>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_all_folders_with_videos().map_err(|e| e.to_string())
+pub async fn copy_folder_to_new_playlist(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    folder_color: String,
+    name: Option<String>,
+) -> AppResult<i64> {
+    db.write(move |db| db.copy_folder_to_new_playlist(playlist_id, &folder_color, name.as_deref()))
+        .await
+}
+
+#[tauri::command]
+pub async fn recolor_folder(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    folder_color: String,
+    new_folder_color: String,
+) -> AppResult<Vec<PlaylistFolderState>> {
+    db.write(move |db| db.recolor_folder(playlist_id, &folder_color, &new_folder_color))
+        .await
 }
 
 #[tauri::command]
-pub fn get_folders_for_playlist(
-    db: State<Mutex<Database>>,
+pub async fn merge_folders(
+    db: State<'_, DbPool>,
     playlist_id: i64,
-) -> Result<Vec<FolderWithVideos>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_folders_for_playlist(playlist_id)
-        .map_err(|e| e.to_string())
+    folder_color: String,
+    into_folder_color: String,
+) -> AppResult<Vec<PlaylistFolderState>> {
+    db.write(move |db| db.merge_folders(playlist_id, &folder_color, &into_folder_color))
+        .await
+}
+
+#[tauri::command]
+pub async fn clear_folder(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    folder_color: String,
+) -> AppResult<usize> {
+    db.write(move |db| db.clear_folder(playlist_id, &folder_color))
+        .await
 }
 
 // Stuck folders commands
 #[tauri::command]
-pub fn toggle_stuck_folder(
-    db: State<Mutex<Database>>,
+pub async fn toggle_stuck_folder(
+    db: State<'_, DbPool>,
     playlist_id: i64,
     folder_color: String,
-) -> Result<bool, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.toggle_stuck_folder(playlist_id, &folder_color)
-        .map_err(|e| e.to_string())
+) -> AppResult<bool> {
+    db.write(move |db| db.toggle_stuck_folder(playlist_id, &folder_color))
+        .await
 }
 
 #[tauri::command]
-pub fn is_folder_stuck(
-    db: State<Mutex<Database>>,
+pub async fn is_folder_stuck(
+    db: State<'_, DbPool>,
     playlist_id: i64,
     folder_color: String,
-) -> Result<bool, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.is_folder_stuck(playlist_id, &folder_color)
-        .map_err(|e| e.to_string())
+) -> AppResult<bool> {
+    db.read(move |db| db.is_folder_stuck(playlist_id, &folder_color))
+        .await
+}
+
+#[tauri::command]
+pub async fn get_all_stuck_folders(db: State<'_, DbPool>) -> AppResult<Vec<(i64, String)>> {
+    db.read(|db| db.get_all_stuck_folders()).await
 }
 
+// Folder visibility commands
 #[tauri::command]
-pub fn get_all_stuck_folders(db: State<Mutex<Database>>) -> Result<Vec<(i64, String)>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_all_stuck_folders().map_err(|e| e.to_string())
+pub async fn get_playlist_folder_states(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+) -> AppResult<Vec<PlaylistFolderState>> {
+    db.read(move |db| db.get_playlist_folder_states(playlist_id))
+        .await
+}
+
+#[tauri::command]
+pub async fn set_folder_visibility(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    folder_color: String,
+    pinned: Option<bool>,
+    hidden: Option<bool>,
+    collapsed: Option<bool>,
+) -> AppResult<FolderVisibility> {
+    db.write(move |db| {
+        db.set_folder_visibility(playlist_id, &folder_color, pinned, hidden, collapsed)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_folder_order(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    folder_colors: Vec<String>,
+) -> AppResult<Vec<PlaylistFolderState>> {
+    db.write(move |db| db.set_folder_order(playlist_id, &folder_colors))
+        .await
 }
 
 // Folder Metadata commands
 #[tauri::command]
-pub fn get_folder_metadata(
-    db: State<Mutex<Database>>,
+pub async fn get_folder_metadata(
+    db: State<'_, DbPool>,
     playlist_id: i64,
     folder_color: String,
-) -> Result<Option<(String, String, Option<String>)>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_folder_metadata(playlist_id, &folder_color)
-        .map_err(|e| e.to_string())
+) -> AppResult<Option<FolderMetadata>> {
+    db.read(move |db| db.get_folder_metadata(playlist_id, &folder_color))
+        .await
 }
 
 #[tauri::command]
-pub fn set_folder_metadata(
-    db: State<Mutex<Database>>,
+pub async fn set_folder_metadata(
+    db: State<'_, DbPool>,
     playlist_id: i64,
     folder_color: String,
     name: Option<String>,
     description: Option<String>,
     custom_ascii: Option<String>,
-) -> Result<bool, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.set_folder_metadata(
-        playlist_id,
-        &folder_color,
-        name.as_deref(),
-        description.as_deref(),
-        custom_ascii.as_deref(),
-    )
-    .map_err(|e| e.to_string())
+) -> AppResult<bool> {
+    db.write(move |db| {
+        db.set_folder_metadata(
+            playlist_id,
+            &folder_color,
+            name.as_deref(),
+            description.as_deref(),
+            custom_ascii.as_deref(),
+        )
+    })
+    .await
 }
 
 // Watch history commands
 #[tauri::command]
-pub fn add_to_watch_history(
-    db: State<Mutex<Database>>,
+pub async fn add_to_watch_history(
+    db: State<'_, DbPool>,
     video_url: String,
     video_id: String,
     title: Option<String>,
     thumbnail_url: Option<String>,
-) -> Result<i64, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.add_to_watch_history(
-        &video_url,
-        &video_id,
-        title.as_deref(),
-        thumbnail_url.as_deref(),
-    )
-    .map_err(|e| e.to_string())
+) -> AppResult<i64> {
+    db.write(move |db| {
+        db.add_to_watch_history(
+            &video_url,
+            &video_id,
+            title.as_deref(),
+            thumbnail_url.as_deref(),
+        )
+    })
+    .await
 }
 
 #[tauri::command]
-pub fn get_watch_history(
-    db: State<Mutex<Database>>,
+pub async fn get_watch_history(
+    db: State<'_, DbPool>,
     limit: i32,
-) -> Result<Vec<crate::models::WatchHistory>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_watch_history(limit).map_err(|e| e.to_string())
+) -> AppResult<Vec<crate::models::WatchHistory>> {
+    db.read(move |db| db.get_watch_history(limit)).await
 }
 
 #[tauri::command]
-pub fn clear_watch_history(db: State<Mutex<Database>>) -> Result<bool, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.clear_watch_history().map_err(|e| e.to_string())
+pub async fn clear_watch_history(db: State<'_, DbPool>) -> AppResult<bool> {
+    db.write(|db| db.clear_watch_history()).await
 }
 
 #[tauri::command]
-pub fn get_watched_video_ids(db: State<Mutex<Database>>) -> Result<Vec<String>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_watched_video_ids().map_err(|e| e.to_string())
+pub async fn get_watched_video_ids(db: State<'_, DbPool>) -> AppResult<Vec<String>> {
+    db.read(|db| db.get_watched_video_ids()).await
 }
 
 // Video progress commands
 #[tauri::command]
-pub fn update_video_progress(
-    db: State<Mutex<Database>>,
+pub async fn update_video_progress(
+    db: State<'_, DbPool>,
     video_id: String,
     video_url: String,
     duration: Option<f64>,
     current_time: f64,
-) -> Result<i64, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.update_video_progress(&video_id, &video_url, duration, current_time)
-        .map_err(|e| e.to_string())
+) -> AppResult<i64> {
+    db.write(move |db| db.update_video_progress(&video_id, &video_url, duration, current_time))
+        .await
 }
 
 #[tauri::command]
-pub fn get_video_progress(
-    db: State<Mutex<Database>>,
+pub async fn get_video_progress(
+    db: State<'_, DbPool>,
     video_id: String,
-) -> Result<Option<crate::models::VideoProgress>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_video_progress(&video_id).map_err(|e| e.to_string())
+) -> AppResult<Option<crate::models::VideoProgress>> {
+    db.read(move |db| db.get_video_progress(&video_id)).await
+}
+
+#[tauri::command]
+pub async fn get_all_video_progress(
+    db: State<'_, DbPool>,
+) -> AppResult<Vec<crate::models::VideoProgress>> {
+    db.read(|db| db.get_all_video_progress()).await
+}
+
+// Undo / redo commands
+#[tauri::command]
+pub async fn undo_last(db: State<'_, DbPool>) -> AppResult<Option<JournalEntry>> {
+    db.write(|db| db.undo_last()).await
+}
+
+#[tauri::command]
+pub async fn redo(db: State<'_, DbPool>) -> AppResult<Option<JournalEntry>> {
+    db.write(|db| db.redo()).await
+}
+
+#[tauri::command]
+pub async fn get_operation_journal(
+    db: State<'_, DbPool>,
+    limit: Option<u32>,
+) -> AppResult<Vec<JournalEntry>> {
+    db.read(move |db| db.get_operation_journal(limit.unwrap_or(50)))
+        .await
+}
+
+// Trash commands
+#[tauri::command]
+pub async fn list_trash(db: State<'_, DbPool>) -> AppResult<Vec<TrashEntry>> {
+    db.read(|db| db.list_trash()).await
+}
+
+#[tauri::command]
+pub async fn restore_from_trash(db: State<'_, DbPool>, trash_id: i64) -> AppResult<bool> {
+    db.write(move |db| db.restore_from_trash(trash_id)).await
+}
+
+#[tauri::command]
+pub async fn purge_trash(db: State<'_, DbPool>, older_than_days: u32) -> AppResult<usize> {
+    db.write(move |db| db.purge_trash(older_than_days)).await
+}
+
+// Pin commands
+#[tauri::command]
+pub async fn get_pins(db: State<'_, DbPool>) -> AppResult<Vec<Pin>> {
+    db.read(|db| db.get_pins()).await
+}
+
+#[tauri::command]
+pub async fn pin_video(db: State<'_, DbPool>, video: VideoRequest) -> AppResult<Pin> {
+    db.write(move |db| db.pin_video(&video)).await
+}
+
+#[tauri::command]
+pub async fn set_priority_pin(
+    db: State<'_, DbPool>,
+    video: VideoRequest,
+    priority: bool,
+) -> AppResult<Pin> {
+    db.write(move |db| db.set_priority_pin(&video, priority))
+        .await
+}
+
+#[tauri::command]
+pub async fn unpin_video(db: State<'_, DbPool>, video_id: String) -> AppResult<bool> {
+    db.write(move |db| db.unpin_video(&video_id)).await
+}
+
+// Like commands
+#[tauri::command]
+pub async fn get_likes(db: State<'_, DbPool>) -> AppResult<Vec<Like>> {
+    db.read(|db| db.get_likes()).await
+}
+
+#[tauri::command]
+pub async fn like_video(db: State<'_, DbPool>, video: VideoRequest) -> AppResult<Like> {
+    db.write(move |db| db.like_video(&video)).await
+}
+
+#[tauri::command]
+pub async fn unlike_video(db: State<'_, DbPool>, video_id: String) -> AppResult<bool> {
+    db.write(move |db| db.unlike_video(&video_id)).await
+}
+
+#[tauri::command]
+pub async fn get_likes_for_video_ids(
+    db: State<'_, DbPool>,
+    video_ids: Vec<String>,
+) -> AppResult<std::collections::HashMap<String, String>> {
+    db.read(move |db| db.get_likes_for_video_ids(&video_ids))
+        .await
+}
+
+// Playback queue commands
+#[tauri::command]
+pub async fn get_playback_queue(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+) -> AppResult<PlaybackQueue> {
+    db.read(move |db| db.get_playback_queue(playlist_id)).await
+}
+
+#[tauri::command]
+pub async fn queue_next(
+    app: AppHandle,
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    auto_advance: Option<bool>,
+) -> AppResult<PlaybackQueue> {
+    let queue = db
+        .write(move |db| db.queue_next(playlist_id, auto_advance.unwrap_or(false)))
+        .await?;
+    emit_queue_changed(&app, &queue);
+    Ok(queue)
+}
+
+#[tauri::command]
+pub async fn queue_previous(
+    app: AppHandle,
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+) -> AppResult<PlaybackQueue> {
+    let queue = db.write(move |db| db.queue_previous(playlist_id)).await?;
+    emit_queue_changed(&app, &queue);
+    Ok(queue)
+}
+
+#[tauri::command]
+pub async fn queue_jump(
+    app: AppHandle,
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    item_id: i64,
+) -> AppResult<PlaybackQueue> {
+    let queue = db
+        .write(move |db| db.queue_jump(playlist_id, item_id))
+        .await?;
+    emit_queue_changed(&app, &queue);
+    Ok(queue)
+}
+
+#[tauri::command]
+pub async fn reshuffle_queue(
+    app: AppHandle,
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+) -> AppResult<PlaybackQueue> {
+    let queue = db.write(move |db| db.reshuffle_queue(playlist_id)).await?;
+    emit_queue_changed(&app, &queue);
+    Ok(queue)
+}
+
+#[tauri::command]
+pub async fn set_queue_shuffle(
+    app: AppHandle,
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    shuffled: bool,
+) -> AppResult<PlaybackQueue> {
+    let queue = db
+        .write(move |db| db.set_queue_shuffle(playlist_id, shuffled))
+        .await?;
+    emit_queue_changed(&app, &queue);
+    Ok(queue)
+}
+
+#[tauri::command]
+pub async fn set_queue_repeat(
+    app: AppHandle,
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    repeat_mode: RepeatMode,
+) -> AppResult<PlaybackQueue> {
+    let queue = db
+        .write(move |db| db.set_queue_repeat(playlist_id, repeat_mode))
+        .await?;
+    emit_queue_changed(&app, &queue);
+    Ok(queue)
+}
+
+fn emit_queue_changed(app: &AppHandle, queue: &PlaybackQueue) {
+    if let Err(e) = app.emit("playback-queue-changed", queue) {
+        eprintln!("Failed to emit playback queue change: {}", e);
+    }
+}
+
+// Item changes can move a queue's current item or add to its end, in smart
+// playlists as well, so every stored queue is re-sent.
+async fn emit_queue_changes(app: &AppHandle, db: &DbPool) {
+    match db.read(|db| db.get_playback_queues()).await {
+        Ok(queues) => {
+            for queue in &queues {
+                emit_queue_changed(app, queue);
+            }
+        }
+        Err(e) => eprintln!("Failed to read playback queues: {}", e),
+    }
+}
+
+// Video metadata commands
+#[tauri::command]
+pub async fn get_video(db: State<'_, DbPool>, video_id: String) -> AppResult<Option<Video>> {
+    db.read(move |db| db.get_video(&video_id)).await
+}
+
+#[tauri::command]
+pub async fn update_video_metadata(
+    db: State<'_, DbPool>,
+    video_id: String,
+    title: Option<String>,
+    thumbnail_url: Option<String>,
+    author: Option<String>,
+    view_count: Option<String>,
+    published_at: Option<String>,
+) -> AppResult<bool> {
+    db.write(move |db| {
+        db.update_video_metadata(
+            &video_id,
+            title.as_deref(),
+            thumbnail_url.as_deref(),
+            author.as_deref(),
+            view_count.as_deref(),
+            published_at.as_deref(),
+        )
+    })
+    .await
+}
+
+// Export / import commands
+#[tauri::command]
+pub async fn export_playlist(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    path: String,
+) -> AppResult<()> {
+    db.read(move |db| {
+        let export = db.export_playlist(playlist_id)?;
+        Bundle::new(vec![export]).write(Path::new(&path))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn export_library(db: State<'_, DbPool>, path: String) -> AppResult<usize> {
+    db.read(move |db| {
+        let mut bundle = Bundle::new(db.export_library()?);
+        bundle.likes = db.export_likes()?;
+        bundle.write(Path::new(&path))?;
+        Ok(bundle.playlists.len())
+    })
+    .await
+}
+
+/// Writes the playlist's local files as an M3U8 playlist and returns how many
+/// entries were written.
+#[tauri::command]
+pub async fn export_playlist_m3u8(
+    db: State<'_, DbPool>,
+    playlist_id: i64,
+    path: String,
+) -> AppResult<usize> {
+    db.read(move |db| {
+        let playlist = db
+            .get_playlist(playlist_id)?
+            .ok_or_else(|| AppError::not_found("playlist", playlist_id))?;
+        let items = db.get_playlist_items(playlist_id)?;
+        let durations = db
+            .get_all_video_progress()?
+            .into_iter()
+            .filter_map(|p| p.duration.map(|d| (p.video_id, d)))
+            .collect();
+
+        let (m3u8, count) = bundle::render_m3u8(&playlist.name, &items, &durations);
+        std::fs::write(&path, m3u8).map_err(|e| AppError::io(path.clone(), e))?;
+        Ok(count)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn import_playlist(db: State<'_, DbPool>, path: String) -> AppResult<ImportReport> {
+    db.write(move |db| {
+        let bundle = Bundle::read(Path::new(&path))?;
+        let [export] = <[PlaylistExport; 1]>::try_from(bundle.playlists).map_err(|playlists| {
+            AppError::validation(
+                "bundle",
+                format!(
+                    "expected a single playlist but the bundle has {}; import it as a library instead",
+                    playlists.len()
+                ),
+            )
+        })?;
+        db.import_playlist(&export)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn import_library(db: State<'_, DbPool>, path: String) -> AppResult<Vec<ImportReport>> {
+    db.write(move |db| {
+        let bundle = Bundle::read(Path::new(&path))?;
+        db.import_library(&bundle.playlists, &bundle.likes)
+    })
+    .await
+}
+
+// Backup commands
+#[tauri::command]
+pub async fn create_backup(backups: State<'_, Backups>) -> AppResult<BackupInfo> {
+    backups.create(BackupKind::Manual).await
+}
+
+#[tauri::command]
+pub async fn list_backups(backups: State<'_, Backups>) -> AppResult<Vec<BackupInfo>> {
+    backups.list()
+}
+
+/// Returns the backup of the previous state that was taken before restoring.
+#[tauri::command]
+pub async fn restore_backup(backups: State<'_, Backups>, path: String) -> AppResult<BackupInfo> {
+    backups.restore(PathBuf::from(path)).await
+}
+
+#[tauri::command]
+pub async fn delete_backup(backups: State<'_, Backups>, file_name: String) -> AppResult<()> {
+    backups.delete(&file_name)
+}
+
+#[tauri::command]
+pub async fn get_backup_settings(backups: State<'_, Backups>) -> AppResult<BackupSettings> {
+    backups.settings()
+}
+
+#[tauri::command]
+pub async fn set_backup_settings(
+    backups: State<'_, Backups>,
+    settings: BackupSettings,
+) -> AppResult<()> {
+    backups.set_settings(settings)?;
+    backups.prune()?;
+    Ok(())
 }
 
+// Search commands
 #[tauri::command]
-pub fn get_all_video_progress(
-    db: State<Mutex<Database>>,
-) -> Result<Vec<crate::models::VideoProgress>, String> {
-    let db = db.lock().map_err(|e| e.to_string())?;
-    db.get_all_video_progress().map_err(|e| e.to_string())
+pub async fn search_library(
+    db: State<'_, DbPool>,
+    query: String,
+    limit: Option<u32>,
+) -> AppResult<Vec<SearchHit>> {
+    db.read(move |db| db.search_library(&query, limit)).await
 }
 
 // Local video file commands
 #[tauri::command]
-pub async fn select_video_files(app: tauri::AppHandle) -> Result<Option<Vec<String>>, String> {
+pub async fn select_video_files(app: tauri::AppHandle) -> AppResult<Option<Vec<String>>> {
     use tauri::async_runtime;
     use tauri_plugin_dialog::DialogExt;
 
@@ -370,7 +1238,7 @@ pub async fn select_video_files(app: tauri::AppHandle) -> Result<Option<Vec<Stri
     let file_paths = rx
         .recv()
         .await
-        .ok_or_else(|| "Failed to receive file paths".to_string())?;
+        .ok_or_else(|| AppError::internal("Failed to receive file paths"))?;
 
     match file_paths {
         Some(paths) => {
@@ -382,26 +1250,25 @@ pub async fn select_video_files(app: tauri::AppHandle) -> Result<Option<Vec<Stri
 }
 
 #[tauri::command]
-pub async fn read_video_file(file_path: String) -> Result<Vec<u8>, String> {
+pub async fn read_video_file(file_path: String) -> AppResult<Vec<u8>> {
     use std::fs;
 
     // Read the file as binary data
-    fs::read(&file_path).map_err(|e| format!("Failed to read video file: {}", e))
+    fs::read(&file_path).map_err(|e| AppError::io(file_path.clone(), e))
 }
 
 #[tauri::command]
-pub fn get_video_stream_url(file_path: String, app: tauri::AppHandle) -> Result<String, String> {
+pub fn get_video_stream_url(file_path: String, app: tauri::AppHandle) -> AppResult<String> {
     use crate::streaming_server::StreamingServer;
     use std::sync::Arc;
 
     // All videos use streaming server for consistent range request support
     let server = app
         .try_state::<Arc<StreamingServer>>()
-        .ok_or("Streaming server not initialized")?;
+        .ok_or_else(|| AppError::internal("Streaming server not initialized"))?;
 
     // Get streaming URL for the file
-    let stream_url = server.get_stream_url(&file_path);
-    Ok(stream_url)
+    server.get_stream_url(&file_path)
 }
 
 // Audio capture commands
@@ -415,11 +1282,9 @@ pub fn test_audio_command() -> String {
 }
 
 #[tauri::command]
-pub fn start_audio_capture(app: AppHandle) -> Result<(), String> {
+pub fn start_audio_capture(app: AppHandle) -> AppResult<()> {
     eprintln!("[Commands] start_audio_capture called!");
-    let mut capture = AUDIO_CAPTURE
-        .lock()
-        .map_err(|e| format!("Lock error: {}", e))?;
+    let mut capture = AUDIO_CAPTURE.lock()?;
 
     if capture.is_none() {
         eprintln!("[Commands] Creating new AudioCapture instance");
@@ -431,9 +1296,8 @@ pub fn start_audio_capture(app: AppHandle) -> Result<(), String> {
     eprintln!("[Commands] Starting audio capture...");
     let result = capture
         .as_mut()
-        .ok_or("Failed to create audio capture")?
-        .start(app)
-        .map_err(|e| format!("Failed to start audio capture: {}", e));
+        .ok_or_else(|| AppError::audio("Failed to create audio capture"))?
+        .start(app);
 
     match &result {
         Ok(_) => eprintln!("[Commands] start_audio_capture succeeded"),
@@ -444,17 +1308,13 @@ pub fn start_audio_capture(app: AppHandle) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn stop_audio_capture() -> Result<(), String> {
+pub fn stop_audio_capture() -> AppResult<()> {
     eprintln!("[Commands] stop_audio_capture called!");
-    let mut capture = AUDIO_CAPTURE
-        .lock()
-        .map_err(|e| format!("Lock error: {}", e))?;
+    let mut capture = AUDIO_CAPTURE.lock()?;
 
     if let Some(ref mut cap) = capture.as_mut() {
         eprintln!("[Commands] Stopping audio capture...");
-        let result = cap
-            .stop()
-            .map_err(|e| format!("Failed to stop audio capture: {}", e));
+        let result = cap.stop();
         match &result {
             Ok(_) => eprintln!("[Commands] stop_audio_capture succeeded"),
             Err(e) => eprintln!("[Commands] stop_audio_capture failed: {}", e),
@@ -462,6 +1322,6 @@ pub fn stop_audio_capture() -> Result<(), String> {
         result
     } else {
         eprintln!("[Commands] stop_audio_capture: Audio capture not running");
-        Err("Audio capture not running".to_string())
+        Err(AppError::conflict("Audio capture not running"))
     }
 }
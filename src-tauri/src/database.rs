@@ -1,257 +1,202 @@
-use crate::models::{Playlist, PlaylistItem, VideoProgress, WatchHistory};
+use crate::error::{AppError, AppResult};
+use crate::journal::JournalOp;
+use crate::migrations;
+use crate::models::{
+    BulkAddResult, ExportedFolder, ExportedItem, ExportedLike, FolderMetadata, ImportReport,
+    NewPlaylistItem, Playlist, PlaylistExport, PlaylistItem, SearchHit, SkippedItem, Video,
+    VideoProgress, VideoRequest, WatchHistory,
+};
+use crate::smart::{smart_rules_from_column, smart_rules_to_json};
 use chrono::Utc;
-use rusqlite::{params, Connection, Result};
-use std::collections::HashMap;
+use rusqlite::backup::{Backup, StepResult};
+use rusqlite::{params, Connection, OpenFlags};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const DEFAULT_SEARCH_LIMIT: u32 = 50;
+
+// Every PlaylistItem query selects these columns, in the order
+// playlist_item_from_row expects; metadata lives on the shared videos row.
+pub(crate) const ITEM_SELECT: &str =
+    "SELECT pi.id, pi.playlist_id, v.video_url, pi.video_id, v.title, v.thumbnail_url,
+        pi.position, pi.added_at, v.is_local, v.author, v.view_count, v.published_at
+     FROM playlist_items pi
+     INNER JOIN videos v ON v.video_id = pi.video_id";
+
+// The videos columns video_from_row expects, qualified with `v`
+pub(crate) const VIDEO_COLUMNS: &str =
+    "v.video_id, v.video_url, v.title, v.thumbnail_url, v.author, v.view_count, v.published_at,
+     v.is_local, v.created_at, v.updated_at";
+
+pub(crate) fn playlist_item_from_row(row: &rusqlite::Row) -> rusqlite::Result<PlaylistItem> {
+    Ok(PlaylistItem {
+        id: row.get(0)?,
+        playlist_id: row.get(1)?,
+        video_url: row.get(2)?,
+        video_id: row.get(3)?,
+        title: row.get(4)?,
+        thumbnail_url: row.get(5)?,
+        position: row.get(6)?,
+        added_at: row.get(7)?,
+        is_local: row.get::<_, i32>(8)? != 0,
+        author: row.get(9).unwrap_or(None),
+        view_count: row.get(10).unwrap_or(None),
+        published_at: row.get(11).unwrap_or(None),
+    })
+}
 
-pub struct Database {
-    conn: Connection,
+fn playlist_from_row(row: &rusqlite::Row) -> rusqlite::Result<Playlist> {
+    Ok(Playlist {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+        custom_ascii: row.get(5)?,
+        custom_thumbnail_url: row.get(6)?,
+        smart_rules: smart_rules_from_column(row, 7)?,
+        group_id: row.get(8)?,
+        sort_order: row.get(9)?,
+    })
 }
 
-impl Database {
-    pub fn new(db_path: Option<&str>) -> Result<Self> {
-        // Use provided path or default to app data directory
-        let path = db_path.unwrap_or("playlists.db");
-        let conn = Connection::open(path)?;
+pub(crate) fn video_from_row(row: &rusqlite::Row) -> rusqlite::Result<Video> {
+    Ok(Video {
+        video_id: row.get(0)?,
+        video_url: row.get(1)?,
+        title: row.get(2)?,
+        thumbnail_url: row.get(3)?,
+        author: row.get(4)?,
+        view_count: row.get(5)?,
+        published_at: row.get(6)?,
+        is_local: row.get::<_, i32>(7)? != 0,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
 
-        let db = Database { conn };
-        db.init_schema()?;
-        Ok(db)
+/// Turn free-form user input into an FTS5 query: every word must match, as a
+/// prefix, and FTS syntax characters in the input are treated literally.
+fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
     }
+}
 
-    fn init_schema(&self) -> Result<()> {
-        // Create playlists table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS playlists (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL,
-                description TEXT,
-                created_at TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                custom_thumbnail_url TEXT
-            )",
-            [],
-        )?;
-
-        // Create playlist_items table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS playlist_items (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                playlist_id INTEGER NOT NULL,
-                video_url TEXT NOT NULL,
-                video_id TEXT NOT NULL,
-                title TEXT,
-                thumbnail_url TEXT,
-                position INTEGER NOT NULL,
-                thumbnail_url TEXT,
-                position INTEGER NOT NULL,
-                added_at TEXT NOT NULL,
-                is_local INTEGER NOT NULL DEFAULT 0,
-                author TEXT,
-                view_count TEXT,
-                published_at TEXT,
-                FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE
-            )",
-            [],
-        )?;
-
-        // Create index for faster lookups
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_playlist_items_playlist_id ON playlist_items(playlist_id)",
-            [],
-        )?;
-
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_playlist_items_position ON playlist_items(playlist_id, position)",
-            [],
-        )?;
-
-        // Create video_folder_assignments table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS video_folder_assignments (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                playlist_id INTEGER NOT NULL,
-                item_id INTEGER NOT NULL,
-                folder_color TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
-                FOREIGN KEY (item_id) REFERENCES playlist_items(id) ON DELETE CASCADE,
-                UNIQUE(playlist_id, item_id, folder_color)
-            )",
-            [],
-        )?;
-
-        // Create index for folder lookups
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_folder_assignments_playlist_color ON video_folder_assignments(playlist_id, folder_color)",
-            [],
-        )?;
+// FTS5 marks matches with these private-use characters instead of HTML tags,
+// so the stored text can be escaped before the tags are put in.
+const MATCH_START: char = '\u{E000}';
+const MATCH_END: char = '\u{E001}';
+
+/// HTML-escape a search snippet and wrap its matches in `<mark></mark>`.
+/// Titles and descriptions come from arbitrary sources, so nothing in them
+/// may reach the page as markup.
+fn highlight(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len());
+    for c in snippet.chars() {
+        match c {
+            MATCH_START => html.push_str("<mark>"),
+            MATCH_END => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
+}
 
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_folder_assignments_item ON video_folder_assignments(item_id)",
-            [],
-        )?;
+// Copies every page in one step: a stepwise backup restarts whenever
+// another connection writes to the source in between steps.
+fn copy_database(src: &Connection, dest: &mut Connection) -> AppResult<()> {
+    match Backup::new(src, dest)?.step(-1)? {
+        StepResult::Done => Ok(()),
+        result => Err(AppError::Busy {
+            message: format!("backup could not finish: {:?}", result),
+        }),
+    }
+}
 
-        // Create watch_history table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS watch_history (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                video_url TEXT NOT NULL,
-                video_id TEXT NOT NULL,
-                title TEXT,
-                thumbnail_url TEXT,
-                watched_at TEXT NOT NULL
-            )",
-            [],
-        )?;
+pub struct Database {
+    pub(crate) conn: Connection,
+}
 
-        // Create index for faster history lookups
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_watch_history_watched_at ON watch_history(watched_at DESC)",
-            [],
-        )?;
+impl Database {
+    pub fn new(db_path: Option<&str>) -> AppResult<Self> {
+        // Use provided path or default to app data directory
+        let path = db_path.unwrap_or("playlists.db");
+        let mut conn = Connection::open(path)?;
+        Self::configure(&conn)?;
 
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_watch_history_video_id ON watch_history(video_id)",
-            [],
-        )?;
+        // WAL lets pooled readers keep reading while the writer commits.
+        // The mode is persistent, so only the writer needs to set it.
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
 
-        // Create video_progress table to track playback progress
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS video_progress (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                video_id TEXT NOT NULL UNIQUE,
-                video_url TEXT NOT NULL,
-                duration REAL,
-                last_progress REAL NOT NULL DEFAULT 0,
-                progress_percentage REAL NOT NULL DEFAULT 0,
-                last_updated TEXT NOT NULL
-            )",
-            [],
-        )?;
+        // Bring the schema up to date before anything else touches it
+        migrations::run(&mut conn)?;
 
-        // Create index for faster progress lookups
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_video_progress_video_id ON video_progress(video_id)",
-            [],
-        )?;
+        Ok(Database { conn })
+    }
 
-        // Create stuck_folders table to track folders that should remain visible
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS stuck_folders (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                playlist_id INTEGER NOT NULL,
-                folder_color TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
-                UNIQUE(playlist_id, folder_color)
-            )",
-            [],
-        )?;
+    /// Open an additional connection that refuses to write. Used for the
+    /// reader pool; the schema is assumed to be migrated already.
+    pub fn open_reader(path: &str) -> AppResult<Self> {
+        let conn = Connection::open(path)?;
+        Self::configure(&conn)?;
+        conn.pragma_update(None, "query_only", true)?;
+        Ok(Database { conn })
+    }
 
-        // Create index for faster stuck folder lookups
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_stuck_folders_playlist_color ON stuck_folders(playlist_id, folder_color)",
-            [],
-        )?;
+    fn configure(conn: &Connection) -> AppResult<()> {
+        // Wait for a competing writer instead of failing immediately with SQLITE_BUSY
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        Ok(())
+    }
 
-        // Create folder_metadata table for custom folder names/descriptions
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS folder_metadata (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                playlist_id INTEGER NOT NULL,
-                folder_color TEXT NOT NULL,
-                custom_name TEXT,
-                description TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
-                UNIQUE(playlist_id, folder_color)
-            )",
-            [],
-        )?;
+    pub fn ping(&self) -> AppResult<()> {
+        self.conn.query_row("SELECT 1", [], |_| Ok(()))?;
+        Ok(())
+    }
 
-        self.conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_folder_metadata_playlist_color ON folder_metadata(playlist_id, folder_color)",
-            [],
+    pub(crate) fn ensure_playlist_exists(&self, playlist_id: i64) -> AppResult<()> {
+        let exists: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM playlists WHERE id = ?1 AND deleted_at IS NULL)",
+            params![playlist_id],
+            |row| row.get(0),
         )?;
 
-        // Migration: Add is_local column to playlist_items if it doesn't exist
-        // SQLite doesn't support IF NOT EXISTS for ALTER TABLE, so we check if column exists
-        let mut stmt = self.conn.prepare("PRAGMA table_info(playlist_items)")?;
-        let columns: Vec<String> = stmt
-            .query_map([], |row| Ok(row.get::<_, String>(1)?))?
-            .collect::<Result<Vec<_>, _>>()?;
-
-        if !columns.contains(&"is_local".to_string()) {
-            self.conn.execute(
-                "ALTER TABLE playlist_items ADD COLUMN is_local INTEGER NOT NULL DEFAULT 0",
-                [],
-            )?;
-        }
-
-        // Migration: Add author and view_count columns
-        if !columns.contains(&"author".to_string()) {
-            self.conn
-                .execute("ALTER TABLE playlist_items ADD COLUMN author TEXT", [])?;
-        }
-
-        if !columns.contains(&"view_count".to_string()) {
-            self.conn
-                .execute("ALTER TABLE playlist_items ADD COLUMN view_count TEXT", [])?;
-        }
-
-        if !columns.contains(&"published_at".to_string()) {
-            self.conn
-                .execute("ALTER TABLE playlist_items ADD COLUMN published_at TEXT", [])?;
-        }
-
-        // Migration: Add Video Progress columns
-        let mut vp_stmt = self.conn.prepare("PRAGMA table_info(video_progress)")?;
-        let vp_columns: Vec<String> = vp_stmt
-            .query_map([], |row| Ok(row.get::<_, String>(1)?))?
-            .collect::<Result<Vec<_>, _>>()?;
-
-        if !vp_columns.contains(&"has_fully_watched".to_string()) {
-            self.conn
-                .execute("ALTER TABLE video_progress ADD COLUMN has_fully_watched INTEGER NOT NULL DEFAULT 0", [])?;
-        }
-
-        // Migration: Add custom_ascii to playlists
-        let mut pl_stmt = self.conn.prepare("PRAGMA table_info(playlists)")?;
-        let pl_columns: Vec<String> = pl_stmt
-            .query_map([], |row| Ok(row.get::<_, String>(1)?))?
-            .collect::<Result<Vec<_>, _>>()?;
-
-        if !pl_columns.contains(&"custom_ascii".to_string()) {
-            self.conn
-                .execute("ALTER TABLE playlists ADD COLUMN custom_ascii TEXT", [])?;
-        }
-
-        // Migration: Add custom_ascii to folder_metadata
-        let mut fm_stmt = self.conn.prepare("PRAGMA table_info(folder_metadata)")?;
-        let fm_columns: Vec<String> = fm_stmt
-            .query_map([], |row| Ok(row.get::<_, String>(1)?))?
-            .collect::<Result<Vec<_>, _>>()?;
-
-        if !fm_columns.contains(&"custom_ascii".to_string()) {
-            self.conn.execute(
-                "ALTER TABLE folder_metadata ADD COLUMN custom_ascii TEXT",
-                [],
-            )?;
-        }
-
-        // Migration: Add custom_thumbnail_url to playlists
-        if !pl_columns.contains(&"custom_thumbnail_url".to_string()) {
-            self.conn
-                .execute("ALTER TABLE playlists ADD COLUMN custom_thumbnail_url TEXT", [])?;
+        if exists {
+            Ok(())
+        } else {
+            Err(AppError::not_found("playlist", playlist_id))
         }
-
-        Ok(())
     }
 
     // Playlist operations
-    pub fn create_playlist(&self, name: &str, description: Option<&str>) -> Result<i64> {
+    pub fn create_playlist(&self, name: &str, description: Option<&str>) -> AppResult<i64> {
+        if name.trim().is_empty() {
+            return Err(AppError::validation(
+                "name",
+                "playlist name cannot be empty",
+            ));
+        }
+
         let now = Utc::now().to_rfc3339();
         self.conn.execute(
             "INSERT INTO playlists (name, description, created_at, updated_at) VALUES (?1, ?2, ?3, ?4)",
@@ -260,100 +205,88 @@ impl Database {
         Ok(self.conn.last_insert_rowid())
     }
 
-    pub fn get_all_playlists(&self) -> Result<Vec<Playlist>> {
+    pub fn get_all_playlists(&self) -> AppResult<Vec<Playlist>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, description, created_at, updated_at, custom_ascii, custom_thumbnail_url FROM playlists ORDER BY created_at DESC"
+            "SELECT id, name, description, created_at, updated_at, custom_ascii, custom_thumbnail_url, smart_rules, group_id, sort_order FROM playlists WHERE deleted_at IS NULL ORDER BY group_id, sort_order, created_at DESC"
         )?;
 
         let playlists = stmt
-            .query_map([], |row| {
-                Ok(Playlist {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    created_at: row.get(3)?,
-                    updated_at: row.get(4)?,
-                    custom_ascii: row.get(5)?,
-                    custom_thumbnail_url: row.get(6)?,
-                })
-            })?
+            .query_map([], playlist_from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(playlists)
     }
 
-    pub fn get_all_playlist_metadata(&self) -> Result<Vec<crate::models::PlaylistMetadata>> {
+    pub fn get_all_playlist_metadata(&self) -> AppResult<Vec<crate::models::PlaylistMetadata>> {
         // 1. Get all playlists IDs
-        let mut stmt = self.conn.prepare("SELECT id FROM playlists")?;
-        let playlist_ids: Vec<i64> = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<_>, _>>()?;
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, smart_rules, group_id FROM playlists WHERE deleted_at IS NULL")?;
+        let playlists = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    smart_rules_from_column(row, 1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Each playlist carries the totals of the group it's in
+        let group_totals: HashMap<i64, (i32, i32)> = self
+            .get_playlist_groups()?
+            .into_iter()
+            .map(|g| (g.id, (g.playlist_count, g.item_count)))
+            .collect();
 
         let mut metadata_list = Vec::new();
 
         // Prepare statements
-        let mut count_stmt = self.conn.prepare("SELECT COUNT(*) FROM playlist_items WHERE playlist_id = ?1")?;
+        let mut count_stmt = self.conn.prepare("SELECT COUNT(*) FROM playlist_items WHERE playlist_id = ?1 AND deleted_at IS NULL")?;
         
         // First video (position 0 or min)
-        let mut first_video_stmt = self.conn.prepare(
-             "SELECT id, playlist_id, video_url, video_id, title, thumbnail_url, position, added_at, is_local, author, view_count, published_at 
-              FROM playlist_items 
-              WHERE playlist_id = ?1 
-              ORDER BY position ASC 
-              LIMIT 1"
-        )?;
+        let mut first_video_stmt = self.conn.prepare(&format!(
+            "{} WHERE pi.playlist_id = ?1 AND pi.deleted_at IS NULL ORDER BY pi.position ASC LIMIT 1",
+            ITEM_SELECT
+        ))?;
 
         // Recent video logic: join video_progress and order by last_updated desc
-        let mut recent_video_stmt = self.conn.prepare(
-             "SELECT pi.id, pi.playlist_id, pi.video_url, pi.video_id, pi.title, pi.thumbnail_url, pi.position, pi.added_at, pi.is_local, pi.author, pi.view_count, pi.published_at 
-              FROM playlist_items pi
-              INNER JOIN video_progress vp ON pi.video_id = vp.video_id
-              WHERE pi.playlist_id = ?1
-              ORDER BY vp.last_updated DESC
-              LIMIT 1"
-        )?;
+        let mut recent_video_stmt = self.conn.prepare(&format!(
+            "{}
+             INNER JOIN video_progress vp ON pi.video_id = vp.video_id
+             WHERE pi.playlist_id = ?1 AND pi.deleted_at IS NULL
+             ORDER BY vp.last_updated DESC
+             LIMIT 1",
+            ITEM_SELECT
+        ))?;
+
+        for (pid, smart_rules, group_id) in playlists {
+            let (group_playlist_count, group_item_count) = group_id
+                .and_then(|id| group_totals.get(&id).copied())
+                .unwrap_or_default();
+
+            if let Some(rules) = smart_rules {
+                metadata_list.push(crate::models::PlaylistMetadata {
+                    group_id,
+                    group_playlist_count,
+                    group_item_count,
+                    ..self.smart_playlist_metadata(pid, &rules)?
+                });
+                continue;
+            }
 
-        for pid in playlist_ids {
             let count: i32 = count_stmt.query_row(params![pid], |row| row.get(0)).unwrap_or(0);
             
-            let first_video = match first_video_stmt.query_row(params![pid], |row| {
-                 Ok(PlaylistItem {
-                    id: row.get(0)?,
-                    playlist_id: row.get(1)?,
-                    video_url: row.get(2)?,
-                    video_id: row.get(3)?,
-                    title: row.get(4)?,
-                    thumbnail_url: row.get(5)?,
-                    position: row.get(6)?,
-                    added_at: row.get(7)?,
-                    is_local: row.get::<_, i32>(8)? != 0,
-                    author: row.get(9).unwrap_or(None),
-                    view_count: row.get(10).unwrap_or(None),
-                    published_at: row.get(11).unwrap_or(None),
-                })
-            }) {
+            let first_video = match first_video_stmt.query_row(params![pid], playlist_item_from_row) {
                 Ok(v) => Some(v),
                 Err(rusqlite::Error::QueryReturnedNoRows) => None,
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
-            let recent_video = match recent_video_stmt.query_row(params![pid], |row| {
-                 Ok(PlaylistItem {
-                    id: row.get(0)?,
-                    playlist_id: row.get(1)?,
-                    video_url: row.get(2)?,
-                    video_id: row.get(3)?,
-                    title: row.get(4)?,
-                    thumbnail_url: row.get(5)?,
-                    position: row.get(6)?,
-                    added_at: row.get(7)?,
-                    is_local: row.get::<_, i32>(8)? != 0,
-                    author: row.get(9).unwrap_or(None),
-                    view_count: row.get(10).unwrap_or(None),
-                    published_at: row.get(11).unwrap_or(None),
-                })
-            }) {
+            let recent_video = match recent_video_stmt.query_row(params![pid], playlist_item_from_row) {
                 Ok(v) => Some(v),
                 Err(rusqlite::Error::QueryReturnedNoRows) => None,
-                Err(e) => return Err(e),
+                Err(e) => return Err(e.into()),
             };
 
             metadata_list.push(crate::models::PlaylistMetadata {
@@ -361,31 +294,24 @@ impl Database {
                 count,
                 first_video,
                 recent_video,
+                group_id,
+                group_playlist_count,
+                group_item_count,
             });
         }
         
         Ok(metadata_list)
     }
 
-    pub fn get_playlist(&self, id: i64) -> Result<Option<Playlist>> {
+    pub fn get_playlist(&self, id: i64) -> AppResult<Option<Playlist>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, description, created_at, updated_at, custom_ascii, custom_thumbnail_url FROM playlists WHERE id = ?1",
+            "SELECT id, name, description, created_at, updated_at, custom_ascii, custom_thumbnail_url, smart_rules, group_id, sort_order FROM playlists WHERE id = ?1 AND deleted_at IS NULL",
         )?;
 
-        match stmt.query_row(params![id], |row| {
-            Ok(Playlist {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                created_at: row.get(3)?,
-                updated_at: row.get(4)?,
-                custom_ascii: row.get(5)?,
-                custom_thumbnail_url: row.get(6)?,
-            })
-        }) {
+        match stmt.query_row(params![id], playlist_from_row) {
             Ok(playlist) => Ok(Some(playlist)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         }
     }
 
@@ -396,7 +322,7 @@ impl Database {
         description: Option<&str>,
         custom_ascii: Option<&str>,
         custom_thumbnail_url: Option<&str>,
-    ) -> Result<bool> {
+    ) -> AppResult<bool> {
         let now = Utc::now().to_rfc3339();
 
         if let Some(name) = name {
@@ -488,18 +414,49 @@ impl Database {
         Ok(false)
     }
 
-    pub fn delete_playlist(&self, id: i64) -> Result<bool> {
-        let rows = self
-            .conn
-            .execute("DELETE FROM playlists WHERE id = ?1", params![id])?;
-        Ok(rows > 0)
+    pub fn delete_playlist(&self, id: i64) -> AppResult<bool> {
+        self.delete_playlists_journaled(&[id])
     }
 
-    pub fn delete_playlist_by_name(&self, name: &str) -> Result<bool> {
-        let rows = self
+    pub fn delete_playlist_by_name(&self, name: &str) -> AppResult<bool> {
+        let mut stmt = self
             .conn
-            .execute("DELETE FROM playlists WHERE name = ?1", params![name])?;
-        Ok(rows > 0)
+            .prepare("SELECT id FROM playlists WHERE name = ?1 AND deleted_at IS NULL")?;
+        let ids: Vec<i64> = stmt
+            .query_map(params![name], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.delete_playlists_journaled(&ids)
+    }
+
+    fn delete_playlists_journaled(&self, ids: &[i64]) -> AppResult<bool> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        let mut trashed = Vec::new();
+        for &id in ids {
+            let Some(playlist) = self.get_playlist(id)? else {
+                continue;
+            };
+            self.trash_playlist_rows(id)?;
+            trashed.push(playlist);
+        }
+        if trashed.is_empty() {
+            return Ok(false);
+        }
+
+        let description = match trashed.as_slice() {
+            [one] => format!("Delete playlist '{}'", one.name),
+            many => format!("Delete {} playlists", many.len()),
+        };
+        self.record_operation(
+            &JournalOp::TrashPlaylists {
+                playlist_ids: trashed.iter().map(|p| p.id).collect(),
+            },
+            &description,
+        )?;
+
+        tx.commit()?;
+        Ok(true)
     }
 
     // Playlist item operations
@@ -514,95 +471,279 @@ impl Database {
         author: Option<&str>,
         view_count: Option<&str>,
         published_at: Option<&str>,
-    ) -> Result<i64> {
+    ) -> AppResult<i64> {
+        self.ensure_regular_playlist(playlist_id)?;
+
         // Get the next position (max position + 1)
-        let position: i32 = self.conn.query_row(
-            "SELECT COALESCE(MAX(position), 0) + 1 FROM playlist_items WHERE playlist_id = ?1",
-            params![playlist_id],
-            |row| row.get(0),
+        let position = self.next_position(playlist_id)?;
+
+        let now = Utc::now().to_rfc3339();
+        let tx = self.conn.unchecked_transaction()?;
+
+        self.upsert_video(&Video {
+            video_id: video_id.to_string(),
+            video_url: video_url.to_string(),
+            title: title.map(str::to_string),
+            thumbnail_url: thumbnail_url.map(str::to_string),
+            author: author.map(str::to_string),
+            view_count: view_count.map(str::to_string),
+            published_at: published_at.map(str::to_string),
+            is_local,
+            created_at: now.clone(),
+            updated_at: now.clone(),
+        })?;
+
+        tx.execute(
+            "INSERT INTO playlist_items (playlist_id, video_id, position, added_at) VALUES (?1, ?2, ?3, ?4)",
+            params![playlist_id, video_id, position, now],
+        )?;
+        let item_id = tx.last_insert_rowid();
+        tx.commit()?;
+
+        Ok(item_id)
+    }
+
+    /// Append a batch of videos in one transaction, with contiguous positions
+    /// after the current last item. Items without a video id or url, or
+    /// naming a folder that doesn't exist, are reported as invalid; with
+    /// `skip_duplicates`, videos already in the playlist (or earlier in the
+    /// batch) are reported as duplicates.
+    pub fn add_videos_to_playlist(
+        &self,
+        playlist_id: i64,
+        items: &[NewPlaylistItem],
+        skip_duplicates: bool,
+    ) -> AppResult<Vec<BulkAddResult>> {
+        self.ensure_regular_playlist(playlist_id)?;
+        let tx = self.conn.unchecked_transaction()?;
+
+        let mut position = self.next_position(playlist_id)?;
+
+        let mut present = HashSet::new();
+        if skip_duplicates {
+            let mut stmt = self.conn.prepare(
+                "SELECT video_id FROM playlist_items WHERE playlist_id = ?1 AND deleted_at IS NULL",
+            )?;
+            for video_id in stmt.query_map(params![playlist_id], |row| row.get::<_, String>(0))? {
+                present.insert(video_id?);
+            }
+        }
+
+        let mut insert_item = self.conn.prepare(
+            "INSERT INTO playlist_items (playlist_id, video_id, position, added_at) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        let mut assign_folder = self.conn.prepare(
+            "INSERT OR IGNORE INTO video_folder_assignments (playlist_id, item_id, folder_color, created_at) VALUES (?1, ?2, ?3, ?4)",
         )?;
 
         let now = Utc::now().to_rfc3339();
+        let mut results = Vec::with_capacity(items.len());
+        for (index, item) in items.iter().enumerate() {
+            let mut result = BulkAddResult {
+                index,
+                video_id: item.video_id.clone(),
+                status: String::new(),
+                item_id: None,
+                position: None,
+                reason: None,
+            };
+
+            if item.video_id.trim().is_empty() || item.video_url.trim().is_empty() {
+                result.status = "invalid".to_string();
+                result.reason = Some("missing video id or url".to_string());
+                results.push(result);
+                continue;
+            }
+            let mut folder_keys = Vec::with_capacity(item.folder_colors.len());
+            for color in &item.folder_colors {
+                folder_keys.extend(self.find_folder_key(color)?);
+            }
+            if folder_keys.len() < item.folder_colors.len() {
+                result.status = "invalid".to_string();
+                result.reason = Some("unknown folder".to_string());
+                results.push(result);
+                continue;
+            }
+            if skip_duplicates && !present.insert(item.video_id.clone()) {
+                result.status = "duplicate".to_string();
+                result.reason = Some("already in the playlist".to_string());
+                results.push(result);
+                continue;
+            }
+
+            self.upsert_video(&Video {
+                video_id: item.video_id.clone(),
+                video_url: item.video_url.clone(),
+                title: item.title.clone(),
+                thumbnail_url: item.thumbnail_url.clone(),
+                author: item.author.clone(),
+                view_count: item.view_count.clone(),
+                published_at: item.published_at.clone(),
+                is_local: item.is_local,
+                created_at: now.clone(),
+                updated_at: now.clone(),
+            })?;
+            insert_item.execute(params![playlist_id, item.video_id, position, now])?;
+            let item_id = self.conn.last_insert_rowid();
+
+            for color in &folder_keys {
+                assign_folder.execute(params![playlist_id, item_id, color, now])?;
+            }
+
+            result.status = "added".to_string();
+            result.item_id = Some(item_id);
+            result.position = Some(position);
+            results.push(result);
+            position += 1;
+        }
+
+        tx.commit()?;
+        Ok(results)
+    }
+
+    // The newest copy of a video's metadata wins for every playlist it is in;
+    // fields the caller doesn't know keep their stored value.
+    pub(crate) fn upsert_video(&self, video: &Video) -> AppResult<()> {
         self.conn.execute(
-            "INSERT INTO playlist_items (playlist_id, video_url, video_id, title, thumbnail_url, position, added_at, is_local, author, view_count, published_at) 
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
-            params![playlist_id, video_url, video_id, title, thumbnail_url, position, now, if is_local { 1 } else { 0 }, author, view_count, published_at],
+            "INSERT INTO videos (video_id, video_url, title, thumbnail_url, author, view_count, published_at, is_local, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+             ON CONFLICT(video_id) DO UPDATE SET
+                video_url = excluded.video_url,
+                title = COALESCE(excluded.title, title),
+                thumbnail_url = COALESCE(excluded.thumbnail_url, thumbnail_url),
+                author = COALESCE(excluded.author, author),
+                view_count = COALESCE(excluded.view_count, view_count),
+                published_at = COALESCE(excluded.published_at, published_at),
+                is_local = excluded.is_local,
+                updated_at = excluded.updated_at",
+            params![
+                video.video_id,
+                video.video_url,
+                video.title,
+                video.thumbnail_url,
+                video.author,
+                video.view_count,
+                video.published_at,
+                video.is_local,
+                video.created_at,
+                video.updated_at
+            ],
         )?;
+        Ok(())
+    }
 
-        Ok(self.conn.last_insert_rowid())
+    /// Store a video sent with a pin or like.
+    pub(crate) fn store_video_request(&self, video: &VideoRequest) -> AppResult<()> {
+        if video.video_id.trim().is_empty() {
+            return Err(AppError::validation("video_id", "video id cannot be empty"));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        self.upsert_video(&Video {
+            video_id: video.video_id.clone(),
+            video_url: video.video_url.clone(),
+            title: video.title.clone(),
+            thumbnail_url: video.thumbnail_url.clone(),
+            author: video.author.clone(),
+            view_count: video.view_count.clone(),
+            published_at: video.published_at.clone(),
+            is_local: video.is_local,
+            created_at: now.clone(),
+            updated_at: now,
+        })
     }
 
-    pub fn get_playlist_items(&self, playlist_id: i64) -> Result<Vec<PlaylistItem>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, playlist_id, video_url, video_id, title, thumbnail_url, position, added_at, is_local, author, view_count, published_at 
-             FROM playlist_items 
-             WHERE playlist_id = ?1 
-             ORDER BY position ASC"
-        )?;
+    pub fn get_playlist_items(&self, playlist_id: i64) -> AppResult<Vec<PlaylistItem>> {
+        if let Some(rules) = self.get_smart_rules(playlist_id)? {
+            return self.evaluate_smart_rules(&rules);
+        }
+
+        let mut stmt = self.conn.prepare(&format!(
+            "{} WHERE pi.playlist_id = ?1 AND pi.deleted_at IS NULL ORDER BY pi.position ASC",
+            ITEM_SELECT
+        ))?;
 
         let items = stmt
-            .query_map(params![playlist_id], |row| {
-                Ok(PlaylistItem {
-                    id: row.get(0)?,
-                    playlist_id: row.get(1)?,
-                    video_url: row.get(2)?,
-                    video_id: row.get(3)?,
-                    title: row.get(4)?,
-                    thumbnail_url: row.get(5)?,
-                    position: row.get(6)?,
-                    added_at: row.get(7)?,
-                    is_local: row.get::<_, i32>(8)? != 0,
-                    author: row.get(9).unwrap_or(None),
-                    view_count: row.get(10).unwrap_or(None),
-                    published_at: row.get(11).unwrap_or(None),
-                })
-            })?
+            .query_map(params![playlist_id], playlist_item_from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(items)
     }
 
-    pub fn remove_video_from_playlist(&self, playlist_id: i64, item_id: i64) -> Result<bool> {
-        // Get the position of the item being removed
-        let removed_position: Option<i32> = match self.conn.query_row(
-            "SELECT position FROM playlist_items WHERE id = ?1 AND playlist_id = ?2",
+    pub fn remove_video_from_playlist(&self, playlist_id: i64, item_id: i64) -> AppResult<bool> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        let Some(item) = self
+            .get_playlist_item(item_id)?
+            .filter(|i| i.playlist_id == playlist_id)
+        else {
+            return Ok(false);
+        };
+        self.trash_item_rows(playlist_id, item_id)?;
+
+        let title = item.title.unwrap_or(item.video_id);
+        self.record_operation(
+            &JournalOp::TrashItem {
+                playlist_id,
+                item_id,
+            },
+            &format!("Remove '{}' from playlist", title),
+        )?;
+
+        tx.commit()?;
+        Ok(true)
+    }
+
+    pub fn reorder_playlist_item(
+        &self,
+        playlist_id: i64,
+        item_id: i64,
+        new_position: i32,
+    ) -> AppResult<bool> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        let current_position: Option<i32> = match self.conn.query_row(
+            "SELECT position FROM playlist_items WHERE id = ?1 AND playlist_id = ?2 AND deleted_at IS NULL",
             params![item_id, playlist_id],
             |row| row.get(0),
         ) {
-            Ok(pos) => Ok(Some(pos)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
-        }?;
+            Ok(pos) => Some(pos),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e.into()),
+        };
 
-        if removed_position.is_none() {
+        let Some(from) = current_position else {
             return Ok(false);
+        };
+        let new_position = new_position.clamp(1, self.live_item_count(playlist_id)?);
+        if from == new_position {
+            return Ok(true);
         }
 
-        // Delete the item
-        self.conn.execute(
-            "DELETE FROM playlist_items WHERE id = ?1 AND playlist_id = ?2",
-            params![item_id, playlist_id],
-        )?;
-
-        // Update positions of remaining items
-        self.conn.execute(
-            "UPDATE playlist_items SET position = position - 1 
-             WHERE playlist_id = ?1 AND position > ?2",
-            params![playlist_id, removed_position.unwrap()],
+        self.move_item_rows(playlist_id, item_id, new_position)?;
+        self.record_operation(
+            &JournalOp::Reorder {
+                playlist_id,
+                item_id,
+                from,
+                to: new_position,
+            },
+            "Move item",
         )?;
 
+        tx.commit()?;
         Ok(true)
     }
 
-    pub fn reorder_playlist_item(
+    pub(crate) fn move_item_rows(
         &self,
         playlist_id: i64,
         item_id: i64,
         new_position: i32,
-    ) -> Result<bool> {
+    ) -> AppResult<bool> {
         // Get current position
         let current_position: Option<i32> = match self.conn.query_row(
-            "SELECT position FROM playlist_items WHERE id = ?1 AND playlist_id = ?2",
+            "SELECT position FROM playlist_items WHERE id = ?1 AND playlist_id = ?2 AND deleted_at IS NULL",
             params![item_id, playlist_id],
             |row| row.get(0),
         ) {
@@ -616,25 +757,24 @@ impl Database {
         }
 
         let current_pos = current_position.unwrap();
+        let new_position = new_position.clamp(1, self.live_item_count(playlist_id)?);
         if current_pos == new_position {
             return Ok(true);
         }
 
+        // Park the item outside the live range while the others shift
+        self.conn.execute(
+            "UPDATE playlist_items SET position = 0 WHERE id = ?1",
+            params![item_id],
+        )?;
+
         // Update positions
         if new_position > current_pos {
             // Moving down: shift items up
-            self.conn.execute(
-                "UPDATE playlist_items SET position = position - 1 
-                 WHERE playlist_id = ?1 AND position > ?2 AND position <= ?3",
-                params![playlist_id, current_pos, new_position],
-            )?;
+            self.shift_positions(playlist_id, current_pos + 1, new_position, -1)?;
         } else {
             // Moving up: shift items down
-            self.conn.execute(
-                "UPDATE playlist_items SET position = position + 1 
-                 WHERE playlist_id = ?1 AND position >= ?2 AND position < ?3",
-                params![playlist_id, new_position, current_pos],
-            )?;
+            self.shift_positions(playlist_id, new_position, current_pos - 1, 1)?;
         }
 
         // Update the item's position
@@ -646,15 +786,262 @@ impl Database {
         Ok(true)
     }
 
+    // Live items of a playlist always sit at positions 1..=n, enforced by a
+    // unique index. The helpers below keep that true inside the caller's
+    // transaction.
+
+    pub(crate) fn live_item_count(&self, playlist_id: i64) -> AppResult<i32> {
+        let count = self.conn.query_row(
+            "SELECT COUNT(*) FROM playlist_items WHERE playlist_id = ?1 AND deleted_at IS NULL",
+            params![playlist_id],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    pub(crate) fn next_position(&self, playlist_id: i64) -> AppResult<i32> {
+        let position = self.conn.query_row(
+            "SELECT COALESCE(MAX(position), 0) + 1 FROM playlist_items WHERE playlist_id = ?1 AND deleted_at IS NULL",
+            params![playlist_id],
+            |row| row.get(0),
+        )?;
+        Ok(position)
+    }
+
+    /// Move the live items at positions `from..=to` by `delta`. SQLite checks
+    /// the unique index row by row, so the items pass through negative
+    /// positions first instead of stepping on each other.
+    pub(crate) fn shift_positions(
+        &self,
+        playlist_id: i64,
+        from: i32,
+        to: i32,
+        delta: i32,
+    ) -> AppResult<()> {
+        if from > to {
+            return Ok(());
+        }
+
+        self.conn.execute(
+            "UPDATE playlist_items SET position = -(position + ?4)
+             WHERE playlist_id = ?1 AND position BETWEEN ?2 AND ?3 AND deleted_at IS NULL",
+            params![playlist_id, from, to, delta],
+        )?;
+        self.conn.execute(
+            "UPDATE playlist_items SET position = -position
+             WHERE playlist_id = ?1 AND position < 0 AND deleted_at IS NULL",
+            params![playlist_id],
+        )?;
+        Ok(())
+    }
+
+    /// Renumber the live items of a playlist, or of every playlist, to
+    /// 1..=n in their current order. Returns how many items moved.
+    pub fn repair_playlist_positions(&self, playlist_id: Option<i64>) -> AppResult<usize> {
+        if let Some(id) = playlist_id {
+            self.ensure_playlist_exists(id)?;
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+
+        // Rank by position, then id, so duplicated positions keep insertion order
+        let ranked =
+            "SELECT id, ROW_NUMBER() OVER (PARTITION BY playlist_id ORDER BY position, id) AS rank
+             FROM playlist_items
+             WHERE deleted_at IS NULL AND (?1 IS NULL OR playlist_id = ?1)";
+
+        let moved: usize = self.conn.query_row(
+            &format!(
+                "SELECT COUNT(*) FROM ({}) r INNER JOIN playlist_items pi ON pi.id = r.id WHERE pi.position != r.rank",
+                ranked
+            ),
+            params![playlist_id],
+            |row| row.get(0),
+        )?;
+        if moved > 0 {
+            self.conn.execute(
+                &format!(
+                    "UPDATE playlist_items SET position = -r.rank FROM ({}) r WHERE playlist_items.id = r.id",
+                    ranked
+                ),
+                params![playlist_id],
+            )?;
+            self.conn.execute(
+                "UPDATE playlist_items SET position = -position WHERE position < 0 AND deleted_at IS NULL",
+                [],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(moved)
+    }
+
+    /// Move items, in the order given, into `target_playlist_id` starting at
+    /// `target_position` (appended when `None`). Items keep their ids and
+    /// `added_at`. Their folder assignments go with them when
+    /// `carry_folders` is set and are dropped otherwise.
+    pub fn move_items(
+        &self,
+        item_ids: &[i64],
+        target_playlist_id: i64,
+        target_position: Option<i32>,
+        carry_folders: bool,
+    ) -> AppResult<Vec<PlaylistItem>> {
+        self.ensure_regular_playlist(target_playlist_id)?;
+        let tx = self.conn.unchecked_transaction()?;
+        let moved =
+            self.place_moved_items(item_ids, target_playlist_id, target_position, carry_folders)?;
+        tx.commit()?;
+        self.items_by_id(&moved)
+    }
+
+    // `move_items` without the checks; callers own the transaction
+    pub(crate) fn place_moved_items(
+        &self,
+        item_ids: &[i64],
+        target_playlist_id: i64,
+        target_position: Option<i32>,
+        carry_folders: bool,
+    ) -> AppResult<Vec<i64>> {
+        let mut insert_at = target_position.unwrap_or(i32::MAX);
+        let mut moved = Vec::new();
+        for item in self.items_for_transfer(item_ids)? {
+            // Earlier moves may have shifted it, so look the position up again
+            let position: i32 = self.conn.query_row(
+                "SELECT position FROM playlist_items WHERE id = ?1",
+                params![item.id],
+                |row| row.get(0),
+            )?;
+            self.conn.execute(
+                "UPDATE playlist_items SET position = 0 WHERE id = ?1",
+                params![item.id],
+            )?;
+            self.shift_positions(item.playlist_id, position + 1, i32::MAX, -1)?;
+
+            let at = insert_at.clamp(1, self.next_position(target_playlist_id)?);
+            self.shift_positions(target_playlist_id, at, i32::MAX, 1)?;
+            self.conn.execute(
+                "UPDATE playlist_items SET playlist_id = ?1, position = ?2 WHERE id = ?3",
+                params![target_playlist_id, at, item.id],
+            )?;
+
+            if item.playlist_id != target_playlist_id {
+                if carry_folders {
+                    self.conn.execute(
+                        "UPDATE video_folder_assignments SET playlist_id = ?1 WHERE item_id = ?2",
+                        params![target_playlist_id, item.id],
+                    )?;
+                } else {
+                    self.conn.execute(
+                        "DELETE FROM video_folder_assignments WHERE item_id = ?1",
+                        params![item.id],
+                    )?;
+                }
+            }
+
+            moved.push(item.id);
+            insert_at = at + 1;
+        }
+        Ok(moved)
+    }
+
+    /// Copy items, in the order given, into `target_playlist_id` starting at
+    /// `target_position` (appended when `None`). Copies keep the original
+    /// `added_at`, and the originals' folder assignments when
+    /// `carry_folders` is set.
+    pub fn copy_items(
+        &self,
+        item_ids: &[i64],
+        target_playlist_id: i64,
+        target_position: Option<i32>,
+        carry_folders: bool,
+    ) -> AppResult<Vec<PlaylistItem>> {
+        self.ensure_regular_playlist(target_playlist_id)?;
+        let tx = self.conn.unchecked_transaction()?;
+        let copies =
+            self.place_copied_items(item_ids, target_playlist_id, target_position, carry_folders)?;
+        tx.commit()?;
+        self.items_by_id(&copies)
+    }
+
+    // `copy_items` without the checks; callers own the transaction
+    pub(crate) fn place_copied_items(
+        &self,
+        item_ids: &[i64],
+        target_playlist_id: i64,
+        target_position: Option<i32>,
+        carry_folders: bool,
+    ) -> AppResult<Vec<i64>> {
+        let now = Utc::now().to_rfc3339();
+        let mut insert_at = target_position.unwrap_or(i32::MAX);
+        let mut copies = Vec::new();
+        for item in self.items_for_transfer(item_ids)? {
+            let at = insert_at.clamp(1, self.next_position(target_playlist_id)?);
+            self.shift_positions(target_playlist_id, at, i32::MAX, 1)?;
+            self.conn.execute(
+                "INSERT INTO playlist_items (playlist_id, video_id, position, added_at) VALUES (?1, ?2, ?3, ?4)",
+                params![target_playlist_id, item.video_id, at, item.added_at],
+            )?;
+            let copy_id = self.conn.last_insert_rowid();
+
+            if carry_folders {
+                self.conn.execute(
+                    "INSERT OR IGNORE INTO video_folder_assignments (playlist_id, item_id, folder_color, created_at)
+                     SELECT ?1, ?2, folder_color, ?3 FROM video_folder_assignments WHERE item_id = ?4",
+                    params![target_playlist_id, copy_id, now, item.id],
+                )?;
+            }
+
+            copies.push(copy_id);
+            insert_at = at + 1;
+        }
+        Ok(copies)
+    }
+
+    // Live items for a move or copy, in the order requested. Every id has to
+    // exist so a stale selection fails as a whole; repeats are ignored.
+    fn items_for_transfer(&self, item_ids: &[i64]) -> AppResult<Vec<PlaylistItem>> {
+        let mut seen = HashSet::new();
+        let mut items = Vec::with_capacity(item_ids.len());
+        for &id in item_ids {
+            if !seen.insert(id) {
+                continue;
+            }
+            let item = self
+                .get_playlist_item(id)?
+                .ok_or_else(|| AppError::not_found("playlist item", id))?;
+            items.push(item);
+        }
+        Ok(items)
+    }
+
+    pub(crate) fn items_by_id(&self, item_ids: &[i64]) -> AppResult<Vec<PlaylistItem>> {
+        let mut items = Vec::with_capacity(item_ids.len());
+        for &id in item_ids {
+            items.extend(self.get_playlist_item(id)?);
+        }
+        Ok(items)
+    }
+
     // Folder assignment operations
     pub fn assign_video_to_folder(
         &self,
         playlist_id: i64,
         item_id: i64,
         folder_color: &str,
-    ) -> Result<i64> {
+    ) -> AppResult<i64> {
         let now = Utc::now().to_rfc3339();
 
+        let item_exists: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM playlist_items WHERE id = ?1 AND playlist_id = ?2 AND deleted_at IS NULL)",
+            params![item_id, playlist_id],
+            |row| row.get(0),
+        )?;
+        if !item_exists {
+            return Err(AppError::not_found("playlist item", item_id));
+        }
+        let folder_color = &self.resolve_folder(folder_color)?;
+
         // First, check if assignment already exists
         let exists: bool = self.conn.query_row(
             "SELECT EXISTS(SELECT 1 FROM video_folder_assignments WHERE playlist_id = ?1 AND item_id = ?2 AND folder_color = ?3)",
@@ -686,7 +1073,44 @@ impl Database {
         playlist_id: i64,
         item_id: i64,
         folder_color: &str,
-    ) -> Result<bool> {
+    ) -> AppResult<bool> {
+        let folder_color = &self.resolve_folder(folder_color)?;
+        let tx = self.conn.unchecked_transaction()?;
+
+        let created_at: Option<String> = match self.conn.query_row(
+            "SELECT created_at FROM video_folder_assignments WHERE playlist_id = ?1 AND item_id = ?2 AND folder_color = ?3",
+            params![playlist_id, item_id, folder_color],
+            |row| row.get(0),
+        ) {
+            Ok(created_at) => Some(created_at),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e.into()),
+        };
+        let Some(created_at) = created_at else {
+            return Ok(false);
+        };
+
+        self.unassign_folder_rows(playlist_id, item_id, folder_color)?;
+        self.record_operation(
+            &JournalOp::UnassignFolder {
+                playlist_id,
+                item_id,
+                folder_color: folder_color.to_string(),
+                created_at,
+            },
+            &format!("Remove item from {} folder", folder_color),
+        )?;
+
+        tx.commit()?;
+        Ok(true)
+    }
+
+    pub(crate) fn unassign_folder_rows(
+        &self,
+        playlist_id: i64,
+        item_id: i64,
+        folder_color: &str,
+    ) -> AppResult<bool> {
         let rows = self.conn.execute(
             "DELETE FROM video_folder_assignments WHERE playlist_id = ?1 AND item_id = ?2 AND folder_color = ?3",
             params![playlist_id, item_id, folder_color],
@@ -698,32 +1122,18 @@ impl Database {
         &self,
         playlist_id: i64,
         folder_color: &str,
-    ) -> Result<Vec<PlaylistItem>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT pi.id, pi.playlist_id, pi.video_url, pi.video_id, pi.title, pi.thumbnail_url, pi.position, pi.added_at, pi.is_local, pi.author, pi.view_count, pi.published_at
-             FROM playlist_items pi
+    ) -> AppResult<Vec<PlaylistItem>> {
+        let folder_color = &self.resolve_folder(folder_color)?;
+        let mut stmt = self.conn.prepare(&format!(
+            "{}
              INNER JOIN video_folder_assignments vfa ON pi.id = vfa.item_id
-             WHERE vfa.playlist_id = ?1 AND vfa.folder_color = ?2
-             ORDER BY pi.position ASC"
-        )?;
+             WHERE vfa.playlist_id = ?1 AND vfa.folder_color = ?2 AND pi.deleted_at IS NULL
+             ORDER BY pi.position ASC",
+            ITEM_SELECT
+        ))?;
 
         let items = stmt
-            .query_map(params![playlist_id, folder_color], |row| {
-                Ok(PlaylistItem {
-                    id: row.get(0)?,
-                    playlist_id: row.get(1)?,
-                    video_url: row.get(2)?,
-                    video_id: row.get(3)?,
-                    title: row.get(4)?,
-                    thumbnail_url: row.get(5)?,
-                    position: row.get(6)?,
-                    added_at: row.get(7)?,
-                    is_local: row.get::<_, i32>(8)? != 0,
-                    author: row.get(9).unwrap_or(None),
-                    view_count: row.get(10).unwrap_or(None),
-                    published_at: row.get(11).unwrap_or(None),
-                })
-            })?
+            .query_map(params![playlist_id, folder_color], playlist_item_from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(items)
@@ -733,7 +1143,7 @@ impl Database {
         &self,
         playlist_id: i64,
         item_id: i64,
-    ) -> Result<Vec<String>> {
+    ) -> AppResult<Vec<String>> {
         let mut stmt = self.conn.prepare(
             "SELECT folder_color FROM video_folder_assignments WHERE playlist_id = ?1 AND item_id = ?2"
         )?;
@@ -750,7 +1160,7 @@ impl Database {
     pub fn get_all_folder_assignments_for_playlist(
         &self,
         playlist_id: i64,
-    ) -> Result<HashMap<String, Vec<String>>> {
+    ) -> AppResult<HashMap<String, Vec<String>>> {
         let mut stmt = self.conn.prepare(
             "SELECT item_id, folder_color 
              FROM video_folder_assignments 
@@ -777,7 +1187,7 @@ impl Database {
         Ok(assignments)
     }
 
-    pub fn get_all_folders_with_videos(&self) -> Result<Vec<crate::models::FolderWithVideos>> {
+    pub fn get_all_folders_with_videos(&self) -> AppResult<Vec<crate::models::FolderWithVideos>> {
         // Query to get all folders that have at least one video, grouped by playlist and folder color
         // Returns playlist info, folder color, video count, and first video
         let mut stmt = self.conn.prepare(
@@ -790,6 +1200,7 @@ impl Database {
              FROM video_folder_assignments vfa
              INNER JOIN playlists p ON vfa.playlist_id = p.id
              INNER JOIN playlist_items pi ON vfa.item_id = pi.id
+             WHERE p.deleted_at IS NULL AND pi.deleted_at IS NULL
              GROUP BY vfa.playlist_id, vfa.folder_color
              ORDER BY p.name, vfa.folder_color",
         )?;
@@ -811,31 +1222,20 @@ impl Database {
             // Get the first video (lowest position) for this folder
             let first_video = if let Some(pos) = min_position {
                 // Get the first video in this folder (by position, then by id for consistency)
-                let mut video_stmt = self.conn.prepare(
-                    "SELECT pi.id, pi.playlist_id, pi.video_url, pi.video_id, pi.title, pi.thumbnail_url, pi.position, pi.added_at, pi.is_local, pi.author, pi.view_count, pi.published_at
-                     FROM playlist_items pi
+                let mut video_stmt = self.conn.prepare(&format!(
+                    "{}
                      INNER JOIN video_folder_assignments vfa ON pi.id = vfa.item_id
                      WHERE vfa.playlist_id = ?1 AND vfa.folder_color = ?2 AND pi.position = ?3
+                       AND pi.deleted_at IS NULL
                      ORDER BY pi.position ASC, pi.id ASC
-                     LIMIT 1"
-                )?;
-
-                match video_stmt.query_row(params![playlist_id, folder_color, pos], |row| {
-                    Ok(PlaylistItem {
-                        id: row.get(0)?,
-                        playlist_id: row.get(1)?,
-                        video_url: row.get(2)?,
-                        video_id: row.get(3)?,
-                        title: row.get(4)?,
-                        thumbnail_url: row.get(5)?,
-                        position: row.get(6)?,
-                        added_at: row.get(7)?,
-                        is_local: row.get::<_, i32>(8)? != 0,
-                        author: row.get(9).unwrap_or(None),
-                        view_count: row.get(10).unwrap_or(None),
-                        published_at: row.get(11).unwrap_or(None),
-                    })
-                }) {
+                     LIMIT 1",
+                    ITEM_SELECT
+                ))?;
+
+                match video_stmt.query_row(
+                    params![playlist_id, folder_color, pos],
+                    playlist_item_from_row,
+                ) {
                     Ok(video) => Some(video),
                     Err(_) => None,
                 }
@@ -858,7 +1258,7 @@ impl Database {
     pub fn get_folders_for_playlist(
         &self,
         playlist_id: i64,
-    ) -> Result<Vec<crate::models::FolderWithVideos>> {
+    ) -> AppResult<Vec<crate::models::FolderWithVideos>> {
         // Query to get folders for a specific playlist that have at least one video
         let mut stmt = self.conn.prepare(
             "SELECT 
@@ -870,7 +1270,7 @@ impl Database {
              FROM video_folder_assignments vfa
              INNER JOIN playlists p ON vfa.playlist_id = p.id
              INNER JOIN playlist_items pi ON vfa.item_id = pi.id
-             WHERE vfa.playlist_id = ?1
+             WHERE vfa.playlist_id = ?1 AND p.deleted_at IS NULL AND pi.deleted_at IS NULL
              GROUP BY vfa.playlist_id, vfa.folder_color
              ORDER BY vfa.folder_color",
         )?;
@@ -891,31 +1291,20 @@ impl Database {
 
             // Get the first video (lowest position) for this folder
             let first_video = if let Some(pos) = min_position {
-                let mut video_stmt = self.conn.prepare(
-                    "SELECT pi.id, pi.playlist_id, pi.video_url, pi.video_id, pi.title, pi.thumbnail_url, pi.position, pi.added_at, pi.is_local, pi.author, pi.view_count, pi.published_at
-                     FROM playlist_items pi
+                let mut video_stmt = self.conn.prepare(&format!(
+                    "{}
                      INNER JOIN video_folder_assignments vfa ON pi.id = vfa.item_id
                      WHERE vfa.playlist_id = ?1 AND vfa.folder_color = ?2 AND pi.position = ?3
+                       AND pi.deleted_at IS NULL
                      ORDER BY pi.position ASC, pi.id ASC
-                     LIMIT 1"
-                )?;
-
-                match video_stmt.query_row(params![playlist_id, folder_color, pos], |row| {
-                    Ok(PlaylistItem {
-                        id: row.get(0)?,
-                        playlist_id: row.get(1)?,
-                        video_url: row.get(2)?,
-                        video_id: row.get(3)?,
-                        title: row.get(4)?,
-                        thumbnail_url: row.get(5)?,
-                        position: row.get(6)?,
-                        added_at: row.get(7)?,
-                        is_local: row.get::<_, i32>(8)? != 0,
-                        author: row.get(9).unwrap_or(None),
-                        view_count: row.get(10).unwrap_or(None),
-                        published_at: row.get(11).unwrap_or(None),
-                    })
-                }) {
+                     LIMIT 1",
+                    ITEM_SELECT
+                ))?;
+
+                match video_stmt.query_row(
+                    params![playlist_id, folder_color, pos],
+                    playlist_item_from_row,
+                ) {
                     Ok(video) => Some(video),
                     Err(_) => None,
                 }
@@ -942,7 +1331,7 @@ impl Database {
         video_id: &str,
         title: Option<&str>,
         thumbnail_url: Option<&str>,
-    ) -> Result<i64> {
+    ) -> AppResult<i64> {
         let now = Utc::now().to_rfc3339();
 
         // First delete any existing entry for this video to avoid duplicates and move to top
@@ -959,50 +1348,35 @@ impl Database {
         Ok(self.conn.last_insert_rowid())
     }
 
-    // Stuck folders operations
-    pub fn toggle_stuck_folder(&self, playlist_id: i64, folder_color: &str) -> Result<bool> {
-        // Check if folder is already stuck
-        let is_stuck = self.conn.query_row(
-            "SELECT EXISTS(SELECT 1 FROM stuck_folders WHERE playlist_id = ?1 AND folder_color = ?2)",
-            params![playlist_id, folder_color],
-            |row| row.get(0),
-        )?;
-
-        if is_stuck {
-            // Unstick: remove from stuck_folders
-            self.conn.execute(
-                "DELETE FROM stuck_folders WHERE playlist_id = ?1 AND folder_color = ?2",
-                params![playlist_id, folder_color],
-            )?;
-        }
-
-        // Stick: insert into stuck_folders
-        self.conn.execute(
-            "INSERT INTO stuck_folders (playlist_id, folder_color, created_at) VALUES (?1, ?2, ?3)",
-            params![playlist_id, folder_color, Utc::now().to_rfc3339()],
-        )?;
-
-        Ok(true)
+    // Stuck folders operations. A stuck folder is a pinned one; see
+    // `set_folder_visibility`.
+    pub fn toggle_stuck_folder(&self, playlist_id: i64, folder_color: &str) -> AppResult<bool> {
+        let folder_color = &self.resolve_folder(folder_color)?;
+        let pinned = !self.is_folder_stuck(playlist_id, folder_color)?;
+        self.set_folder_visibility(playlist_id, folder_color, Some(pinned), None, None)?;
+        Ok(pinned)
     }
 
-    pub fn is_folder_stuck(&self, playlist_id: i64, folder_color: &str) -> Result<bool> {
-        self.conn.query_row(
-            "SELECT EXISTS(SELECT 1 FROM stuck_folders WHERE playlist_id = ?1 AND folder_color = ?2)",
+    pub fn is_folder_stuck(&self, playlist_id: i64, folder_color: &str) -> AppResult<bool> {
+        let folder_color = &self.resolve_folder(folder_color)?;
+        let stuck = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM folder_visibility WHERE playlist_id = ?1 AND folder_color = ?2 AND pinned)",
             params![playlist_id, folder_color],
             |row| row.get(0),
-        )
+        )?;
+        Ok(stuck)
     }
 
-    pub fn get_all_stuck_folders(&self) -> Result<Vec<(i64, String)>> {
+    pub fn get_all_stuck_folders(&self) -> AppResult<Vec<(i64, String)>> {
         let mut stmt = self.conn.prepare(
-            "SELECT playlist_id, folder_color FROM stuck_folders"
+            "SELECT playlist_id, folder_color FROM folder_visibility WHERE pinned"
         )?;
 
         let folder_iter = stmt.query_map([], |row| {
             Ok((row.get(0)?, row.get(1)?))
         })?;
 
-        folder_iter.collect()
+        Ok(folder_iter.collect::<Result<Vec<_>, _>>()?)
     }
 
     // Folder Metadata operations
@@ -1010,7 +1384,8 @@ impl Database {
         &self,
         playlist_id: i64,
         folder_color: &str,
-    ) -> Result<Option<(String, String, Option<String>)>> {
+    ) -> AppResult<Option<FolderMetadata>> {
+        let folder_color = &self.resolve_folder(folder_color)?;
         let mut stmt = self.conn.prepare(
             "SELECT custom_name, description, custom_ascii FROM folder_metadata WHERE playlist_id = ?1 AND folder_color = ?2"
         )?;
@@ -1020,7 +1395,7 @@ impl Database {
         }) {
             Ok(data) => Ok(Some(data)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         }
     }
 
@@ -1031,7 +1406,8 @@ impl Database {
         name: Option<&str>,
         description: Option<&str>,
         custom_ascii: Option<&str>,
-    ) -> Result<bool> {
+    ) -> AppResult<bool> {
+        let folder_color = &self.resolve_folder(folder_color)?;
         let now = Utc::now().to_rfc3339();
 
         // Check if exists
@@ -1095,7 +1471,7 @@ impl Database {
     pub fn get_playlists_for_video_ids(
         &self,
         video_ids: &[String],
-    ) -> Result<HashMap<String, Vec<String>>> {
+    ) -> AppResult<HashMap<String, Vec<String>>> {
         if video_ids.is_empty() {
             return Ok(HashMap::new());
         }
@@ -1106,7 +1482,7 @@ impl Database {
             "SELECT pi.video_id, p.name 
              FROM playlist_items pi
              INNER JOIN playlists p ON pi.playlist_id = p.id
-             WHERE pi.video_id IN ({})",
+             WHERE pi.video_id IN ({}) AND pi.deleted_at IS NULL AND p.deleted_at IS NULL",
             placeholders.join(",")
         );
 
@@ -1138,11 +1514,12 @@ impl Database {
 
 
 
-    pub fn get_watch_history(&self, limit: i32) -> Result<Vec<WatchHistory>> {
+    pub fn get_watch_history(&self, limit: i32) -> AppResult<Vec<WatchHistory>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, video_url, video_id, title, thumbnail_url, watched_at 
-             FROM watch_history 
-             ORDER BY watched_at DESC 
+            "SELECT wh.id, wh.video_url, wh.video_id, COALESCE(v.title, wh.title), COALESCE(v.thumbnail_url, wh.thumbnail_url), wh.watched_at
+             FROM watch_history wh
+             LEFT JOIN videos v ON v.video_id = wh.video_id
+             ORDER BY wh.watched_at DESC
              LIMIT ?1",
         )?;
 
@@ -1162,12 +1539,12 @@ impl Database {
         Ok(history)
     }
 
-    pub fn clear_watch_history(&self) -> Result<bool> {
+    pub fn clear_watch_history(&self) -> AppResult<bool> {
         self.conn.execute("DELETE FROM watch_history", [])?;
         Ok(true)
     }
 
-    pub fn get_watched_video_ids(&self) -> Result<Vec<String>> {
+    pub fn get_watched_video_ids(&self) -> AppResult<Vec<String>> {
         // Only return video IDs that have >= 85% progress
         let mut stmt = self.conn.prepare(
             "SELECT DISTINCT video_id FROM video_progress WHERE progress_percentage >= 85.0",
@@ -1189,7 +1566,7 @@ impl Database {
         video_url: &str,
         duration: Option<f64>,
         current_time: f64,
-    ) -> Result<i64> {
+    ) -> AppResult<i64> {
         let now = Utc::now().to_rfc3339();
 
         // Check if record exists and get existing data
@@ -1243,7 +1620,7 @@ impl Database {
         Ok(self.conn.last_insert_rowid())
     }
 
-    pub fn get_video_progress(&self, video_id: &str) -> Result<Option<VideoProgress>> {
+    pub fn get_video_progress(&self, video_id: &str) -> AppResult<Option<VideoProgress>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, video_id, video_url, duration, last_progress, progress_percentage, last_updated, has_fully_watched 
              FROM video_progress 
@@ -1264,11 +1641,11 @@ impl Database {
         }) {
             Ok(progress) => Ok(Some(progress)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         }
     }
 
-    pub fn get_all_video_progress(&self) -> Result<Vec<VideoProgress>> {
+    pub fn get_all_video_progress(&self) -> AppResult<Vec<VideoProgress>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, video_id, video_url, duration, last_progress, progress_percentage, last_updated, has_fully_watched 
              FROM video_progress"
@@ -1291,4 +1668,1089 @@ impl Database {
 
         Ok(progress)
     }
+
+    // Video metadata operations
+    pub fn get_video(&self, video_id: &str) -> AppResult<Option<Video>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM videos v WHERE v.video_id = ?1",
+            VIDEO_COLUMNS
+        ))?;
+
+        match stmt.query_row(params![video_id], video_from_row) {
+            Ok(video) => Ok(Some(video)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Update a video's metadata in every playlist that contains it. Fields
+    /// left as `None` keep their current value.
+    pub fn update_video_metadata(
+        &self,
+        video_id: &str,
+        title: Option<&str>,
+        thumbnail_url: Option<&str>,
+        author: Option<&str>,
+        view_count: Option<&str>,
+        published_at: Option<&str>,
+    ) -> AppResult<bool> {
+        let rows = self.conn.execute(
+            "UPDATE videos SET
+                title = COALESCE(?1, title),
+                thumbnail_url = COALESCE(?2, thumbnail_url),
+                author = COALESCE(?3, author),
+                view_count = COALESCE(?4, view_count),
+                published_at = COALESCE(?5, published_at),
+                updated_at = ?6
+             WHERE video_id = ?7",
+            params![
+                title,
+                thumbnail_url,
+                author,
+                view_count,
+                published_at,
+                Utc::now().to_rfc3339(),
+                video_id
+            ],
+        )?;
+        Ok(rows > 0)
+    }
+
+    pub fn get_playlist_item(&self, item_id: i64) -> AppResult<Option<PlaylistItem>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("{} WHERE pi.id = ?1 AND pi.deleted_at IS NULL", ITEM_SELECT))?;
+
+        match stmt.query_row(params![item_id], playlist_item_from_row) {
+            Ok(item) => Ok(Some(item)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Search operations
+    pub fn search_library(&self, query: &str, limit: Option<u32>) -> AppResult<Vec<SearchHit>> {
+        let Some(fts) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        // Title matches count most, then author, then descriptions. Nothing in
+        // the trash shows up.
+        let mut stmt = self.conn.prepare(
+            "SELECT s.kind, s.source_id, s.playlist_id, p.name, s.folder_color,
+                    snippet(library_search, 0, char(57344), char(57345), '…', 12),
+                    snippet(library_search, -1, char(57344), char(57345), '…', 12),
+                    bm25(library_search, 10.0, 5.0, 2.0) AS rank
+             FROM library_search s
+             INNER JOIN playlists p ON p.id = s.playlist_id
+             LEFT JOIN playlist_items pi ON s.kind = 'item' AND pi.id = s.source_id
+             WHERE library_search MATCH ?1 AND p.deleted_at IS NULL AND pi.deleted_at IS NULL
+             ORDER BY rank
+             LIMIT ?2",
+        )?;
+
+        let rows = stmt
+            .query_map(params![fts, limit.unwrap_or(DEFAULT_SEARCH_LIMIT)], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,         // kind
+                    row.get::<_, i64>(1)?,            // source_id
+                    row.get::<_, i64>(2)?,            // playlist_id
+                    row.get::<_, String>(3)?,         // playlist_name
+                    row.get::<_, Option<String>>(4)?, // folder_color
+                    row.get::<_, Option<String>>(5)?, // title_snippet
+                    row.get::<_, Option<String>>(6)?, // snippet
+                    row.get::<_, f64>(7)?,            // rank
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut hits = Vec::with_capacity(rows.len());
+        for (
+            kind,
+            source_id,
+            playlist_id,
+            playlist_name,
+            folder_color,
+            title_snippet,
+            snippet,
+            rank,
+        ) in rows
+        {
+            let (item, folder_colors) = if kind == "item" {
+                (
+                    self.get_playlist_item(source_id)?,
+                    self.get_video_folder_assignments(playlist_id, source_id)?,
+                )
+            } else {
+                (None, Vec::new())
+            };
+
+            hits.push(SearchHit {
+                kind,
+                playlist_id,
+                playlist_name,
+                item,
+                folder_color,
+                folder_colors,
+                title_snippet: title_snippet.as_deref().map(highlight),
+                snippet: snippet.as_deref().map(highlight),
+                rank,
+            });
+        }
+
+        Ok(hits)
+    }
+
+    // Export / import operations
+    pub fn export_playlist(&self, playlist_id: i64) -> AppResult<PlaylistExport> {
+        let playlist = self
+            .get_playlist(playlist_id)?
+            .ok_or_else(|| AppError::not_found("playlist", playlist_id))?;
+        let mut assignments = self.get_all_folder_assignments_for_playlist(playlist_id)?;
+
+        // A smart playlist's items come from its rules, not from the bundle.
+        // Its rules refer to playlists by id, so name those for the import.
+        let (items, rule_playlists) = match &playlist.smart_rules {
+            Some(rules) => (Vec::new(), self.rule_playlist_names(rules)?),
+            None => (self.get_playlist_items(playlist_id)?, HashMap::new()),
+        };
+        let items = items
+            .into_iter()
+            .map(|item| ExportedItem {
+                folders: assignments.remove(&item.id.to_string()).unwrap_or_default(),
+                video_id: item.video_id,
+                video_url: item.video_url,
+                title: item.title,
+                thumbnail_url: item.thumbnail_url,
+                author: item.author,
+                view_count: item.view_count,
+                published_at: item.published_at,
+                is_local: item.is_local,
+                position: item.position,
+                added_at: item.added_at,
+            })
+            .collect();
+
+        // A folder can have visibility settings without having metadata, and
+        // vice versa
+        let mut stmt = self.conn.prepare(
+            "SELECT colors.folder_color, fm.custom_name, fm.description, fm.custom_ascii,
+                    COALESCE(fv.pinned, 0), COALESCE(fv.hidden, 0), COALESCE(fv.collapsed, 0), fv.sort_order
+             FROM (
+                SELECT folder_color FROM folder_metadata WHERE playlist_id = ?1
+                UNION
+                SELECT folder_color FROM folder_visibility WHERE playlist_id = ?1
+             ) colors
+             LEFT JOIN folder_metadata fm ON fm.playlist_id = ?1 AND fm.folder_color = colors.folder_color
+             LEFT JOIN folder_visibility fv ON fv.playlist_id = ?1 AND fv.folder_color = colors.folder_color
+             ORDER BY colors.folder_color",
+        )?;
+
+        let folders = stmt
+            .query_map(params![playlist_id], |row| {
+                Ok(ExportedFolder {
+                    color: row.get(0)?,
+                    custom_name: row.get(1)?,
+                    description: row.get(2)?,
+                    custom_ascii: row.get(3)?,
+                    stuck: row.get(4)?,
+                    hidden: row.get(5)?,
+                    collapsed: row.get(6)?,
+                    sort_order: row.get(7)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(PlaylistExport {
+            name: playlist.name,
+            description: playlist.description,
+            custom_ascii: playlist.custom_ascii,
+            custom_thumbnail_url: playlist.custom_thumbnail_url,
+            created_at: playlist.created_at,
+            smart_rules: playlist.smart_rules,
+            rule_playlists,
+            items,
+            folders,
+        })
+    }
+
+    pub fn export_library(&self) -> AppResult<Vec<PlaylistExport>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM playlists WHERE deleted_at IS NULL ORDER BY id")?;
+        let playlist_ids: Vec<i64> = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        playlist_ids
+            .into_iter()
+            .map(|id| self.export_playlist(id))
+            .collect()
+    }
+
+    pub fn import_playlist(&self, export: &PlaylistExport) -> AppResult<ImportReport> {
+        let tx = self.conn.unchecked_transaction()?;
+        let report = self.insert_playlist_export(export, &HashMap::new())?;
+        tx.commit()?;
+        Ok(report)
+    }
+
+    /// Import every playlist and like in one transaction, so a failure leaves
+    /// the library untouched. Smart playlists' rules point at the playlists
+    /// imported alongside them where the bundle has them.
+    pub fn import_library(
+        &self,
+        exports: &[PlaylistExport],
+        likes: &[ExportedLike],
+    ) -> AppResult<Vec<ImportReport>> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        // Regular playlists first, so smart rules can find their new ids
+        let mut imported_ids = HashMap::new();
+        let mut reports = Vec::with_capacity(exports.len());
+        for smart in [false, true] {
+            for (index, export) in exports.iter().enumerate() {
+                if export.smart_rules.is_some() != smart {
+                    continue;
+                }
+                let report = self.insert_playlist_export(export, &imported_ids)?;
+                imported_ids
+                    .entry(export.name.clone())
+                    .or_insert(report.playlist_id);
+                reports.push((index, report));
+            }
+        }
+        reports.sort_by_key(|(index, _)| *index);
+
+        self.insert_exported_likes(likes)?;
+        tx.commit()?;
+        Ok(reports.into_iter().map(|(_, report)| report).collect())
+    }
+
+    // Callers own the transaction. `imported_ids` maps the names of playlists
+    // already imported from the same bundle to their new ids.
+    fn insert_playlist_export(
+        &self,
+        export: &PlaylistExport,
+        imported_ids: &HashMap<String, i64>,
+    ) -> AppResult<ImportReport> {
+        if export.name.trim().is_empty() {
+            return Err(AppError::validation(
+                "name",
+                "playlist name cannot be empty",
+            ));
+        }
+
+        let name = self.unique_playlist_name(&export.name)?;
+        let now = Utc::now().to_rfc3339();
+
+        let (smart_rules, skipped_rules) = match &export.smart_rules {
+            Some(rules) => {
+                let (rules, skipped) = self.import_smart_rules(
+                    &export.name,
+                    rules,
+                    &export.rule_playlists,
+                    imported_ids,
+                )?;
+                (Some(smart_rules_to_json(&rules)?), skipped)
+            }
+            None => (None, Vec::new()),
+        };
+        self.conn.execute(
+            "INSERT INTO playlists (name, description, custom_ascii, custom_thumbnail_url, smart_rules, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                name,
+                export.description,
+                export.custom_ascii,
+                export.custom_thumbnail_url,
+                smart_rules,
+                export.created_at,
+                now
+            ],
+        )?;
+        let playlist_id = self.conn.last_insert_rowid();
+
+        let mut items: Vec<&ExportedItem> = if export.smart_rules.is_some() {
+            Vec::new()
+        } else {
+            export.items.iter().collect()
+        };
+        items.sort_by_key(|item| item.position);
+
+        let mut imported = 0;
+        let mut skipped = Vec::new();
+        let mut skipped_folders = BTreeSet::new();
+
+        for item in items {
+            let reason = if item.video_id.trim().is_empty() {
+                Some("missing video id")
+            } else if item.video_url.trim().is_empty() {
+                Some("missing video url")
+            } else {
+                None
+            };
+
+            if let Some(reason) = reason {
+                skipped.push(SkippedItem {
+                    position: item.position,
+                    video_id: item.video_id.clone(),
+                    reason: reason.to_string(),
+                });
+                continue;
+            }
+
+            self.upsert_video(&Video {
+                video_id: item.video_id.clone(),
+                video_url: item.video_url.clone(),
+                title: item.title.clone(),
+                thumbnail_url: item.thumbnail_url.clone(),
+                author: item.author.clone(),
+                view_count: item.view_count.clone(),
+                published_at: item.published_at.clone(),
+                is_local: item.is_local,
+                created_at: now.clone(),
+                updated_at: now.clone(),
+            })?;
+
+            // Renumbered from 1 so skipped items and hand-edited bundles
+            // can't leave gaps or duplicate positions
+            self.conn.execute(
+                "INSERT INTO playlist_items (playlist_id, video_id, position, added_at) VALUES (?1, ?2, ?3, ?4)",
+                params![playlist_id, item.video_id, imported as i32 + 1, item.added_at],
+            )?;
+            let item_id = self.conn.last_insert_rowid();
+
+            for color in &item.folders {
+                let Some(color) = self.find_folder_key(color)? else {
+                    skipped_folders.insert(color.trim().to_lowercase());
+                    continue;
+                };
+                self.conn.execute(
+                    "INSERT OR IGNORE INTO video_folder_assignments (playlist_id, item_id, folder_color, created_at)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![playlist_id, item_id, color, now],
+                )?;
+            }
+
+            imported += 1;
+        }
+
+        for folder in &export.folders {
+            // Folders the bundle's library had but this one doesn't are
+            // reported rather than created, since the bundle has no name or hex
+            let Some(color) = self.find_folder_key(&folder.color)? else {
+                skipped_folders.insert(folder.color.trim().to_lowercase());
+                continue;
+            };
+            if folder.custom_name.is_some()
+                || folder.description.is_some()
+                || folder.custom_ascii.is_some()
+            {
+                self.conn.execute(
+                    "INSERT INTO folder_metadata (playlist_id, folder_color, custom_name, description, custom_ascii, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+                    params![
+                        playlist_id,
+                        color,
+                        folder.custom_name,
+                        folder.description,
+                        folder.custom_ascii,
+                        now
+                    ],
+                )?;
+            }
+
+            if folder.stuck || folder.hidden || folder.collapsed || folder.sort_order.is_some() {
+                self.conn.execute(
+                    "INSERT INTO folder_visibility (playlist_id, folder_color, pinned, hidden, collapsed, sort_order, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        playlist_id,
+                        color,
+                        folder.stuck,
+                        folder.hidden,
+                        folder.collapsed,
+                        folder.sort_order,
+                        now
+                    ],
+                )?;
+            }
+        }
+
+        Ok(ImportReport {
+            playlist_id,
+            name,
+            original_name: export.name.clone(),
+            imported,
+            skipped,
+            skipped_rules,
+            skipped_folders: skipped_folders.into_iter().collect(),
+        })
+    }
+
+    // "Name", then "Name (2)", "Name (3)", ...
+    fn unique_playlist_name(&self, name: &str) -> AppResult<String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT EXISTS(SELECT 1 FROM playlists WHERE name = ?1 AND deleted_at IS NULL)")?;
+
+        let mut candidate = name.to_string();
+        let mut n = 2;
+        while stmt.query_row(params![candidate], |row| row.get::<_, bool>(0))? {
+            candidate = format!("{} ({})", name, n);
+            n += 1;
+        }
+
+        Ok(candidate)
+    }
+
+    // Backup operations
+
+    /// Copy the whole database to `dest` with SQLite's online backup API.
+    pub fn backup_to(&self, dest: &Path) -> AppResult<()> {
+        let mut dest_conn = Connection::open(dest)?;
+        copy_database(&self.conn, &mut dest_conn)?;
+
+        // Leave a self-contained file rather than one that expects a -wal beside it
+        dest_conn.pragma_update_and_check(None, "journal_mode", "DELETE", |_| Ok(()))?;
+        Ok(())
+    }
+
+    /// Replace this database's contents with those of `src`, migrated if the
+    /// backup predates the current schema. The backup is migrated in memory
+    /// first, so one that can't be migrated leaves this database untouched.
+    /// Other connections see either the old or the new database, never a mix.
+    pub fn restore_from(&mut self, src: &Path) -> AppResult<()> {
+        let src_conn = Connection::open_with_flags(src, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut staged = Connection::open_in_memory()?;
+        copy_database(&src_conn, &mut staged)?;
+        staged.pragma_update(None, "foreign_keys", true)?;
+        migrations::run(&mut staged)?;
+
+        copy_database(&staged, &mut self.conn)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{SmartRule, SmartRules};
+
+    // Small deterministic generator so failures replay from the seed alone
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((self.0 >> 33) as usize) % n.max(1)
+        }
+
+        fn pick<T: Copy>(&mut self, values: &[T]) -> Option<T> {
+            (!values.is_empty()).then(|| values[self.below(values.len())])
+        }
+    }
+
+    fn new_item(video_id: &str) -> NewPlaylistItem {
+        NewPlaylistItem {
+            video_url: format!("https://youtu.be/{}", video_id),
+            video_id: video_id.to_string(),
+            title: Some(video_id.to_string()),
+            thumbnail_url: None,
+            author: None,
+            view_count: None,
+            published_at: None,
+            is_local: false,
+            folder_colors: Vec::new(),
+        }
+    }
+
+    fn live_ids(db: &Database, playlist_id: i64) -> Vec<i64> {
+        db.get_playlist_items(playlist_id)
+            .unwrap()
+            .iter()
+            .map(|i| i.id)
+            .collect()
+    }
+
+    fn assert_contiguous(db: &Database, playlists: &[i64], context: &str) {
+        for &playlist_id in playlists {
+            let mut positions: Vec<i32> = db
+                .get_playlist_items(playlist_id)
+                .unwrap()
+                .iter()
+                .map(|i| i.position)
+                .collect();
+            positions.sort_unstable();
+            let expected: Vec<i32> = (1..=positions.len() as i32).collect();
+            assert_eq!(
+                positions, expected,
+                "playlist {} after {}",
+                playlist_id, context
+            );
+        }
+        assert_eq!(
+            db.repair_playlist_positions(None).unwrap(),
+            0,
+            "after {}",
+            context
+        );
+    }
+
+    #[test]
+    fn random_operations_keep_positions_contiguous() {
+        for seed in 1..=8u64 {
+            let db = Database::new(Some(":memory:")).unwrap();
+            let playlists = [
+                db.create_playlist("a", None).unwrap(),
+                db.create_playlist("b", None).unwrap(),
+            ];
+            let mut rng = Lcg(seed);
+            let mut next_video = 0;
+
+            for step in 0..150 {
+                let playlist_id = playlists[rng.below(2)];
+                let other_id = playlists[rng.below(2)];
+                let ids = live_ids(&db, playlist_id);
+                let n = ids.len() as i32;
+                // Positions past either end are clamped
+                let target = match rng.below(4) {
+                    0 => 0,
+                    1 => n + 1 + rng.below(5) as i32,
+                    _ => 1 + rng.below(n.max(1) as usize) as i32,
+                };
+                let selection: Vec<i64> = (0..1 + rng.below(3))
+                    .filter_map(|_| rng.pick(&ids))
+                    .collect();
+
+                let op = rng.below(9);
+                match op {
+                    0 => {
+                        next_video += 1;
+                        let video_id = format!("v{}", next_video);
+                        db.add_video_to_playlist(
+                            playlist_id,
+                            "u",
+                            &video_id,
+                            None,
+                            None,
+                            false,
+                            None,
+                            None,
+                            None,
+                        )
+                        .unwrap();
+                    }
+                    1 => {
+                        let items: Vec<NewPlaylistItem> = (0..1 + rng.below(4))
+                            .map(|_| {
+                                next_video += 1;
+                                new_item(&format!("v{}", next_video))
+                            })
+                            .collect();
+                        db.add_videos_to_playlist(playlist_id, &items, false)
+                            .unwrap();
+                    }
+                    2 => {
+                        if let Some(&item_id) = selection.first() {
+                            assert!(db.remove_video_from_playlist(playlist_id, item_id).unwrap());
+                        }
+                    }
+                    3 => {
+                        if let Some(&item_id) = selection.first() {
+                            assert!(db
+                                .reorder_playlist_item(playlist_id, item_id, target)
+                                .unwrap());
+                        }
+                    }
+                    4 => {
+                        let at = (rng.below(3) > 0).then_some(target);
+                        db.move_items(&selection, other_id, at, rng.below(2) == 0)
+                            .unwrap();
+                    }
+                    5 => {
+                        let at = (rng.below(3) > 0).then_some(target);
+                        db.copy_items(&selection, other_id, at, rng.below(2) == 0)
+                            .unwrap();
+                    }
+                    6 => {
+                        let trashed: Vec<i64> = db
+                            .list_trash()
+                            .unwrap()
+                            .iter()
+                            .filter(|e| e.kind == "item")
+                            .map(|e| e.id)
+                            .collect();
+                        if let Some(trash_id) = rng.pick(&trashed) {
+                            db.restore_from_trash(trash_id).unwrap();
+                        }
+                    }
+                    // Undo and redo refuse, without side effects, when a later
+                    // operation got in the way
+                    7 => match db.undo_last() {
+                        Ok(_) | Err(AppError::Conflict { .. }) => {}
+                        Err(e) => panic!("undo failed: {}", e),
+                    },
+                    _ => match db.redo() {
+                        Ok(_) | Err(AppError::Conflict { .. }) => {}
+                        Err(e) => panic!("redo failed: {}", e),
+                    },
+                }
+
+                assert_contiguous(
+                    &db,
+                    &playlists,
+                    &format!("seed {} step {} (op {})", seed, step, op),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn failed_restore_leaves_live_database_untouched() {
+        let dir = std::env::temp_dir();
+        let live_path = dir.join(format!("restore-live-{}.db", std::process::id()));
+        let backup_path = dir.join(format!("restore-backup-{}.db", std::process::id()));
+        for path in [&live_path, &backup_path] {
+            let _ = std::fs::remove_file(path);
+        }
+
+        // An unversioned database whose folder color migration 19 rejects
+        let backup = Connection::open(&backup_path).unwrap();
+        backup
+            .execute_batch(
+                "CREATE TABLE playlists (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    description TEXT,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+                CREATE TABLE playlist_items (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    playlist_id INTEGER NOT NULL,
+                    video_url TEXT NOT NULL,
+                    video_id TEXT NOT NULL,
+                    title TEXT,
+                    thumbnail_url TEXT,
+                    position INTEGER NOT NULL,
+                    added_at TEXT NOT NULL
+                );
+                CREATE TABLE video_folder_assignments (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    playlist_id INTEGER NOT NULL,
+                    item_id INTEGER NOT NULL,
+                    folder_color TEXT NOT NULL,
+                    created_at TEXT NOT NULL
+                );
+                INSERT INTO playlists VALUES (1, 'Old', NULL, '1', '1');
+                INSERT INTO playlist_items VALUES (1, 1, 'https://youtu.be/o', 'o', 'Old one', NULL, 0, '1');
+                INSERT INTO video_folder_assignments VALUES (1, 1, 1, 'magenta', '1');",
+            )
+            .unwrap();
+        drop(backup);
+
+        let mut db = Database::new(Some(live_path.to_str().unwrap())).unwrap();
+        let id = db.create_playlist("Live", None).unwrap();
+        db.add_video_to_playlist(
+            id,
+            "https://youtu.be/l",
+            "l",
+            Some("Live one"),
+            None,
+            false,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        db.assign_video_to_folder(id, 1, "red").unwrap();
+
+        let err = db.restore_from(&backup_path).unwrap_err();
+        assert!(err.to_string().contains("magenta"), "{}", err);
+
+        let playlists: Vec<String> = db
+            .get_all_playlists()
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(playlists, vec!["Live".to_string()]);
+        assert_eq!(live_ids(&db, id), vec![1]);
+        assert_eq!(db.get_videos_in_folder(id, "red").unwrap().len(), 1);
+        assert_eq!(
+            migrations::user_version(&db.conn).unwrap(),
+            migrations::latest_version()
+        );
+
+        // Reopening sees the same, untouched database
+        drop(db);
+        let db = Database::new(Some(live_path.to_str().unwrap())).unwrap();
+        assert_eq!(live_ids(&db, id), vec![1]);
+
+        drop(db);
+        for path in [&live_path, &backup_path] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    // An export as JSON, minus the name, which imports may change
+    fn export_without_name(db: &Database, playlist_id: i64) -> serde_json::Value {
+        let mut value = serde_json::to_value(db.export_playlist(playlist_id).unwrap()).unwrap();
+        value.as_object_mut().unwrap().remove("name");
+        value
+    }
+
+    // Serialize and parse the export the way a bundle file would, then import it
+    fn reimport(db: &Database, playlist_id: i64) -> ImportReport {
+        let json = serde_json::to_string(&db.export_playlist(playlist_id).unwrap()).unwrap();
+        let export: PlaylistExport = serde_json::from_str(&json).unwrap();
+        db.import_playlist(&export).unwrap()
+    }
+
+    #[test]
+    fn export_import_round_trip_keeps_everything_but_the_name() {
+        let db = Database::new(Some(":memory:")).unwrap();
+        let id = db.create_playlist("Mix", Some("all sorts")).unwrap();
+        db.update_playlist(id, None, None, Some("(^_^)"), Some("cover.png"))
+            .unwrap();
+
+        let first = db
+            .add_video_to_playlist(
+                id,
+                "https://youtu.be/a",
+                "a",
+                Some("Alpha"),
+                Some("a.jpg"),
+                false,
+                Some("Ann"),
+                Some("1200"),
+                Some("2020-02-02"),
+            )
+            .unwrap();
+        let second = db
+            .add_video_to_playlist(
+                id,
+                "/music/b.mp3",
+                "b",
+                Some("Bravo"),
+                None,
+                true,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        db.add_video_to_playlist(
+            id,
+            "https://youtu.be/c",
+            "c",
+            None,
+            None,
+            false,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        db.assign_video_to_folder(id, first, "red").unwrap();
+        db.assign_video_to_folder(id, first, "blue").unwrap();
+        db.assign_video_to_folder(id, second, "green").unwrap();
+        db.set_folder_metadata(id, "red", Some("Reds"), Some("warm"), Some("<3"))
+            .unwrap();
+        db.set_folder_metadata(id, "green", None, Some("leafy"), None)
+            .unwrap();
+        db.set_folder_visibility(id, "red", Some(true), None, None)
+            .unwrap();
+        db.set_folder_visibility(id, "blue", None, Some(true), Some(true))
+            .unwrap();
+        // Visibility alone, on a folder without items
+        db.set_folder_visibility(id, "pink", None, None, Some(true))
+            .unwrap();
+        db.set_folder_order(id, &["green".to_string(), "red".to_string()])
+            .unwrap();
+
+        let original = export_without_name(&db, id);
+        let folders = original["folders"].as_array().unwrap();
+        assert_eq!(folders.len(), 4);
+        assert_eq!(original["items"][0]["folders"].as_array().unwrap().len(), 2);
+
+        let copy = reimport(&db, id);
+        assert_eq!(copy.original_name, "Mix");
+        assert_eq!(copy.name, "Mix (2)");
+        assert_eq!(copy.imported, 3);
+        assert!(copy.skipped.is_empty());
+        assert!(copy.skipped_folders.is_empty());
+        assert_eq!(export_without_name(&db, copy.playlist_id), original);
+
+        // Importing again picks the next free suffix
+        let again = reimport(&db, id);
+        assert_eq!(again.name, "Mix (3)");
+
+        // A copy of the copy is still the same playlist
+        let copy_of_copy = reimport(&db, copy.playlist_id);
+        assert_eq!(copy_of_copy.name, "Mix (2) (2)");
+        assert_eq!(export_without_name(&db, copy_of_copy.playlist_id), original);
+    }
+
+    fn in_folder_rules(playlist_id: i64, folder_color: &str) -> SmartRules {
+        SmartRules {
+            match_all: true,
+            rules: vec![
+                SmartRule::InPlaylist { playlist_id },
+                SmartRule::InFolder {
+                    playlist_id,
+                    folder_color: folder_color.to_string(),
+                },
+            ],
+        }
+    }
+
+    fn smart_playlist_ids(db: &Database, playlist_id: i64) -> Vec<i64> {
+        db.get_smart_rules(playlist_id)
+            .unwrap()
+            .unwrap()
+            .rules
+            .iter()
+            .filter_map(|rule| match rule {
+                SmartRule::InPlaylist { playlist_id } | SmartRule::InFolder { playlist_id, .. } => {
+                    Some(*playlist_id)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn imported_smart_rules_point_at_the_imported_playlists() {
+        let source = Database::new(Some(":memory:")).unwrap();
+        let mix = source.create_playlist("Mix", None).unwrap();
+        let item = source
+            .add_video_to_playlist(mix, "u", "a", None, None, false, None, None, None)
+            .unwrap();
+        source.assign_video_to_folder(mix, item, "red").unwrap();
+        let reds = source
+            .create_smart_playlist("Reds", None, &in_folder_rules(mix, "#EF4444"))
+            .unwrap();
+        // The smart playlist comes before the playlist its rules name
+        let mut exports = source.export_library().unwrap();
+        exports.reverse();
+        assert_eq!(
+            exports[0].rule_playlists.get(&mix).map(String::as_str),
+            Some("Mix")
+        );
+
+        // A library whose ids are taken by unrelated playlists, one of them
+        // with the same name as the smart playlist's source
+        let target = Database::new(Some(":memory:")).unwrap();
+        let unrelated = target.create_playlist("Other", None).unwrap();
+        let existing_mix = target.create_playlist("Mix", None).unwrap();
+        let reports = target.import_library(&exports, &[]).unwrap();
+
+        assert_eq!(reports[0].original_name, "Reds");
+        assert!(reports[0].skipped_rules.is_empty());
+        assert_eq!(reports[1].name, "Mix (2)");
+        let new_mix = reports[1].playlist_id;
+        assert!(![mix, unrelated, existing_mix].contains(&new_mix));
+        assert_eq!(
+            smart_playlist_ids(&target, reports[0].playlist_id),
+            vec![new_mix, new_mix]
+        );
+        let items = target.get_playlist_items(reports[0].playlist_id).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].playlist_id, new_mix);
+
+        // Imported on its own, the smart playlist finds Mix by name
+        let single = target.export_playlist(reports[0].playlist_id).unwrap();
+        assert_eq!(
+            single.rule_playlists.get(&new_mix).map(String::as_str),
+            Some("Mix (2)")
+        );
+        let report = source
+            .import_playlist(&source.export_playlist(reds).unwrap())
+            .unwrap();
+        assert_eq!(
+            smart_playlist_ids(&source, report.playlist_id),
+            vec![mix, mix]
+        );
+    }
+
+    #[test]
+    fn smart_rules_that_cannot_be_resolved_are_reported_or_refused() {
+        let db = Database::new(Some(":memory:")).unwrap();
+        let export = |rules: Vec<SmartRule>, names: &[(i64, &str)]| PlaylistExport {
+            name: "Smart".to_string(),
+            description: None,
+            custom_ascii: None,
+            custom_thumbnail_url: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            smart_rules: Some(SmartRules {
+                match_all: false,
+                rules,
+            }),
+            rule_playlists: names
+                .iter()
+                .map(|(id, name)| (*id, name.to_string()))
+                .collect(),
+            items: Vec::new(),
+            folders: Vec::new(),
+        };
+        let mix = db.create_playlist("Mix", None).unwrap();
+
+        let report = db
+            .import_playlist(&export(
+                vec![
+                    SmartRule::Author {
+                        author: "Ann".to_string(),
+                    },
+                    // Named, but no such playlist here
+                    SmartRule::InPlaylist { playlist_id: 40 },
+                    // Not named at all, as in bundles from older builds
+                    SmartRule::InPlaylist { playlist_id: mix },
+                    // Playlist found, folder unknown
+                    SmartRule::InFolder {
+                        playlist_id: 41,
+                        folder_color: "magenta".to_string(),
+                    },
+                ],
+                &[(40, "Gone"), (41, "Mix")],
+            ))
+            .unwrap();
+        let reasons: Vec<&str> = report
+            .skipped_rules
+            .iter()
+            .map(|skipped| skipped.reason.as_str())
+            .collect();
+        assert_eq!(reasons.len(), 3, "{:?}", reasons);
+        assert!(reasons[0].contains("Gone"), "{}", reasons[0]);
+        assert!(
+            reasons[1].contains("isn't named in the bundle"),
+            "{}",
+            reasons[1]
+        );
+        assert!(reasons[2].contains("magenta"), "{}", reasons[2]);
+        let rules = db.get_smart_rules(report.playlist_id).unwrap().unwrap();
+        assert_eq!(rules.rules.len(), 1);
+        assert!(matches!(rules.rules[0], SmartRule::Author { .. }));
+
+        // Nothing left to match on: refused, and nothing is created
+        let before = db.get_all_playlists().unwrap().len();
+        let err = db
+            .import_playlist(&export(
+                vec![SmartRule::InPlaylist { playlist_id: 40 }],
+                &[(40, "Gone")],
+            ))
+            .unwrap_err();
+        assert!(matches!(err, AppError::Validation { .. }), "{:?}", err);
+        assert_eq!(db.get_all_playlists().unwrap().len(), before);
+    }
+
+    #[test]
+    fn import_renumbers_positions_and_reports_unknown_folders() {
+        let db = Database::new(Some(":memory:")).unwrap();
+        let item = |video_id: &str, position: i32, folders: &[&str]| ExportedItem {
+            video_id: video_id.to_string(),
+            video_url: format!("https://youtu.be/{}", video_id),
+            title: None,
+            thumbnail_url: None,
+            author: None,
+            view_count: None,
+            published_at: None,
+            is_local: false,
+            position,
+            added_at: "2024-01-01T00:00:00Z".to_string(),
+            folders: folders.iter().map(|f| f.to_string()).collect(),
+        };
+        let folder = |color: &str| ExportedFolder {
+            color: color.to_string(),
+            custom_name: Some(format!("{} folder", color)),
+            description: None,
+            custom_ascii: None,
+            stuck: true,
+            hidden: false,
+            collapsed: false,
+            sort_order: None,
+        };
+        let export = PlaylistExport {
+            name: "Gappy".to_string(),
+            description: None,
+            custom_ascii: None,
+            custom_thumbnail_url: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            smart_rules: None,
+            rule_playlists: HashMap::new(),
+            items: vec![
+                item("c", 30, &["magenta"]),
+                item("a", 4, &["RED", "magenta"]),
+                item("", 12, &[]),
+                item("b", 12, &["#22C55E"]),
+            ],
+            folders: vec![folder("magenta"), folder("Red")],
+        };
+
+        let report = db.import_playlist(&export).unwrap();
+        assert_eq!(report.name, "Gappy");
+        assert_eq!(report.imported, 3);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].position, 12);
+        assert_eq!(report.skipped_folders, vec!["magenta".to_string()]);
+
+        let exported = db.export_playlist(report.playlist_id).unwrap();
+        let items: Vec<(&str, i32, Vec<String>)> = exported
+            .items
+            .iter()
+            .map(|i| (i.video_id.as_str(), i.position, i.folders.clone()))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("a", 1, vec!["red".to_string()]),
+                ("b", 2, vec!["green".to_string()]),
+                ("c", 3, vec![]),
+            ]
+        );
+        let folders: Vec<(&str, bool)> = exported
+            .folders
+            .iter()
+            .map(|f| (f.color.as_str(), f.stuck))
+            .collect();
+        assert_eq!(folders, vec![("red", true)]);
+        assert_eq!(
+            exported.folders[0].custom_name.as_deref(),
+            Some("Red folder")
+        );
+    }
+
+    #[test]
+    fn search_snippets_escape_markup_around_matches() {
+        let db = Database::new(Some(":memory:")).unwrap();
+        let playlist_id = db.create_playlist("p", None).unwrap();
+        db.add_video_to_playlist(
+            playlist_id,
+            "u",
+            "a",
+            Some("<img src=x onerror=alert(1)> cat & \"dog\""),
+            None,
+            false,
+            Some("O'Brien"),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let hits = db.search_library("cat", None).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].title_snippet.as_deref(),
+            Some("&lt;img src=x onerror=alert(1)&gt; <mark>cat</mark> &amp; &quot;dog&quot;")
+        );
+
+        let hits = db.search_library("brien", None).unwrap();
+        assert_eq!(hits[0].snippet.as_deref(), Some("O&#39;<mark>Brien</mark>"));
+    }
 }
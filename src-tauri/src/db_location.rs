@@ -0,0 +1,120 @@
+use crate::error::{AppError, AppResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DB_FILE_NAME: &str = "playlists.db";
+
+/// Environment variable that overrides where the database lives.
+pub const DB_PATH_ENV: &str = "YTTV_DB_PATH";
+
+/// Command line flag that overrides where the database lives. Accepts both
+/// `--db-path <path>` and `--db-path=<path>`, and wins over the env var.
+const DB_PATH_FLAG: &str = "--db-path";
+
+// SQLite keeps uncommitted state next to the main file; it has to move with it.
+const SIDECAR_SUFFIXES: &[&str] = &["-wal", "-shm", "-journal"];
+
+/// Work out which database file to open, creating its directory if needed.
+///
+/// Order of precedence: `--db-path`, then `YTTV_DB_PATH`, then (debug builds
+/// only) `../playlists.db` so the dev file watcher doesn't restart the app,
+/// and finally `playlists.db` inside the per-user app data directory.
+pub fn resolve(app_data_dir: &Path) -> AppResult<PathBuf> {
+    let override_path = path_from_args(std::env::args()).or_else(|| {
+        std::env::var_os(DB_PATH_ENV)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    });
+
+    if let Some(path) = override_path {
+        ensure_parent_dir(&path)?;
+        return Ok(path);
+    }
+
+    if cfg!(debug_assertions) {
+        return Ok(PathBuf::from("..").join(DB_FILE_NAME));
+    }
+
+    let path = app_data_dir.join(DB_FILE_NAME);
+    ensure_parent_dir(&path)?;
+
+    if !path.exists() {
+        if let Some(legacy) = legacy_path().filter(|p| p.exists()) {
+            move_database(&legacy, &path)?;
+            println!(
+                "Moved legacy database from {} to {}",
+                legacy.display(),
+                path.display()
+            );
+        }
+    }
+
+    Ok(path)
+}
+
+fn path_from_args(args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    let mut args = args.skip(1);
+    while let Some(arg) = args.next() {
+        if arg == DB_PATH_FLAG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(value) = arg
+            .strip_prefix(DB_PATH_FLAG)
+            .and_then(|v| v.strip_prefix('='))
+        {
+            return Some(PathBuf::from(value));
+        }
+    }
+    None
+}
+
+// Release builds used to open a relative "playlists.db", which ended up next to
+// the executable for anyone launching the app normally.
+fn legacy_path() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    Some(exe.parent()?.join(DB_FILE_NAME))
+}
+
+fn ensure_parent_dir(path: &Path) -> AppResult<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => {
+            fs::create_dir_all(dir).map_err(|e| AppError::io(dir.display().to_string(), e))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn move_database(from: &Path, to: &Path) -> AppResult<()> {
+    move_file(from, to)?;
+
+    for suffix in SIDECAR_SUFFIXES {
+        let from_sidecar = with_suffix(from, suffix);
+        if from_sidecar.exists() {
+            move_file(&from_sidecar, &with_suffix(to, suffix))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn move_file(from: &Path, to: &Path) -> AppResult<()> {
+    // rename fails across volumes (e.g. exe on D:, app data on C:), so fall
+    // back to copy + delete
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    fs::copy(from, to).map_err(|e| AppError::io(to.display().to_string(), e))?;
+
+    // The copy is what matters; a leftover legacy file is never read again
+    if let Err(e) = fs::remove_file(from) {
+        eprintln!("Failed to remove {} after copying: {}", from.display(), e);
+    }
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
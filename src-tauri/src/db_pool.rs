@@ -0,0 +1,118 @@
+use crate::database::Database;
+use crate::error::{AppError, AppResult};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const READER_POOL_SIZE: u32 = 4;
+
+// How long a command waits for a free reader before reporting BUSY.
+const READER_CHECKOUT_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct ReaderManager {
+    path: String,
+}
+
+impl r2d2::ManageConnection for ReaderManager {
+    type Connection = Database;
+    type Error = AppError;
+
+    fn connect(&self) -> AppResult<Database> {
+        Database::open_reader(&self.path)
+    }
+
+    fn is_valid(&self, db: &mut Database) -> AppResult<()> {
+        db.ping()
+    }
+
+    fn has_broken(&self, _db: &mut Database) -> bool {
+        false
+    }
+}
+
+/// Shared database handle managed as Tauri state.
+///
+/// The database runs in WAL mode so readers never wait on the writer: reads
+/// check out one of a small pool of `query_only` connections, while all writes
+/// go through a single connection behind a mutex. Both run on Tokio's blocking
+/// thread pool, so a slow query never stalls the async runtime or other
+/// commands.
+#[derive(Clone)]
+pub struct DbPool {
+    readers: r2d2::Pool<ReaderManager>,
+    writer: Arc<Mutex<Database>>,
+}
+
+impl DbPool {
+    pub fn open(path: &str) -> AppResult<Self> {
+        // The writer runs migrations, so it must exist before any reader opens
+        let writer = Database::new(Some(path))?;
+
+        let readers = r2d2::Pool::builder()
+            .max_size(READER_POOL_SIZE)
+            .min_idle(Some(1))
+            .connection_timeout(READER_CHECKOUT_TIMEOUT)
+            .build(ReaderManager {
+                path: path.to_string(),
+            })
+            .map_err(|e| AppError::Busy {
+                message: e.to_string(),
+            })?;
+
+        Ok(DbPool {
+            readers,
+            writer: Arc::new(Mutex::new(writer)),
+        })
+    }
+
+    /// Run `f` against a pooled read-only connection.
+    pub async fn read<T, F>(&self, f: F) -> AppResult<T>
+    where
+        F: FnOnce(&Database) -> AppResult<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let readers = self.readers.clone();
+        run_blocking(move || {
+            let db = readers.get().map_err(|e| AppError::Busy {
+                message: e.to_string(),
+            })?;
+            f(&db)
+        })
+        .await
+    }
+
+    /// Run `f` against the single writer connection.
+    pub async fn write<T, F>(&self, f: F) -> AppResult<T>
+    where
+        F: FnOnce(&Database) -> AppResult<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let writer = Arc::clone(&self.writer);
+        run_blocking(move || {
+            let db = writer.lock()?;
+            f(&db)
+        })
+        .await
+    }
+
+    /// Swap the live database for the contents of the backup at `src`. Holds
+    /// the writer for the duration, so no write can interleave with the swap.
+    pub async fn restore(&self, src: PathBuf) -> AppResult<()> {
+        let writer = Arc::clone(&self.writer);
+        run_blocking(move || {
+            let mut db = writer.lock()?;
+            db.restore_from(&src)
+        })
+        .await
+    }
+}
+
+async fn run_blocking<T, F>(f: F) -> AppResult<T>
+where
+    F: FnOnce() -> AppResult<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| AppError::internal(format!("database task failed: {}", e)))?
+}
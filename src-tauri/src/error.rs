@@ -0,0 +1,234 @@
+use crate::migrations::MigrationError;
+use rusqlite::ErrorCode;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use serde_json::json;
+use std::fmt;
+use std::sync::PoisonError;
+
+pub type AppResult<T> = Result<T, AppError>;
+
+/// Error returned by every command. It serializes to
+/// `{ code, message, details }` so the frontend can branch on `code`
+/// (which never changes once shipped) and show `message` to the user.
+#[derive(Debug)]
+pub enum AppError {
+    NotFound {
+        entity: &'static str,
+        id: String,
+    },
+    Conflict {
+        message: String,
+    },
+    Validation {
+        field: String,
+        message: String,
+    },
+    /// SQLite reported the database as busy or locked by another connection.
+    Busy {
+        message: String,
+    },
+    Storage {
+        message: String,
+    },
+    SchemaTooNew {
+        found: i64,
+        supported: i64,
+    },
+    Io {
+        path: Option<String>,
+        message: String,
+    },
+    AudioDevice {
+        message: String,
+    },
+    /// A mutex guarding shared state was poisoned by a panicking thread.
+    Lock {
+        resource: String,
+    },
+    Internal {
+        message: String,
+    },
+}
+
+impl AppError {
+    pub fn not_found(entity: &'static str, id: impl ToString) -> Self {
+        AppError::NotFound {
+            entity,
+            id: id.to_string(),
+        }
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        AppError::Conflict {
+            message: message.into(),
+        }
+    }
+
+    pub fn validation(field: impl Into<String>, message: impl Into<String>) -> Self {
+        AppError::Validation {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn io(path: impl Into<String>, err: std::io::Error) -> Self {
+        AppError::Io {
+            path: Some(path.into()),
+            message: err.to_string(),
+        }
+    }
+
+    pub fn audio(message: impl Into<String>) -> Self {
+        AppError::AudioDevice {
+            message: message.into(),
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        AppError::Internal {
+            message: message.into(),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound { .. } => "NOT_FOUND",
+            AppError::Conflict { .. } => "CONFLICT",
+            AppError::Validation { .. } => "VALIDATION",
+            AppError::Busy { .. } => "BUSY",
+            AppError::Storage { .. } => "STORAGE",
+            AppError::SchemaTooNew { .. } => "SCHEMA_TOO_NEW",
+            AppError::Io { .. } => "IO",
+            AppError::AudioDevice { .. } => "AUDIO_DEVICE",
+            AppError::Lock { .. } => "LOCK_POISONED",
+            AppError::Internal { .. } => "INTERNAL",
+        }
+    }
+
+    fn details(&self) -> serde_json::Value {
+        match self {
+            AppError::NotFound { entity, id } => json!({ "entity": entity, "id": id }),
+            AppError::Validation { field, .. } => json!({ "field": field }),
+            AppError::SchemaTooNew { found, supported } => {
+                json!({ "found": found, "supported": supported })
+            }
+            AppError::Io { path, .. } => json!({ "path": path }),
+            AppError::Lock { resource } => json!({ "resource": resource }),
+            _ => serde_json::Value::Null,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::NotFound { entity, id } => write!(f, "{} {} not found", entity, id),
+            AppError::Conflict { message } => write!(f, "{}", message),
+            AppError::Validation { field, message } => write!(f, "{}: {}", field, message),
+            AppError::Busy { message } => write!(f, "database is busy: {}", message),
+            AppError::Storage { message } => write!(f, "database error: {}", message),
+            AppError::SchemaTooNew { found, supported } => write!(
+                f,
+                "database schema version {} is newer than the latest supported version {}",
+                found, supported
+            ),
+            AppError::Io {
+                path: Some(path),
+                message,
+            } => write!(f, "{}: {}", path, message),
+            AppError::Io {
+                path: None,
+                message,
+            } => write!(f, "{}", message),
+            AppError::AudioDevice { message } => write!(f, "audio device error: {}", message),
+            AppError::Lock { resource } => write!(f, "{} lock poisoned", resource),
+            AppError::Internal { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        match &e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound {
+                entity: "row",
+                id: String::new(),
+            },
+            rusqlite::Error::SqliteFailure(err, _) => match err.code {
+                ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => AppError::Busy {
+                    message: e.to_string(),
+                },
+                ErrorCode::ConstraintViolation => AppError::Conflict {
+                    message: e.to_string(),
+                },
+                _ => AppError::Storage {
+                    message: e.to_string(),
+                },
+            },
+            _ => AppError::Storage {
+                message: e.to_string(),
+            },
+        }
+    }
+}
+
+impl From<MigrationError> for AppError {
+    fn from(e: MigrationError) -> Self {
+        match e {
+            MigrationError::NewerSchema { found, supported } => {
+                AppError::SchemaTooNew { found, supported }
+            }
+            MigrationError::Sqlite(e) => e.into(),
+            e @ MigrationError::Failed { .. } => AppError::Storage {
+                message: e.to_string(),
+            },
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io {
+            path: None,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl<T> From<PoisonError<T>> for AppError {
+    fn from(_: PoisonError<T>) -> Self {
+        AppError::Lock {
+            resource: std::any::type_name::<T>().to_string(),
+        }
+    }
+}
+
+// Lets the streaming server's handlers return AppError directly.
+impl axum::response::IntoResponse for AppError {
+    fn into_response(self) -> axum::response::Response {
+        use axum::http::StatusCode;
+
+        let status = match self {
+            AppError::NotFound { .. } => StatusCode::NOT_FOUND,
+            AppError::Conflict { .. } => StatusCode::CONFLICT,
+            AppError::Validation { .. } => StatusCode::BAD_REQUEST,
+            AppError::Busy { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, axum::Json(self)).into_response()
+    }
+}
@@ -0,0 +1,570 @@
+use crate::database::Database;
+use crate::error::{AppError, AppResult};
+use crate::groups::insert_at;
+use crate::journal::JournalOp;
+use crate::models::{Folder, FolderVisibility, PlaylistFolderState, PlaylistItem};
+use chrono::Utc;
+use rusqlite::params;
+
+const FOLDER_SELECT: &str =
+    "SELECT id, color_key, name, hex, is_builtin, position, created_at, updated_at FROM folders";
+
+fn folder_from_row(row: &rusqlite::Row) -> rusqlite::Result<Folder> {
+    Ok(Folder {
+        id: row.get(0)?,
+        color_key: row.get(1)?,
+        name: row.get(2)?,
+        hex: row.get(3)?,
+        is_builtin: row.get(4)?,
+        position: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+fn validate_color_key(color_key: &str) -> AppResult<()> {
+    let valid = !color_key.is_empty()
+        && color_key.len() <= 32
+        && color_key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::validation(
+            "color_key",
+            "folder key must be 1-32 lowercase letters, digits or dashes",
+        ))
+    }
+}
+
+fn validate_hex(hex: &str) -> AppResult<String> {
+    let hex = hex.trim().to_lowercase();
+    let valid =
+        hex.len() == 7 && hex.starts_with('#') && hex[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if valid {
+        Ok(hex)
+    } else {
+        Err(AppError::validation(
+            "hex",
+            "folder color must look like #rrggbb",
+        ))
+    }
+}
+
+// The 16 builtin folders are seeded by the migration and can be renamed,
+// recolored and reordered but not deleted. User folders go after them;
+// deleting one takes its assignments, visibility and metadata with it.
+//
+// Visibility is per playlist. Only folders with something other than the
+// defaults (not pinned, hidden or collapsed, and no sort order) have a row.
+//
+// The bulk operations act on one folder of a playlist at once and keep its
+// assignments, visibility and metadata together.
+impl Database {
+    pub fn get_folders(&self) -> AppResult<Vec<Folder>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("{} ORDER BY position, id", FOLDER_SELECT))?;
+        let folders = stmt
+            .query_map([], folder_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(folders)
+    }
+
+    fn get_folder(&self, color_key: &str) -> AppResult<Folder> {
+        match self.conn.query_row(
+            &format!("{} WHERE color_key = ?1", FOLDER_SELECT),
+            params![color_key],
+            folder_from_row,
+        ) {
+            Ok(folder) => Ok(folder),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Err(AppError::not_found("folder", color_key))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The stored key for a folder color as the frontend sends it: keys are
+    /// matched case-insensitively, and a folder's hex value works too.
+    pub(crate) fn resolve_folder(&self, folder_color: &str) -> AppResult<String> {
+        self.find_folder_key(folder_color)?
+            .ok_or_else(|| AppError::not_found("folder", folder_color))
+    }
+
+    // Like `resolve_folder`, for imports that skip unknown folders
+    pub(crate) fn find_folder_key(&self, folder_color: &str) -> AppResult<Option<String>> {
+        let color = folder_color.trim().to_lowercase();
+        match self.conn.query_row(
+            "SELECT color_key FROM folders WHERE color_key = ?1 OR hex = ?1
+             ORDER BY color_key = ?1 DESC, position LIMIT 1",
+            params![color],
+            |row| row.get(0),
+        ) {
+            Ok(color_key) => Ok(Some(color_key)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Create a user folder after the existing ones.
+    pub fn create_folder(&self, color_key: &str, name: &str, hex: &str) -> AppResult<Folder> {
+        let color_key = color_key.trim().to_lowercase();
+        validate_color_key(&color_key)?;
+        let hex = validate_hex(hex)?;
+        if name.trim().is_empty() {
+            return Err(AppError::validation("name", "folder name cannot be empty"));
+        }
+
+        let exists: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM folders WHERE color_key = ?1)",
+            params![color_key],
+            |row| row.get(0),
+        )?;
+        if exists {
+            return Err(AppError::conflict(format!(
+                "a folder with key '{}' already exists",
+                color_key
+            )));
+        }
+
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO folders (color_key, name, hex, is_builtin, position, created_at, updated_at)
+             SELECT ?1, ?2, ?3, 0, COALESCE(MAX(position), 0) + 1, ?4, ?4 FROM folders",
+            params![color_key, name.trim(), hex, now],
+        )?;
+        self.get_folder(&color_key)
+    }
+
+    /// Rename or recolor a folder. Fields left `None` are unchanged.
+    pub fn update_folder(
+        &self,
+        color_key: &str,
+        name: Option<&str>,
+        hex: Option<&str>,
+    ) -> AppResult<Folder> {
+        let color_key = self.resolve_folder(color_key)?;
+        if name.is_some_and(|name| name.trim().is_empty()) {
+            return Err(AppError::validation("name", "folder name cannot be empty"));
+        }
+        let hex = hex.map(validate_hex).transpose()?;
+
+        self.conn.execute(
+            "UPDATE folders SET name = COALESCE(?1, name), hex = COALESCE(?2, hex), updated_at = ?3
+             WHERE color_key = ?4",
+            params![name.map(str::trim), hex, Utc::now().to_rfc3339(), color_key],
+        )?;
+        self.get_folder(&color_key)
+    }
+
+    /// Move a folder to the 1-based `position` among the folders.
+    pub fn move_folder(&self, color_key: &str, position: i32) -> AppResult<Folder> {
+        let folder = self.get_folder(&self.resolve_folder(color_key)?)?;
+        let tx = self.conn.unchecked_transaction()?;
+
+        let mut folder_ids: Vec<i64> = self
+            .conn
+            .prepare("SELECT id FROM folders WHERE id != ?1 ORDER BY position, id")?
+            .query_map(params![folder.id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        insert_at(&mut folder_ids, folder.id, Some(position));
+        for (index, id) in folder_ids.iter().enumerate() {
+            self.conn.execute(
+                "UPDATE folders SET position = ?1 WHERE id = ?2",
+                params![index as i32 + 1, id],
+            )?;
+        }
+
+        tx.commit()?;
+        self.get_folder(&folder.color_key)
+    }
+
+    /// Delete a user folder along with everything filed under it.
+    pub fn delete_folder(&self, color_key: &str) -> AppResult<bool> {
+        let folder = match self.resolve_folder(color_key) {
+            Ok(color_key) => self.get_folder(&color_key)?,
+            Err(AppError::NotFound { .. }) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        if folder.is_builtin {
+            return Err(AppError::conflict(format!(
+                "the builtin '{}' folder can't be deleted",
+                folder.color_key
+            )));
+        }
+
+        let rows = self
+            .conn
+            .execute("DELETE FROM folders WHERE id = ?1", params![folder.id])?;
+        Ok(rows > 0)
+    }
+
+    /// Every folder as it appears in a playlist, in display order: folders
+    /// with a sort order first, then the rest in folder order.
+    pub fn get_playlist_folder_states(
+        &self,
+        playlist_id: i64,
+    ) -> AppResult<Vec<PlaylistFolderState>> {
+        self.ensure_playlist_exists(playlist_id)?;
+        let mut stmt = self.conn.prepare(
+            "SELECT f.color_key, f.name, f.hex,
+                    (SELECT COUNT(*) FROM video_folder_assignments vfa
+                     INNER JOIN playlist_items pi ON pi.id = vfa.item_id
+                     WHERE vfa.playlist_id = ?1 AND vfa.folder_color = f.color_key AND pi.deleted_at IS NULL),
+                    fm.custom_name, fm.description, fm.custom_ascii,
+                    COALESCE(fv.pinned, 0), COALESCE(fv.hidden, 0), COALESCE(fv.collapsed, 0), fv.sort_order
+             FROM folders f
+             LEFT JOIN folder_metadata fm ON fm.playlist_id = ?1 AND fm.folder_color = f.color_key
+             LEFT JOIN folder_visibility fv ON fv.playlist_id = ?1 AND fv.folder_color = f.color_key
+             ORDER BY fv.sort_order IS NULL, fv.sort_order, f.position, f.id",
+        )?;
+        let states = stmt
+            .query_map(params![playlist_id], |row| {
+                Ok(PlaylistFolderState {
+                    folder_color: row.get(0)?,
+                    name: row.get(1)?,
+                    hex: row.get(2)?,
+                    video_count: row.get(3)?,
+                    custom_name: row.get(4)?,
+                    description: row.get(5)?,
+                    custom_ascii: row.get(6)?,
+                    visibility: FolderVisibility {
+                        pinned: row.get(7)?,
+                        hidden: row.get(8)?,
+                        collapsed: row.get(9)?,
+                        sort_order: row.get(10)?,
+                    },
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(states)
+    }
+
+    fn get_folder_visibility(
+        &self,
+        playlist_id: i64,
+        folder_color: &str,
+    ) -> AppResult<FolderVisibility> {
+        match self.conn.query_row(
+            "SELECT pinned, hidden, collapsed, sort_order FROM folder_visibility
+             WHERE playlist_id = ?1 AND folder_color = ?2",
+            params![playlist_id, folder_color],
+            |row| {
+                Ok(FolderVisibility {
+                    pinned: row.get(0)?,
+                    hidden: row.get(1)?,
+                    collapsed: row.get(2)?,
+                    sort_order: row.get(3)?,
+                })
+            },
+        ) {
+            Ok(visibility) => Ok(visibility),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(FolderVisibility::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Pin, hide or collapse a folder in a playlist. Flags left `None` are
+    /// unchanged.
+    pub fn set_folder_visibility(
+        &self,
+        playlist_id: i64,
+        folder_color: &str,
+        pinned: Option<bool>,
+        hidden: Option<bool>,
+        collapsed: Option<bool>,
+    ) -> AppResult<FolderVisibility> {
+        self.ensure_playlist_exists(playlist_id)?;
+        let folder_color = self.resolve_folder(folder_color)?;
+        let tx = self.conn.unchecked_transaction()?;
+
+        self.conn.execute(
+            "INSERT INTO folder_visibility (playlist_id, folder_color, pinned, hidden, collapsed, updated_at)
+             VALUES (?1, ?2, COALESCE(?3, 0), COALESCE(?4, 0), COALESCE(?5, 0), ?6)
+             ON CONFLICT(playlist_id, folder_color) DO UPDATE SET
+                pinned = COALESCE(?3, pinned),
+                hidden = COALESCE(?4, hidden),
+                collapsed = COALESCE(?5, collapsed),
+                updated_at = ?6",
+            params![
+                playlist_id,
+                folder_color,
+                pinned,
+                hidden,
+                collapsed,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        self.drop_default_visibility(playlist_id)?;
+
+        tx.commit()?;
+        self.get_folder_visibility(playlist_id, &folder_color)
+    }
+
+    /// Put the given folders first in a playlist, in that order. The rest
+    /// lose their sort order and follow in folder order.
+    pub fn set_folder_order(
+        &self,
+        playlist_id: i64,
+        folder_colors: &[String],
+    ) -> AppResult<Vec<PlaylistFolderState>> {
+        self.ensure_playlist_exists(playlist_id)?;
+        let mut ordered: Vec<String> = Vec::with_capacity(folder_colors.len());
+        for color in folder_colors {
+            let color_key = self.resolve_folder(color)?;
+            if !ordered.contains(&color_key) {
+                ordered.push(color_key);
+            }
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "UPDATE folder_visibility SET sort_order = NULL, updated_at = ?2
+             WHERE playlist_id = ?1 AND sort_order IS NOT NULL",
+            params![playlist_id, now],
+        )?;
+        for (index, color_key) in ordered.iter().enumerate() {
+            self.conn.execute(
+                "INSERT INTO folder_visibility (playlist_id, folder_color, sort_order, updated_at)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(playlist_id, folder_color) DO UPDATE SET
+                    sort_order = excluded.sort_order,
+                    updated_at = excluded.updated_at",
+                params![playlist_id, color_key, index as i32 + 1, now],
+            )?;
+        }
+        self.drop_default_visibility(playlist_id)?;
+
+        tx.commit()?;
+        self.get_playlist_folder_states(playlist_id)
+    }
+
+    fn drop_default_visibility(&self, playlist_id: i64) -> AppResult<()> {
+        self.conn.execute(
+            "DELETE FROM folder_visibility
+             WHERE playlist_id = ?1 AND NOT pinned AND NOT hidden AND NOT collapsed AND sort_order IS NULL",
+            params![playlist_id],
+        )?;
+        Ok(())
+    }
+
+    // Live items in a folder of a playlist, in playlist order
+    fn folder_item_ids(&self, playlist_id: i64, folder_color: &str) -> AppResult<Vec<i64>> {
+        let ids = self
+            .conn
+            .prepare(
+                "SELECT pi.id FROM video_folder_assignments vfa
+                 INNER JOIN playlist_items pi ON pi.id = vfa.item_id
+                 WHERE vfa.playlist_id = ?1 AND vfa.folder_color = ?2 AND pi.deleted_at IS NULL
+                 ORDER BY pi.position, pi.id",
+            )?
+            .query_map(params![playlist_id, folder_color], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(ids)
+    }
+
+    // Whether a folder has any assignments, metadata or visibility in a playlist
+    fn folder_in_use(&self, playlist_id: i64, folder_color: &str) -> AppResult<bool> {
+        let in_use = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM video_folder_assignments WHERE playlist_id = ?1 AND folder_color = ?2)
+                 OR EXISTS(SELECT 1 FROM folder_metadata WHERE playlist_id = ?1 AND folder_color = ?2)
+                 OR EXISTS(SELECT 1 FROM folder_visibility WHERE playlist_id = ?1 AND folder_color = ?2)",
+            params![playlist_id, folder_color],
+            |row| row.get(0),
+        )?;
+        Ok(in_use)
+    }
+
+    // After items arrive from another playlist, give the target the source's
+    // metadata and visibility for each folder they brought along, unless the
+    // target already has its own. Callers own the transaction.
+    fn carry_folder_settings(&self, from_playlist_id: i64, to_playlist_id: i64) -> AppResult<()> {
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT OR IGNORE INTO folder_metadata (playlist_id, folder_color, custom_name, description, custom_ascii, created_at, updated_at)
+             SELECT ?2, folder_color, custom_name, description, custom_ascii, ?3, ?3 FROM folder_metadata
+             WHERE playlist_id = ?1
+               AND folder_color IN (SELECT folder_color FROM video_folder_assignments WHERE playlist_id = ?2)",
+            params![from_playlist_id, to_playlist_id, now],
+        )?;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO folder_visibility (playlist_id, folder_color, pinned, hidden, collapsed, sort_order, updated_at)
+             SELECT ?2, folder_color, pinned, hidden, collapsed, sort_order, ?3 FROM folder_visibility
+             WHERE playlist_id = ?1
+               AND folder_color IN (SELECT folder_color FROM video_folder_assignments WHERE playlist_id = ?2)",
+            params![from_playlist_id, to_playlist_id, now],
+        )?;
+        Ok(())
+    }
+
+    /// Move every item in a folder, in playlist order, to `target_playlist_id`
+    /// starting at `target_position` (appended when `None`). The items keep all
+    /// their folders, and the target picks up the folders' metadata and
+    /// visibility where it has none of its own.
+    pub fn move_folder_items(
+        &self,
+        playlist_id: i64,
+        folder_color: &str,
+        target_playlist_id: i64,
+        target_position: Option<i32>,
+    ) -> AppResult<Vec<PlaylistItem>> {
+        self.ensure_regular_playlist(playlist_id)?;
+        self.ensure_regular_playlist(target_playlist_id)?;
+        let folder_color = self.resolve_folder(folder_color)?;
+        let item_ids = self.folder_item_ids(playlist_id, &folder_color)?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        let moved = self.place_moved_items(&item_ids, target_playlist_id, target_position, true)?;
+        if target_playlist_id != playlist_id {
+            self.carry_folder_settings(playlist_id, target_playlist_id)?;
+        }
+        tx.commit()?;
+        self.items_by_id(&moved)
+    }
+
+    /// Copy the items in a folder, in playlist order, into a new playlist
+    /// named `name`, or after the folder when `None`. The copies keep all
+    /// their folders along with the folders' metadata and visibility.
+    pub fn copy_folder_to_new_playlist(
+        &self,
+        playlist_id: i64,
+        folder_color: &str,
+        name: Option<&str>,
+    ) -> AppResult<i64> {
+        self.ensure_regular_playlist(playlist_id)?;
+        let folder_color = self.resolve_folder(folder_color)?;
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => match self.get_folder_metadata(playlist_id, &folder_color)? {
+                Some((Some(custom_name), _, _)) if !custom_name.trim().is_empty() => custom_name,
+                _ => self.get_folder(&folder_color)?.name,
+            },
+        };
+        let item_ids = self.folder_item_ids(playlist_id, &folder_color)?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        let new_playlist_id = self.create_playlist(name.trim(), None)?;
+        self.place_copied_items(&item_ids, new_playlist_id, None, true)?;
+        self.carry_folder_settings(playlist_id, new_playlist_id)?;
+        tx.commit()?;
+        Ok(new_playlist_id)
+    }
+
+    /// Re-key a folder of a playlist to another folder, moving its
+    /// assignments, visibility and metadata together. The new folder must be
+    /// unused in the playlist; use `merge_folders` otherwise.
+    pub fn recolor_folder(
+        &self,
+        playlist_id: i64,
+        folder_color: &str,
+        new_folder_color: &str,
+    ) -> AppResult<Vec<PlaylistFolderState>> {
+        self.ensure_playlist_exists(playlist_id)?;
+        let from = self.resolve_folder(folder_color)?;
+        let to = self.resolve_folder(new_folder_color)?;
+        if from != to {
+            if self.folder_in_use(playlist_id, &to)? {
+                return Err(AppError::conflict(format!(
+                    "the {} folder is already in use in this playlist",
+                    to
+                )));
+            }
+            let tx = self.conn.unchecked_transaction()?;
+            self.merge_folder_rows(playlist_id, &from, &to)?;
+            tx.commit()?;
+        }
+        self.get_playlist_folder_states(playlist_id)
+    }
+
+    /// Move everything in one folder of a playlist into another. Items already
+    /// in both stay put; where both folders have metadata or visibility, the
+    /// target's wins.
+    pub fn merge_folders(
+        &self,
+        playlist_id: i64,
+        folder_color: &str,
+        into_folder_color: &str,
+    ) -> AppResult<Vec<PlaylistFolderState>> {
+        self.ensure_playlist_exists(playlist_id)?;
+        let from = self.resolve_folder(folder_color)?;
+        let into = self.resolve_folder(into_folder_color)?;
+        if from == into {
+            return Err(AppError::validation(
+                "into_folder_color",
+                "a folder can't be merged into itself",
+            ));
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.merge_folder_rows(playlist_id, &from, &into)?;
+        tx.commit()?;
+        self.get_playlist_folder_states(playlist_id)
+    }
+
+    // Rows the target already has stay as they are, and the source's
+    // counterparts are dropped. Callers own the transaction.
+    fn merge_folder_rows(&self, playlist_id: i64, from: &str, into: &str) -> AppResult<()> {
+        for table in [
+            "video_folder_assignments",
+            "folder_metadata",
+            "folder_visibility",
+        ] {
+            self.conn.execute(
+                &format!(
+                    "UPDATE OR IGNORE {} SET folder_color = ?3 WHERE playlist_id = ?1 AND folder_color = ?2",
+                    table
+                ),
+                params![playlist_id, from, into],
+            )?;
+            self.conn.execute(
+                &format!(
+                    "DELETE FROM {} WHERE playlist_id = ?1 AND folder_color = ?2",
+                    table
+                ),
+                params![playlist_id, from],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Take every item out of a folder of a playlist, trashed ones included.
+    /// The folder's metadata and visibility stay. Can be undone.
+    pub fn clear_folder(&self, playlist_id: i64, folder_color: &str) -> AppResult<usize> {
+        self.ensure_playlist_exists(playlist_id)?;
+        let folder_color = self.resolve_folder(folder_color)?;
+
+        let assignments: Vec<(i64, String)> = self
+            .conn
+            .prepare(
+                "SELECT item_id, created_at FROM video_folder_assignments
+                 WHERE playlist_id = ?1 AND folder_color = ?2 ORDER BY id",
+            )?
+            .query_map(params![playlist_id, folder_color], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<_, _>>()?;
+        if assignments.is_empty() {
+            return Ok(0);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute(
+            "DELETE FROM video_folder_assignments WHERE playlist_id = ?1 AND folder_color = ?2",
+            params![playlist_id, folder_color],
+        )?;
+        let cleared = assignments.len();
+        self.record_operation(
+            &JournalOp::ClearFolder {
+                playlist_id,
+                folder_color: folder_color.clone(),
+                assignments,
+            },
+            &format!("Clear {} folder", folder_color),
+        )?;
+        tx.commit()?;
+        Ok(cleared)
+    }
+}
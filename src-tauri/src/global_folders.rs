@@ -0,0 +1,233 @@
+use crate::database::{playlist_item_from_row, Database, ITEM_SELECT};
+use crate::error::{AppError, AppResult};
+use crate::models::{GlobalFolder, PlaylistItem};
+use chrono::Utc;
+use rusqlite::params;
+use std::collections::HashMap;
+
+// Counts only take live items in live playlists
+const GLOBAL_FOLDER_SELECT: &str =
+    "SELECT gf.id, gf.name, gf.description, gf.folder_color, gf.position,
+        COUNT(p.id), COUNT(DISTINCT p.id), gf.created_at, gf.updated_at
+     FROM global_folders gf
+     LEFT JOIN global_folder_items gfi ON gfi.global_folder_id = gf.id
+     LEFT JOIN playlist_items pi ON pi.id = gfi.item_id AND pi.deleted_at IS NULL
+     LEFT JOIN playlists p ON p.id = pi.playlist_id AND p.deleted_at IS NULL";
+
+fn global_folder_from_row(row: &rusqlite::Row) -> rusqlite::Result<GlobalFolder> {
+    Ok(GlobalFolder {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        folder_color: row.get(3)?,
+        position: row.get(4)?,
+        item_count: row.get(5)?,
+        playlist_count: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+// Global folders hold items from any playlist, in the order they were added.
+// Membership follows an item when it moves between playlists. Trashed items,
+// and items of trashed playlists, drop out of the contents and counts but keep
+// their membership so restoring them puts them back; purging deletes it.
+impl Database {
+    pub fn get_global_folders(&self) -> AppResult<Vec<GlobalFolder>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "{} GROUP BY gf.id ORDER BY gf.position, gf.id",
+            GLOBAL_FOLDER_SELECT
+        ))?;
+        let folders = stmt
+            .query_map([], global_folder_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(folders)
+    }
+
+    fn get_global_folder(&self, global_folder_id: i64) -> AppResult<GlobalFolder> {
+        match self.conn.query_row(
+            &format!("{} WHERE gf.id = ?1 GROUP BY gf.id", GLOBAL_FOLDER_SELECT),
+            params![global_folder_id],
+            global_folder_from_row,
+        ) {
+            Ok(folder) => Ok(folder),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Err(AppError::not_found("global folder", global_folder_id))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Create a global folder after the existing ones, optionally shown in a
+    /// folder's color.
+    pub fn create_global_folder(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        folder_color: Option<&str>,
+    ) -> AppResult<GlobalFolder> {
+        if name.trim().is_empty() {
+            return Err(AppError::validation("name", "folder name cannot be empty"));
+        }
+        let folder_color = folder_color
+            .map(|color| self.resolve_folder(color))
+            .transpose()?;
+
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO global_folders (name, description, folder_color, position, created_at, updated_at)
+             SELECT ?1, ?2, ?3, COALESCE(MAX(position), 0) + 1, ?4, ?4 FROM global_folders",
+            params![name.trim(), description, folder_color, now],
+        )?;
+        self.get_global_folder(self.conn.last_insert_rowid())
+    }
+
+    /// Rename, describe or recolor a global folder. Fields left `None` are
+    /// unchanged; an empty description or color clears it.
+    pub fn update_global_folder(
+        &self,
+        global_folder_id: i64,
+        name: Option<&str>,
+        description: Option<&str>,
+        folder_color: Option<&str>,
+    ) -> AppResult<GlobalFolder> {
+        self.get_global_folder(global_folder_id)?;
+        if name.is_some_and(|name| name.trim().is_empty()) {
+            return Err(AppError::validation("name", "folder name cannot be empty"));
+        }
+        let folder_color = match folder_color.map(str::trim) {
+            Some("") => Some(None),
+            Some(color) => Some(Some(self.resolve_folder(color)?)),
+            None => None,
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        let now = Utc::now().to_rfc3339();
+        if let Some(name) = name {
+            self.conn.execute(
+                "UPDATE global_folders SET name = ?1, updated_at = ?2 WHERE id = ?3",
+                params![name.trim(), now, global_folder_id],
+            )?;
+        }
+        if let Some(description) = description {
+            self.conn.execute(
+                "UPDATE global_folders SET description = NULLIF(?1, ''), updated_at = ?2 WHERE id = ?3",
+                params![description, now, global_folder_id],
+            )?;
+        }
+        if let Some(folder_color) = folder_color {
+            self.conn.execute(
+                "UPDATE global_folders SET folder_color = ?1, updated_at = ?2 WHERE id = ?3",
+                params![folder_color, now, global_folder_id],
+            )?;
+        }
+        tx.commit()?;
+        self.get_global_folder(global_folder_id)
+    }
+
+    /// Delete a global folder. The items in it are untouched.
+    pub fn delete_global_folder(&self, global_folder_id: i64) -> AppResult<bool> {
+        let rows = self.conn.execute(
+            "DELETE FROM global_folders WHERE id = ?1",
+            params![global_folder_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// Live items in a global folder, in the order they were added. Each
+    /// item's `playlist_id` says where it lives.
+    pub fn get_global_folder_items(&self, global_folder_id: i64) -> AppResult<Vec<PlaylistItem>> {
+        self.get_global_folder(global_folder_id)?;
+        let mut stmt = self.conn.prepare(&format!(
+            "{}
+             INNER JOIN global_folder_items gfi ON gfi.item_id = pi.id
+             INNER JOIN playlists p ON p.id = pi.playlist_id
+             WHERE gfi.global_folder_id = ?1 AND pi.deleted_at IS NULL AND p.deleted_at IS NULL
+             ORDER BY gfi.position, gfi.item_id",
+            ITEM_SELECT
+        ))?;
+        let items = stmt
+            .query_map(params![global_folder_id], playlist_item_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(items)
+    }
+
+    /// Add items, in the order given, after the folder's current contents.
+    /// Items already in the folder stay where they are. Fails without changing
+    /// anything if any item doesn't exist.
+    pub fn add_items_to_global_folder(
+        &self,
+        global_folder_id: i64,
+        item_ids: &[i64],
+    ) -> AppResult<GlobalFolder> {
+        self.get_global_folder(global_folder_id)?;
+        for &item_id in item_ids {
+            if self.get_playlist_item(item_id)?.is_none() {
+                return Err(AppError::not_found("playlist item", item_id));
+            }
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let now = Utc::now().to_rfc3339();
+        for &item_id in item_ids {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO global_folder_items (global_folder_id, item_id, position, added_at)
+                 SELECT ?1, ?2, COALESCE(MAX(position), 0) + 1, ?3 FROM global_folder_items
+                 WHERE global_folder_id = ?1",
+                params![global_folder_id, item_id, now],
+            )?;
+        }
+        self.touch_global_folder(global_folder_id)?;
+        tx.commit()?;
+        self.get_global_folder(global_folder_id)
+    }
+
+    pub fn remove_items_from_global_folder(
+        &self,
+        global_folder_id: i64,
+        item_ids: &[i64],
+    ) -> AppResult<GlobalFolder> {
+        self.get_global_folder(global_folder_id)?;
+        let tx = self.conn.unchecked_transaction()?;
+        for &item_id in item_ids {
+            self.conn.execute(
+                "DELETE FROM global_folder_items WHERE global_folder_id = ?1 AND item_id = ?2",
+                params![global_folder_id, item_id],
+            )?;
+        }
+        self.touch_global_folder(global_folder_id)?;
+        tx.commit()?;
+        self.get_global_folder(global_folder_id)
+    }
+
+    /// Ids of the global folders each item is in, for the given items.
+    pub fn get_global_folders_for_items(
+        &self,
+        item_ids: &[i64],
+    ) -> AppResult<HashMap<i64, Vec<i64>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT gfi.global_folder_id FROM global_folder_items gfi
+             INNER JOIN global_folders gf ON gf.id = gfi.global_folder_id
+             WHERE gfi.item_id = ?1
+             ORDER BY gf.position, gf.id",
+        )?;
+        let mut folders = HashMap::new();
+        for &item_id in item_ids {
+            let ids = stmt
+                .query_map(params![item_id], |row| row.get(0))?
+                .collect::<Result<Vec<i64>, _>>()?;
+            if !ids.is_empty() {
+                folders.insert(item_id, ids);
+            }
+        }
+        Ok(folders)
+    }
+
+    fn touch_global_folder(&self, global_folder_id: i64) -> AppResult<()> {
+        self.conn.execute(
+            "UPDATE global_folders SET updated_at = ?1 WHERE id = ?2",
+            params![Utc::now().to_rfc3339(), global_folder_id],
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,307 @@
+use crate::database::Database;
+use crate::error::{AppError, AppResult};
+use crate::models::PlaylistGroup;
+use chrono::Utc;
+use rusqlite::params;
+use std::collections::HashMap;
+
+const GROUP_SELECT: &str =
+    "SELECT id, parent_id, name, position, created_at, updated_at FROM playlist_groups";
+
+fn group_from_row(row: &rusqlite::Row) -> rusqlite::Result<PlaylistGroup> {
+    Ok(PlaylistGroup {
+        id: row.get(0)?,
+        parent_id: row.get(1)?,
+        name: row.get(2)?,
+        position: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+        playlist_count: 0,
+        item_count: 0,
+    })
+}
+
+// Playlists are ordered within their group by `sort_order` and groups within
+// their parent by `position`. New playlists get sort_order 0 and so show up
+// first; moving anything renumbers its new siblings from 1.
+impl Database {
+    fn ensure_group_exists(&self, group_id: i64) -> AppResult<()> {
+        let exists: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM playlist_groups WHERE id = ?1)",
+            params![group_id],
+            |row| row.get(0),
+        )?;
+
+        if exists {
+            Ok(())
+        } else {
+            Err(AppError::not_found("playlist group", group_id))
+        }
+    }
+
+    fn get_group(&self, group_id: i64) -> AppResult<PlaylistGroup> {
+        match self.conn.query_row(
+            &format!("{} WHERE id = ?1", GROUP_SELECT),
+            params![group_id],
+            group_from_row,
+        ) {
+            Ok(group) => Ok(group),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Err(AppError::not_found("playlist group", group_id))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn create_playlist_group(
+        &self,
+        name: &str,
+        parent_id: Option<i64>,
+    ) -> AppResult<PlaylistGroup> {
+        if name.trim().is_empty() {
+            return Err(AppError::validation("name", "group name cannot be empty"));
+        }
+        if let Some(parent_id) = parent_id {
+            self.ensure_group_exists(parent_id)?;
+        }
+
+        let position: i32 = self.conn.query_row(
+            "SELECT COALESCE(MAX(position), 0) + 1 FROM playlist_groups WHERE parent_id IS ?1",
+            params![parent_id],
+            |row| row.get(0),
+        )?;
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO playlist_groups (parent_id, name, position, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?4)",
+            params![parent_id, name.trim(), position, now],
+        )?;
+
+        self.get_group(self.conn.last_insert_rowid())
+    }
+
+    pub fn rename_playlist_group(&self, group_id: i64, name: &str) -> AppResult<bool> {
+        if name.trim().is_empty() {
+            return Err(AppError::validation("name", "group name cannot be empty"));
+        }
+
+        let rows = self.conn.execute(
+            "UPDATE playlist_groups SET name = ?1, updated_at = ?2 WHERE id = ?3",
+            params![name.trim(), Utc::now().to_rfc3339(), group_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// Every group, parents before their children and siblings in order,
+    /// with counts that include nested groups.
+    pub fn get_playlist_groups(&self) -> AppResult<Vec<PlaylistGroup>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "{} ORDER BY parent_id, position, id",
+            GROUP_SELECT
+        ))?;
+        let groups = stmt
+            .query_map([], group_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Direct counts first, then each group adds them to all its ancestors
+        let mut stmt = self.conn.prepare(
+            "SELECT p.group_id, COUNT(*),
+                    SUM(CASE WHEN p.smart_rules IS NULL THEN
+                        (SELECT COUNT(*) FROM playlist_items pi
+                         WHERE pi.playlist_id = p.id AND pi.deleted_at IS NULL)
+                    ELSE 0 END)
+             FROM playlists p
+             WHERE p.group_id IS NOT NULL AND p.deleted_at IS NULL
+             GROUP BY p.group_id",
+        )?;
+        let direct: HashMap<i64, (i32, i32)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?
+            .collect::<Result<_, _>>()?;
+
+        let parents: HashMap<i64, Option<i64>> =
+            groups.iter().map(|g| (g.id, g.parent_id)).collect();
+        let mut totals: HashMap<i64, (i32, i32)> = HashMap::new();
+        for (&group_id, &(playlists, items)) in &direct {
+            let mut current = Some(group_id);
+            while let Some(id) = current {
+                let total = totals.entry(id).or_default();
+                total.0 += playlists;
+                total.1 += items;
+                current = parents.get(&id).copied().flatten();
+            }
+        }
+
+        Ok(order_as_tree(groups)
+            .into_iter()
+            .map(|mut group| {
+                let (playlists, items) = totals.get(&group.id).copied().unwrap_or_default();
+                group.playlist_count = playlists;
+                group.item_count = items;
+                group
+            })
+            .collect())
+    }
+
+    /// Move a group under `parent_id` (`None` for the top level) at
+    /// `position`, or last when it's `None`.
+    pub fn move_playlist_group(
+        &self,
+        group_id: i64,
+        parent_id: Option<i64>,
+        position: Option<i32>,
+    ) -> AppResult<PlaylistGroup> {
+        self.ensure_group_exists(group_id)?;
+        if let Some(parent_id) = parent_id {
+            self.ensure_group_exists(parent_id)?;
+
+            // Walking up from the new parent must not reach the group itself
+            let mut current = Some(parent_id);
+            while let Some(id) = current {
+                if id == group_id {
+                    return Err(AppError::conflict(
+                        "a group can't be moved into itself or one of its subgroups",
+                    ));
+                }
+                current = self.get_group(id)?.parent_id;
+            }
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+
+        let mut siblings: Vec<i64> = self
+            .conn
+            .prepare(
+                "SELECT id FROM playlist_groups WHERE parent_id IS ?1 AND id != ?2
+                 ORDER BY position, id",
+            )?
+            .query_map(params![parent_id, group_id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        insert_at(&mut siblings, group_id, position);
+
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "UPDATE playlist_groups SET parent_id = ?1, updated_at = ?2 WHERE id = ?3",
+            params![parent_id, now, group_id],
+        )?;
+        for (index, id) in siblings.iter().enumerate() {
+            self.conn.execute(
+                "UPDATE playlist_groups SET position = ?1 WHERE id = ?2",
+                params![index as i32 + 1, id],
+            )?;
+        }
+
+        tx.commit()?;
+        self.get_group(group_id)
+    }
+
+    /// Move a playlist into `group_id` (`None` for the top level) at
+    /// `position`, or last when it's `None`.
+    pub fn move_playlist_to_group(
+        &self,
+        playlist_id: i64,
+        group_id: Option<i64>,
+        position: Option<i32>,
+    ) -> AppResult<bool> {
+        self.ensure_playlist_exists(playlist_id)?;
+        if let Some(group_id) = group_id {
+            self.ensure_group_exists(group_id)?;
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+
+        let mut siblings: Vec<i64> = self
+            .conn
+            .prepare(
+                "SELECT id FROM playlists
+                 WHERE group_id IS ?1 AND id != ?2 AND deleted_at IS NULL
+                 ORDER BY sort_order, created_at DESC",
+            )?
+            .query_map(params![group_id, playlist_id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        insert_at(&mut siblings, playlist_id, position);
+
+        self.conn.execute(
+            "UPDATE playlists SET group_id = ?1 WHERE id = ?2",
+            params![group_id, playlist_id],
+        )?;
+        for (index, id) in siblings.iter().enumerate() {
+            self.conn.execute(
+                "UPDATE playlists SET sort_order = ?1 WHERE id = ?2",
+                params![index as i32 + 1, id],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Delete a group. Its playlists and subgroups move up into its parent,
+    /// after the ones already there.
+    pub fn delete_playlist_group(&self, group_id: i64) -> AppResult<bool> {
+        let group = match self.get_group(group_id) {
+            Ok(group) => group,
+            Err(AppError::NotFound { .. }) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+
+        let group_base: i32 = self.conn.query_row(
+            "SELECT COALESCE(MAX(position), 0) FROM playlist_groups WHERE parent_id IS ?1",
+            params![group.parent_id],
+            |row| row.get(0),
+        )?;
+        self.conn.execute(
+            "UPDATE playlist_groups SET parent_id = ?1, position = position + ?2 WHERE parent_id = ?3",
+            params![group.parent_id, group_base, group_id],
+        )?;
+
+        // Trashed playlists move too, so they come back somewhere that exists
+        let playlist_base: i32 = self.conn.query_row(
+            "SELECT COALESCE(MAX(sort_order), 0) FROM playlists WHERE group_id IS ?1",
+            params![group.parent_id],
+            |row| row.get(0),
+        )?;
+        self.conn.execute(
+            "UPDATE playlists SET group_id = ?1, sort_order = sort_order + ?2 WHERE group_id = ?3",
+            params![group.parent_id, playlist_base, group_id],
+        )?;
+
+        self.conn.execute(
+            "DELETE FROM playlist_groups WHERE id = ?1",
+            params![group_id],
+        )?;
+
+        tx.commit()?;
+        Ok(true)
+    }
+}
+
+// Put `id` at the 1-based `position` among `ids`, clamped to the ends
+pub(crate) fn insert_at(ids: &mut Vec<i64>, id: i64, position: Option<i32>) {
+    let index = match position {
+        Some(position) => (position.max(1) as usize - 1).min(ids.len()),
+        None => ids.len(),
+    };
+    ids.insert(index, id);
+}
+
+// Depth-first, so every group comes right after its parent's earlier children
+fn order_as_tree(groups: Vec<PlaylistGroup>) -> Vec<PlaylistGroup> {
+    let mut children: HashMap<Option<i64>, Vec<PlaylistGroup>> = HashMap::new();
+    for group in groups {
+        children.entry(group.parent_id).or_default().push(group);
+    }
+
+    let mut ordered = Vec::new();
+    let mut stack: Vec<PlaylistGroup> = children.remove(&None).unwrap_or_default();
+    stack.reverse();
+    while let Some(group) = stack.pop() {
+        if let Some(mut nested) = children.remove(&Some(group.id)) {
+            nested.reverse();
+            stack.extend(nested);
+        }
+        ordered.push(group);
+    }
+    ordered
+}
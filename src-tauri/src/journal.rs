@@ -0,0 +1,386 @@
+use crate::database::Database;
+use crate::error::{AppError, AppResult};
+use crate::models::JournalEntry;
+use chrono::Utc;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+/// How many operations can be undone. Older entries are dropped as new ones
+/// are recorded.
+const JOURNAL_LIMIT: i64 = 100;
+
+/// A destructive operation with enough captured state to reverse it and to
+/// perform it again.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum JournalOp {
+    TrashPlaylists {
+        playlist_ids: Vec<i64>,
+    },
+    TrashItem {
+        playlist_id: i64,
+        item_id: i64,
+    },
+    UnassignFolder {
+        playlist_id: i64,
+        item_id: i64,
+        folder_color: String,
+        created_at: String,
+    },
+    /// Every assignment of a folder in a playlist, as item ids with the time
+    /// each was assigned
+    ClearFolder {
+        playlist_id: i64,
+        folder_color: String,
+        assignments: Vec<(i64, String)>,
+    },
+    Reorder {
+        playlist_id: i64,
+        item_id: i64,
+        from: i32,
+        to: i32,
+    },
+    /// A whole playlist reordered at once, as item ids in order
+    SetOrder {
+        playlist_id: i64,
+        before: Vec<i64>,
+        after: Vec<i64>,
+    },
+}
+
+impl JournalOp {
+    fn kind(&self) -> &'static str {
+        match self {
+            JournalOp::TrashPlaylists { .. } => "trash_playlists",
+            JournalOp::TrashItem { .. } => "trash_item",
+            JournalOp::UnassignFolder { .. } => "unassign_folder",
+            JournalOp::ClearFolder { .. } => "clear_folder",
+            JournalOp::Reorder { .. } => "reorder",
+            JournalOp::SetOrder { .. } => "set_order",
+        }
+    }
+}
+
+fn journal_entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<JournalEntry> {
+    Ok(JournalEntry {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        description: row.get(2)?,
+        undone: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+impl Database {
+    /// Record `op` as the newest undoable operation. Must run inside the
+    /// caller's transaction so the journal never disagrees with the data.
+    pub(crate) fn record_operation(&self, op: &JournalOp, description: &str) -> AppResult<()> {
+        let payload = serde_json::to_string(op)
+            .map_err(|e| AppError::internal(format!("failed to serialize operation: {}", e)))?;
+
+        // A new operation forks history; whatever was undone can't be redone
+        self.conn
+            .execute("DELETE FROM operation_journal WHERE undone = 1", [])?;
+        self.conn.execute(
+            "INSERT INTO operation_journal (kind, description, payload, undone, created_at) VALUES (?1, ?2, ?3, 0, ?4)",
+            params![op.kind(), description, payload, Utc::now().to_rfc3339()],
+        )?;
+        self.conn.execute(
+            "DELETE FROM operation_journal WHERE id NOT IN (
+                SELECT id FROM operation_journal ORDER BY id DESC LIMIT ?1
+             )",
+            params![JOURNAL_LIMIT],
+        )?;
+        Ok(())
+    }
+
+    /// Recent operations, newest first, including undone ones that can still
+    /// be redone.
+    pub fn get_operation_journal(&self, limit: u32) -> AppResult<Vec<JournalEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, kind, description, undone, created_at
+             FROM operation_journal
+             ORDER BY id DESC
+             LIMIT ?1",
+        )?;
+
+        let entries = stmt
+            .query_map(params![limit], journal_entry_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entries)
+    }
+
+    /// Reverse the most recent operation. Returns `None` when there is nothing
+    /// left to undo.
+    pub fn undo_last(&self) -> AppResult<Option<JournalEntry>> {
+        self.step_journal(
+            "SELECT id, kind, description, undone, created_at, payload
+             FROM operation_journal WHERE undone = 0 ORDER BY id DESC LIMIT 1",
+            true,
+        )
+    }
+
+    /// Perform the most recently undone operation again. Returns `None` when
+    /// there is nothing to redo.
+    pub fn redo(&self) -> AppResult<Option<JournalEntry>> {
+        self.step_journal(
+            "SELECT id, kind, description, undone, created_at, payload
+             FROM operation_journal WHERE undone = 1 ORDER BY id ASC LIMIT 1",
+            false,
+        )
+    }
+
+    fn step_journal(&self, select: &str, undo: bool) -> AppResult<Option<JournalEntry>> {
+        let row = match self.conn.query_row(select, [], |row| {
+            Ok((journal_entry_from_row(row)?, row.get::<_, String>(5)?))
+        }) {
+            Ok(row) => row,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let (mut entry, payload) = row;
+
+        let op: JournalOp = serde_json::from_str(&payload).map_err(|e| {
+            AppError::internal(format!("unreadable journal entry {}: {}", entry.id, e))
+        })?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        let applied = if undo {
+            self.apply_inverse(&op)
+        } else {
+            self.apply_forward(&op)
+        };
+
+        if let Err(e) = applied {
+            // The library has moved on since (e.g. the item's playlist is gone).
+            // Drop the entry so it doesn't block the rest of the history.
+            drop(tx);
+            self.conn.execute(
+                "DELETE FROM operation_journal WHERE id = ?1",
+                params![entry.id],
+            )?;
+            return Err(AppError::conflict(format!(
+                "could not {} '{}': {}",
+                if undo { "undo" } else { "redo" },
+                entry.description,
+                e
+            )));
+        }
+
+        self.conn.execute(
+            "UPDATE operation_journal SET undone = ?1 WHERE id = ?2",
+            params![undo, entry.id],
+        )?;
+        tx.commit()?;
+
+        entry.undone = undo;
+        Ok(Some(entry))
+    }
+
+    fn apply_forward(&self, op: &JournalOp) -> AppResult<()> {
+        match op {
+            JournalOp::TrashPlaylists { playlist_ids } => {
+                for id in playlist_ids {
+                    if !self.trash_playlist_rows(*id)? {
+                        return Err(AppError::not_found("playlist", id));
+                    }
+                }
+            }
+            JournalOp::TrashItem {
+                playlist_id,
+                item_id,
+            } => {
+                if !self.trash_item_rows(*playlist_id, *item_id)? {
+                    return Err(AppError::not_found("playlist item", item_id));
+                }
+            }
+            JournalOp::UnassignFolder {
+                playlist_id,
+                item_id,
+                folder_color,
+                ..
+            } => {
+                self.unassign_folder_rows(*playlist_id, *item_id, folder_color)?;
+            }
+            JournalOp::ClearFolder {
+                playlist_id,
+                folder_color,
+                assignments,
+            } => {
+                for (item_id, _) in assignments {
+                    self.unassign_folder_rows(*playlist_id, *item_id, folder_color)?;
+                }
+            }
+            JournalOp::Reorder {
+                playlist_id,
+                item_id,
+                to,
+                ..
+            } => {
+                self.move_item_rows(*playlist_id, *item_id, *to)?;
+            }
+            JournalOp::SetOrder {
+                playlist_id, after, ..
+            } => {
+                self.set_item_order(*playlist_id, after)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Folder assignments belong to the item's playlist, so they can't be put
+    // back once the item has been moved to another one
+    fn ensure_item_still_in(&self, playlist_id: i64, item_id: i64) -> AppResult<()> {
+        let current: i64 = match self.conn.query_row(
+            "SELECT playlist_id FROM playlist_items WHERE id = ?1",
+            params![item_id],
+            |row| row.get(0),
+        ) {
+            Ok(current) => current,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Err(AppError::not_found("playlist item", item_id))
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if current != playlist_id {
+            return Err(AppError::conflict(format!(
+                "item {} has moved from playlist {} to playlist {}",
+                item_id, playlist_id, current
+            )));
+        }
+        Ok(())
+    }
+
+    fn apply_inverse(&self, op: &JournalOp) -> AppResult<()> {
+        match op {
+            // Anything already restored or purged from the trash in the
+            // meantime can't be brought back from here
+            JournalOp::TrashPlaylists { playlist_ids } => {
+                for id in playlist_ids {
+                    if !self.untrash_playlist_rows(*id)? {
+                        return Err(AppError::not_found("trashed playlist", id));
+                    }
+                }
+            }
+            JournalOp::TrashItem {
+                playlist_id,
+                item_id,
+            } => {
+                if !self.untrash_item_rows(*playlist_id, *item_id)? {
+                    return Err(AppError::not_found("trashed playlist item", item_id));
+                }
+            }
+            JournalOp::UnassignFolder {
+                playlist_id,
+                item_id,
+                folder_color,
+                created_at,
+            } => {
+                // A user folder may have been deleted since
+                let folder_color = self.resolve_folder(folder_color)?;
+                self.ensure_item_still_in(*playlist_id, *item_id)?;
+                self.conn.execute(
+                    "INSERT OR IGNORE INTO video_folder_assignments (playlist_id, item_id, folder_color, created_at)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![playlist_id, item_id, folder_color, created_at],
+                )?;
+            }
+            JournalOp::ClearFolder {
+                playlist_id,
+                folder_color,
+                assignments,
+            } => {
+                let folder_color = self.resolve_folder(folder_color)?;
+                for (item_id, created_at) in assignments {
+                    self.ensure_item_still_in(*playlist_id, *item_id)?;
+                    self.conn.execute(
+                        "INSERT OR IGNORE INTO video_folder_assignments (playlist_id, item_id, folder_color, created_at)
+                         VALUES (?1, ?2, ?3, ?4)",
+                        params![playlist_id, item_id, folder_color, created_at],
+                    )?;
+                }
+            }
+            JournalOp::Reorder {
+                playlist_id,
+                item_id,
+                from,
+                ..
+            } => {
+                if !self.move_item_rows(*playlist_id, *item_id, *from)? {
+                    return Err(AppError::not_found("playlist item", item_id));
+                }
+            }
+            JournalOp::SetOrder {
+                playlist_id,
+                before,
+                ..
+            } => {
+                self.set_item_order(*playlist_id, before)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::Database;
+    use crate::error::AppError;
+
+    #[test]
+    fn undoing_folder_removal_after_a_move_is_refused() {
+        let db = Database::new(Some(":memory:")).unwrap();
+        let from = db.create_playlist("from", None).unwrap();
+        let to = db.create_playlist("to", None).unwrap();
+        let add = |video_id: &str| {
+            db.add_video_to_playlist(from, "u", video_id, None, None, false, None, None, None)
+                .unwrap()
+        };
+        let (first, second) = (add("a"), add("b"));
+        db.assign_video_to_folder(from, first, "red").unwrap();
+        db.assign_video_to_folder(from, second, "blue").unwrap();
+
+        db.unassign_video_from_folder(from, first, "red").unwrap();
+        db.clear_folder(from, "blue").unwrap();
+        db.move_items(&[first, second], to, None, true).unwrap();
+
+        for _ in 0..2 {
+            match db.undo_last() {
+                Err(AppError::Conflict { message }) => {
+                    assert!(message.contains("has moved"), "{}", message)
+                }
+                other => panic!("expected a conflict, got {:?}", other),
+            }
+        }
+
+        // Nothing was assigned to either playlist
+        for playlist_id in [from, to] {
+            for color in ["red", "blue"] {
+                assert!(db
+                    .get_videos_in_folder(playlist_id, color)
+                    .unwrap()
+                    .is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn undoing_folder_removal_in_place_restores_it() {
+        let db = Database::new(Some(":memory:")).unwrap();
+        let playlist_id = db.create_playlist("p", None).unwrap();
+        let item_id = db
+            .add_video_to_playlist(playlist_id, "u", "a", None, None, false, None, None, None)
+            .unwrap();
+        db.assign_video_to_folder(playlist_id, item_id, "red")
+            .unwrap();
+        db.unassign_video_from_folder(playlist_id, item_id, "red")
+            .unwrap();
+
+        assert!(db.undo_last().unwrap().is_some());
+        let items = db.get_videos_in_folder(playlist_id, "red").unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, item_id);
+    }
+}
@@ -1,11 +1,30 @@
 mod audio_capture;
+mod backup;
+mod bundle;
 mod commands;
 mod database;
+mod db_location;
+mod db_pool;
+mod error;
+mod folders;
+mod global_folders;
+mod groups;
+mod journal;
+mod likes;
+mod migrations;
 mod models;
+mod paging;
+mod pins;
+mod queue;
+mod smart;
+mod sorting;
 mod streaming_server;
+mod tabs;
+mod trash;
 
-use database::Database;
-use std::sync::{Arc, Mutex};
+use backup::Backups;
+use db_pool::DbPool;
+use std::sync::Arc;
 use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -16,20 +35,21 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .setup(|app| {
-            // Initialize database - use app data directory to avoid triggering rebuilds
-            // In dev mode, store outside src-tauri/ to prevent file watcher from restarting app
-            let db_path = if cfg!(debug_assertions) {
-                // Development: store in project root (outside src-tauri/)
-                Some("../playlists.db")
-            } else {
-                // Production: use app data directory
-                // TODO: Use app.path() with path plugin for production
-                None
-            };
+            // Initialize database in the per-user app data directory (or wherever
+            // --db-path / YTTV_DB_PATH point)
+            let app_data_dir = app.path().app_data_dir()?;
+            let db_path = db_location::resolve(&app_data_dir)?;
 
-            let db = Database::new(db_path.as_deref()).expect("Failed to initialize database");
+            let db = DbPool::open(&db_path.to_string_lossy())
+                .expect("Failed to initialize database");
 
-            app.manage(Mutex::new(db));
+            // Automatic backups live with the rest of the app data, wherever
+            // the database itself is
+            let backups = Backups::open(db.clone(), app_data_dir.join(backup::BACKUP_DIR_NAME))?;
+            tauri::async_runtime::spawn(backups.clone().run_schedule());
+
+            app.manage(db);
+            app.manage(backups);
 
             // Start streaming server for local video files
             let streaming_server = Arc::new(streaming_server::StreamingServer::new(1422));
@@ -47,17 +67,60 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::create_playlist,
+            commands::create_smart_playlist,
+            commands::update_smart_playlist_rules,
             commands::get_all_playlists,
             commands::get_all_playlist_metadata,
             commands::get_playlist,
             commands::update_playlist,
             commands::delete_playlist,
             commands::delete_playlist_by_name,
+            commands::create_playlist_group,
+            commands::get_playlist_groups,
+            commands::rename_playlist_group,
+            commands::move_playlist_group,
+            commands::delete_playlist_group,
+            commands::move_playlist_to_group,
+            commands::get_tabs,
+            commands::create_tab,
+            commands::rename_tab,
+            commands::move_tab,
+            commands::delete_tab,
+            commands::add_playlist_to_tab,
+            commands::remove_playlist_from_tab,
+            commands::get_tab_presets,
+            commands::create_tab_preset,
+            commands::update_tab_preset,
+            commands::move_tab_preset,
+            commands::delete_tab_preset,
+            commands::import_local_tabs,
             commands::add_video_to_playlist,
+            commands::add_videos_to_playlist,
             commands::get_playlist_items,
+            commands::get_playlist_items_page,
             commands::get_playlists_for_video_ids,
             commands::remove_video_from_playlist,
             commands::reorder_playlist_item,
+            commands::move_items,
+            commands::copy_items,
+            commands::sort_playlist_items,
+            commands::save_playlist_view,
+            commands::get_playlist_views,
+            commands::delete_playlist_view,
+            commands::repair_playlist_positions,
+            commands::get_folders,
+            commands::create_folder,
+            commands::update_folder,
+            commands::move_folder,
+            commands::delete_folder,
+            commands::get_global_folders,
+            commands::create_global_folder,
+            commands::update_global_folder,
+            commands::delete_global_folder,
+            commands::get_global_folder_items,
+            commands::add_items_to_global_folder,
+            commands::remove_items_from_global_folder,
+            commands::get_global_folders_for_items,
             commands::assign_video_to_folder,
             commands::unassign_video_from_folder,
             commands::get_videos_in_folder,
@@ -65,9 +128,17 @@ pub fn run() {
             commands::get_video_folder_assignments,
             commands::get_all_folders_with_videos,
             commands::get_folders_for_playlist,
+            commands::move_folder_items,
+            commands::copy_folder_to_new_playlist,
+            commands::recolor_folder,
+            commands::merge_folders,
+            commands::clear_folder,
             commands::toggle_stuck_folder,
             commands::is_folder_stuck,
             commands::get_all_stuck_folders,
+            commands::get_playlist_folder_states,
+            commands::set_folder_visibility,
+            commands::set_folder_order,
             commands::get_folder_metadata,
             commands::set_folder_metadata,
             commands::add_to_watch_history,
@@ -77,6 +148,41 @@ pub fn run() {
             commands::update_video_progress,
             commands::get_video_progress,
             commands::get_all_video_progress,
+            commands::undo_last,
+            commands::redo,
+            commands::get_operation_journal,
+            commands::list_trash,
+            commands::restore_from_trash,
+            commands::purge_trash,
+            commands::get_pins,
+            commands::pin_video,
+            commands::set_priority_pin,
+            commands::unpin_video,
+            commands::get_likes,
+            commands::like_video,
+            commands::unlike_video,
+            commands::get_likes_for_video_ids,
+            commands::get_playback_queue,
+            commands::queue_next,
+            commands::queue_previous,
+            commands::queue_jump,
+            commands::reshuffle_queue,
+            commands::set_queue_shuffle,
+            commands::set_queue_repeat,
+            commands::get_video,
+            commands::update_video_metadata,
+            commands::export_playlist,
+            commands::export_library,
+            commands::export_playlist_m3u8,
+            commands::import_playlist,
+            commands::import_library,
+            commands::create_backup,
+            commands::list_backups,
+            commands::restore_backup,
+            commands::delete_backup,
+            commands::get_backup_settings,
+            commands::set_backup_settings,
+            commands::search_library,
             commands::select_video_files,
             commands::read_video_file,
             commands::get_video_stream_url,
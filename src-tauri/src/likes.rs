@@ -0,0 +1,125 @@
+use crate::database::{video_from_row, Database, VIDEO_COLUMNS};
+use crate::error::AppResult;
+use crate::models::{ExportedLike, Like, VideoRequest};
+use chrono::Utc;
+use rusqlite::params;
+use std::collections::HashMap;
+
+fn like_from_row(row: &rusqlite::Row) -> rusqlite::Result<Like> {
+    Ok(Like {
+        video: video_from_row(row)?,
+        liked_at: row.get(10)?,
+    })
+}
+
+// Likes are keyed by video, like pins, so liking a video from any playlist
+// likes it everywhere.
+impl Database {
+    /// Liked videos, most recently liked first.
+    pub fn get_likes(&self) -> AppResult<Vec<Like>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}, l.liked_at
+             FROM likes l
+             INNER JOIN videos v ON v.video_id = l.video_id
+             ORDER BY l.liked_at DESC, l.video_id",
+            VIDEO_COLUMNS
+        ))?;
+
+        let likes = stmt
+            .query_map([], like_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(likes)
+    }
+
+    /// Like a video. Liking it again keeps the original time.
+    pub fn like_video(&self, video: &VideoRequest) -> AppResult<Like> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.store_video_request(video)?;
+        self.conn.execute(
+            "INSERT INTO likes (video_id, liked_at) VALUES (?1, ?2)
+             ON CONFLICT(video_id) DO NOTHING",
+            params![video.video_id, Utc::now().to_rfc3339()],
+        )?;
+        tx.commit()?;
+
+        let like = self.conn.query_row(
+            &format!(
+                "SELECT {}, l.liked_at
+                 FROM likes l
+                 INNER JOIN videos v ON v.video_id = l.video_id
+                 WHERE l.video_id = ?1",
+                VIDEO_COLUMNS
+            ),
+            params![video.video_id],
+            like_from_row,
+        )?;
+        Ok(like)
+    }
+
+    pub fn unlike_video(&self, video_id: &str) -> AppResult<bool> {
+        let rows = self
+            .conn
+            .execute("DELETE FROM likes WHERE video_id = ?1", params![video_id])?;
+        Ok(rows > 0)
+    }
+
+    /// When each of `video_ids` was liked. Videos that aren't liked are left
+    /// out.
+    pub fn get_likes_for_video_ids(
+        &self,
+        video_ids: &[String],
+    ) -> AppResult<HashMap<String, String>> {
+        if video_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let placeholders = vec!["?"; video_ids.len()].join(",");
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT video_id, liked_at FROM likes WHERE video_id IN ({})",
+            placeholders
+        ))?;
+
+        let likes = stmt
+            .query_map(rusqlite::params_from_iter(video_ids), |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(likes)
+    }
+
+    pub fn export_likes(&self) -> AppResult<Vec<ExportedLike>> {
+        let likes = self
+            .get_likes()?
+            .into_iter()
+            .map(|like| ExportedLike {
+                video: VideoRequest {
+                    video_id: like.video.video_id,
+                    video_url: like.video.video_url,
+                    title: like.video.title,
+                    thumbnail_url: like.video.thumbnail_url,
+                    author: like.video.author,
+                    view_count: like.video.view_count,
+                    published_at: like.video.published_at,
+                    is_local: like.video.is_local,
+                },
+                liked_at: like.liked_at,
+            })
+            .collect();
+        Ok(likes)
+    }
+
+    // Callers own the transaction. Videos that are already liked keep their
+    // time; returns how many likes were added.
+    pub(crate) fn insert_exported_likes(&self, likes: &[ExportedLike]) -> AppResult<usize> {
+        let mut added = 0;
+        for like in likes {
+            self.store_video_request(&like.video)?;
+            added += self.conn.execute(
+                "INSERT INTO likes (video_id, liked_at) VALUES (?1, ?2)
+                 ON CONFLICT(video_id) DO NOTHING",
+                params![like.video.video_id, like.liked_at],
+            )?;
+        }
+        Ok(added)
+    }
+}
@@ -0,0 +1,1502 @@
+use chrono::Utc;
+use rusqlite::{params, Connection, Transaction};
+use std::fmt;
+
+/// A single schema change. Migrations are applied in ascending `version`
+/// order, each inside its own transaction, and `PRAGMA user_version` is bumped
+/// to `version` in the same transaction so a failed step leaves the database
+/// untouched at the previous version.
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    up: fn(&Transaction) -> rusqlite::Result<()>,
+}
+
+/// Every schema change ever shipped, oldest first. Never edit or reorder an
+/// entry once released - append a new one instead.
+///
+/// Versions 1-6 reproduce what the old ad-hoc `init_schema` did. Databases
+/// created before versioning report `user_version = 0` but may already have
+/// any subset of those columns, so those steps only add what is missing.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema",
+        up: initial_schema,
+    },
+    Migration {
+        version: 2,
+        description: "add playlist_items.is_local",
+        up: add_is_local,
+    },
+    Migration {
+        version: 3,
+        description: "add playlist_items author, view_count and published_at",
+        up: add_item_metadata,
+    },
+    Migration {
+        version: 4,
+        description: "add video_progress.has_fully_watched",
+        up: add_has_fully_watched,
+    },
+    Migration {
+        version: 5,
+        description: "add custom_ascii to playlists and folder_metadata",
+        up: add_custom_ascii,
+    },
+    Migration {
+        version: 6,
+        description: "add playlists.custom_thumbnail_url",
+        up: add_custom_thumbnail_url,
+    },
+    Migration {
+        version: 7,
+        description: "full-text search index over items, playlists and folders",
+        up: add_library_search,
+    },
+    Migration {
+        version: 8,
+        description: "canonical videos table shared by playlist items",
+        up: add_videos_table,
+    },
+    Migration {
+        version: 9,
+        description: "operation journal for undo and redo",
+        up: add_operation_journal,
+    },
+    Migration {
+        version: 10,
+        description: "soft-delete trash for playlists and items",
+        up: add_trash,
+    },
+    Migration {
+        version: 11,
+        description: "gap-free, unique item positions",
+        up: enforce_unique_positions,
+    },
+    Migration {
+        version: 12,
+        description: "saved sort views for playlists",
+        up: add_playlist_views,
+    },
+    Migration {
+        version: 13,
+        description: "smart playlists defined by rules",
+        up: add_smart_playlists,
+    },
+    Migration {
+        version: 14,
+        description: "nested playlist groups and manual playlist order",
+        up: add_playlist_groups,
+    },
+    Migration {
+        version: 15,
+        description: "pinned videos",
+        up: add_pins,
+    },
+    Migration {
+        version: 16,
+        description: "per-playlist playback queues",
+        up: add_playback_queues,
+    },
+    Migration {
+        version: 17,
+        description: "liked videos",
+        up: add_likes,
+    },
+    Migration {
+        version: 18,
+        description: "playlist tabs and tab presets",
+        up: add_tabs,
+    },
+    Migration {
+        version: 19,
+        description: "folders table referenced by folder colors",
+        up: add_folders,
+    },
+    Migration {
+        version: 20,
+        description: "folder_visibility replaces stuck_folders",
+        up: add_folder_visibility,
+    },
+    Migration {
+        version: 21,
+        description: "global folders spanning playlists",
+        up: add_global_folders,
+    },
+];
+
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    Sqlite(rusqlite::Error),
+    /// The database was written by a newer build of the app. Opening it would
+    /// risk corrupting columns this build doesn't know about.
+    NewerSchema {
+        found: i64,
+        supported: i64,
+    },
+    Failed {
+        version: i64,
+        description: &'static str,
+        source: rusqlite::Error,
+    },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::Sqlite(e) => write!(f, "{}", e),
+            MigrationError::NewerSchema { found, supported } => write!(
+                f,
+                "database schema version {} is newer than the latest supported version {}",
+                found, supported
+            ),
+            MigrationError::Failed {
+                version,
+                description,
+                source,
+            } => write!(
+                f,
+                "migration {} ({}) failed: {}",
+                version, description, source
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MigrationError::Sqlite(e) => Some(e),
+            MigrationError::Failed { source, .. } => Some(source),
+            MigrationError::NewerSchema { .. } => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(e: rusqlite::Error) -> Self {
+        MigrationError::Sqlite(e)
+    }
+}
+
+pub fn user_version(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Bring the database up to `latest_version()`.
+pub fn run(conn: &mut Connection) -> Result<(), MigrationError> {
+    let current = user_version(conn)?;
+    let latest = latest_version();
+
+    if current > latest {
+        return Err(MigrationError::NewerSchema {
+            found: current,
+            supported: latest,
+        });
+    }
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    if current == latest {
+        return Ok(());
+    }
+
+    // Table rebuilds (create new, copy, drop old, rename) must not trigger
+    // cascading deletes, and foreign_keys can't be toggled inside a transaction.
+    let foreign_keys: bool = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0))?;
+    conn.pragma_update(None, "foreign_keys", false)?;
+
+    let result = apply_pending(conn, current);
+
+    conn.pragma_update(None, "foreign_keys", foreign_keys)?;
+    result
+}
+
+fn apply_pending(conn: &mut Connection, current: i64) -> Result<(), MigrationError> {
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let fail = |source| MigrationError::Failed {
+            version: migration.version,
+            description: migration.description,
+            source,
+        };
+
+        let tx = conn.transaction().map_err(fail)?;
+        (migration.up)(&tx).map_err(fail)?;
+        tx.execute(
+            "INSERT OR REPLACE INTO schema_migrations (version, description, applied_at) VALUES (?1, ?2, ?3)",
+            params![
+                migration.version,
+                migration.description,
+                Utc::now().to_rfc3339()
+            ],
+        )
+        .map_err(fail)?;
+        tx.pragma_update(None, "user_version", migration.version)
+            .map_err(fail)?;
+        tx.commit().map_err(fail)?;
+    }
+
+    Ok(())
+}
+
+fn column_names(tx: &Transaction, table: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = tx.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(columns)
+}
+
+// SQLite has no ADD COLUMN IF NOT EXISTS, and pre-versioning databases may
+// already carry the column.
+fn add_column_if_missing(
+    tx: &Transaction,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<()> {
+    if !column_names(tx, table)?.iter().any(|c| c == column) {
+        tx.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+fn initial_schema(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS playlists (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            description TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS playlist_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            playlist_id INTEGER NOT NULL,
+            video_url TEXT NOT NULL,
+            video_id TEXT NOT NULL,
+            title TEXT,
+            thumbnail_url TEXT,
+            position INTEGER NOT NULL,
+            added_at TEXT NOT NULL,
+            FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_playlist_items_playlist_id ON playlist_items(playlist_id);
+        CREATE INDEX IF NOT EXISTS idx_playlist_items_position ON playlist_items(playlist_id, position);
+
+        CREATE TABLE IF NOT EXISTS video_folder_assignments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            playlist_id INTEGER NOT NULL,
+            item_id INTEGER NOT NULL,
+            folder_color TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
+            FOREIGN KEY (item_id) REFERENCES playlist_items(id) ON DELETE CASCADE,
+            UNIQUE(playlist_id, item_id, folder_color)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_folder_assignments_playlist_color ON video_folder_assignments(playlist_id, folder_color);
+        CREATE INDEX IF NOT EXISTS idx_folder_assignments_item ON video_folder_assignments(item_id);
+
+        CREATE TABLE IF NOT EXISTS watch_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            video_url TEXT NOT NULL,
+            video_id TEXT NOT NULL,
+            title TEXT,
+            thumbnail_url TEXT,
+            watched_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_watch_history_watched_at ON watch_history(watched_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_watch_history_video_id ON watch_history(video_id);
+
+        CREATE TABLE IF NOT EXISTS video_progress (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            video_id TEXT NOT NULL UNIQUE,
+            video_url TEXT NOT NULL,
+            duration REAL,
+            last_progress REAL NOT NULL DEFAULT 0,
+            progress_percentage REAL NOT NULL DEFAULT 0,
+            last_updated TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_video_progress_video_id ON video_progress(video_id);
+
+        CREATE TABLE IF NOT EXISTS stuck_folders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            playlist_id INTEGER NOT NULL,
+            folder_color TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
+            UNIQUE(playlist_id, folder_color)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_stuck_folders_playlist_color ON stuck_folders(playlist_id, folder_color);
+
+        CREATE TABLE IF NOT EXISTS folder_metadata (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            playlist_id INTEGER NOT NULL,
+            folder_color TEXT NOT NULL,
+            custom_name TEXT,
+            description TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
+            UNIQUE(playlist_id, folder_color)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_folder_metadata_playlist_color ON folder_metadata(playlist_id, folder_color);",
+    )
+}
+
+fn add_is_local(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(
+        tx,
+        "playlist_items",
+        "is_local",
+        "INTEGER NOT NULL DEFAULT 0",
+    )
+}
+
+fn add_item_metadata(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "playlist_items", "author", "TEXT")?;
+    add_column_if_missing(tx, "playlist_items", "view_count", "TEXT")?;
+    add_column_if_missing(tx, "playlist_items", "published_at", "TEXT")
+}
+
+fn add_has_fully_watched(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(
+        tx,
+        "video_progress",
+        "has_fully_watched",
+        "INTEGER NOT NULL DEFAULT 0",
+    )
+}
+
+fn add_custom_ascii(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "playlists", "custom_ascii", "TEXT")?;
+    add_column_if_missing(tx, "folder_metadata", "custom_ascii", "TEXT")
+}
+
+fn add_custom_thumbnail_url(tx: &Transaction) -> rusqlite::Result<()> {
+    add_column_if_missing(tx, "playlists", "custom_thumbnail_url", "TEXT")
+}
+
+// One FTS5 table indexes all three sources. The rowid encodes the source so
+// triggers can update a single row without scanning: id * 4 + 1 for items,
+// + 2 for playlists, + 3 for folder metadata.
+fn add_library_search(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE VIRTUAL TABLE library_search USING fts5(
+            title,
+            author,
+            description,
+            kind UNINDEXED,
+            source_id UNINDEXED,
+            playlist_id UNINDEXED,
+            folder_color UNINDEXED,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            SELECT id * 4 + 1, title, author, NULL, 'item', id, playlist_id, NULL FROM playlist_items;
+        INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            SELECT id * 4 + 2, name, NULL, description, 'playlist', id, id, NULL FROM playlists;
+        INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            SELECT id * 4 + 3, custom_name, NULL, description, 'folder', id, playlist_id, folder_color FROM folder_metadata;
+
+        CREATE TRIGGER library_search_item_insert AFTER INSERT ON playlist_items BEGIN
+            INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            VALUES (new.id * 4 + 1, new.title, new.author, NULL, 'item', new.id, new.playlist_id, NULL);
+        END;
+        CREATE TRIGGER library_search_item_update AFTER UPDATE OF title, author, playlist_id ON playlist_items BEGIN
+            DELETE FROM library_search WHERE rowid = old.id * 4 + 1;
+            INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            VALUES (new.id * 4 + 1, new.title, new.author, NULL, 'item', new.id, new.playlist_id, NULL);
+        END;
+        CREATE TRIGGER library_search_item_delete AFTER DELETE ON playlist_items BEGIN
+            DELETE FROM library_search WHERE rowid = old.id * 4 + 1;
+        END;
+
+        CREATE TRIGGER library_search_playlist_insert AFTER INSERT ON playlists BEGIN
+            INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            VALUES (new.id * 4 + 2, new.name, NULL, new.description, 'playlist', new.id, new.id, NULL);
+        END;
+        CREATE TRIGGER library_search_playlist_update AFTER UPDATE OF name, description ON playlists BEGIN
+            DELETE FROM library_search WHERE rowid = old.id * 4 + 2;
+            INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            VALUES (new.id * 4 + 2, new.name, NULL, new.description, 'playlist', new.id, new.id, NULL);
+        END;
+        CREATE TRIGGER library_search_playlist_delete AFTER DELETE ON playlists BEGIN
+            DELETE FROM library_search WHERE rowid = old.id * 4 + 2;
+        END;
+
+        CREATE TRIGGER library_search_folder_insert AFTER INSERT ON folder_metadata BEGIN
+            INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            VALUES (new.id * 4 + 3, new.custom_name, NULL, new.description, 'folder', new.id, new.playlist_id, new.folder_color);
+        END;
+        CREATE TRIGGER library_search_folder_update AFTER UPDATE OF custom_name, description, playlist_id, folder_color ON folder_metadata BEGIN
+            DELETE FROM library_search WHERE rowid = old.id * 4 + 3;
+            INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            VALUES (new.id * 4 + 3, new.custom_name, NULL, new.description, 'folder', new.id, new.playlist_id, new.folder_color);
+        END;
+        CREATE TRIGGER library_search_folder_delete AFTER DELETE ON folder_metadata BEGIN
+            DELETE FROM library_search WHERE rowid = old.id * 4 + 3;
+        END;",
+    )
+}
+
+// Video metadata used to be copied into every playlist_items row. Collapse it
+// into one row per video_id, keeping the most recently added non-null value of
+// each field, and rebuild playlist_items to reference it.
+fn add_videos_table(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE videos (
+            video_id TEXT PRIMARY KEY,
+            video_url TEXT NOT NULL,
+            title TEXT,
+            thumbnail_url TEXT,
+            author TEXT,
+            view_count TEXT,
+            published_at TEXT,
+            is_local INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        INSERT INTO videos (video_id, video_url, title, thumbnail_url, author, view_count, published_at, is_local, created_at, updated_at)
+            SELECT
+                pi.video_id,
+                (SELECT video_url FROM playlist_items x WHERE x.video_id = pi.video_id
+                    ORDER BY x.added_at DESC, x.id DESC LIMIT 1),
+                (SELECT title FROM playlist_items x WHERE x.video_id = pi.video_id AND x.title IS NOT NULL
+                    ORDER BY x.added_at DESC, x.id DESC LIMIT 1),
+                (SELECT thumbnail_url FROM playlist_items x WHERE x.video_id = pi.video_id AND x.thumbnail_url IS NOT NULL
+                    ORDER BY x.added_at DESC, x.id DESC LIMIT 1),
+                (SELECT author FROM playlist_items x WHERE x.video_id = pi.video_id AND x.author IS NOT NULL
+                    ORDER BY x.added_at DESC, x.id DESC LIMIT 1),
+                (SELECT view_count FROM playlist_items x WHERE x.video_id = pi.video_id AND x.view_count IS NOT NULL
+                    ORDER BY x.added_at DESC, x.id DESC LIMIT 1),
+                (SELECT published_at FROM playlist_items x WHERE x.video_id = pi.video_id AND x.published_at IS NOT NULL
+                    ORDER BY x.added_at DESC, x.id DESC LIMIT 1),
+                MAX(pi.is_local),
+                MIN(pi.added_at),
+                MAX(pi.added_at)
+            FROM playlist_items pi
+            GROUP BY pi.video_id;
+
+        CREATE TABLE playlist_items_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            playlist_id INTEGER NOT NULL,
+            video_id TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            added_at TEXT NOT NULL,
+            FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
+            FOREIGN KEY (video_id) REFERENCES videos(video_id) ON UPDATE CASCADE
+        );
+
+        INSERT INTO playlist_items_new (id, playlist_id, video_id, position, added_at)
+            SELECT id, playlist_id, video_id, position, added_at FROM playlist_items;
+
+        DROP TABLE playlist_items;
+        ALTER TABLE playlist_items_new RENAME TO playlist_items;
+
+        CREATE INDEX idx_playlist_items_playlist_id ON playlist_items(playlist_id);
+        CREATE INDEX idx_playlist_items_position ON playlist_items(playlist_id, position);
+        CREATE INDEX idx_playlist_items_video_id ON playlist_items(video_id);
+
+        DELETE FROM library_search WHERE kind = 'item';
+        INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            SELECT pi.id * 4 + 1, v.title, v.author, NULL, 'item', pi.id, pi.playlist_id, NULL
+            FROM playlist_items pi INNER JOIN videos v ON v.video_id = pi.video_id;
+
+        CREATE TRIGGER library_search_item_insert AFTER INSERT ON playlist_items BEGIN
+            INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            SELECT new.id * 4 + 1, title, author, NULL, 'item', new.id, new.playlist_id, NULL
+            FROM videos WHERE video_id = new.video_id;
+        END;
+        CREATE TRIGGER library_search_item_update AFTER UPDATE OF video_id, playlist_id ON playlist_items BEGIN
+            DELETE FROM library_search WHERE rowid = old.id * 4 + 1;
+            INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            SELECT new.id * 4 + 1, title, author, NULL, 'item', new.id, new.playlist_id, NULL
+            FROM videos WHERE video_id = new.video_id;
+        END;
+        CREATE TRIGGER library_search_item_delete AFTER DELETE ON playlist_items BEGIN
+            DELETE FROM library_search WHERE rowid = old.id * 4 + 1;
+        END;
+
+        CREATE TRIGGER library_search_video_update AFTER UPDATE OF title, author ON videos BEGIN
+            DELETE FROM library_search WHERE rowid IN (
+                SELECT id * 4 + 1 FROM playlist_items WHERE video_id = new.video_id
+            );
+            INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            SELECT id * 4 + 1, new.title, new.author, NULL, 'item', id, playlist_id, NULL
+            FROM playlist_items WHERE video_id = new.video_id;
+        END;",
+    )
+}
+
+fn add_operation_journal(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE operation_journal (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            description TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            undone INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX idx_operation_journal_undone ON operation_journal(undone, id);",
+    )
+}
+
+fn add_trash(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE playlists ADD COLUMN deleted_at TEXT;
+        ALTER TABLE playlist_items ADD COLUMN deleted_at TEXT;
+
+        CREATE TABLE trash (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL CHECK (kind IN ('playlist', 'item')),
+            playlist_id INTEGER NOT NULL REFERENCES playlists(id) ON DELETE CASCADE,
+            item_id INTEGER REFERENCES playlist_items(id) ON DELETE CASCADE,
+            deleted_at TEXT NOT NULL
+        );
+
+        CREATE INDEX idx_trash_deleted_at ON trash(deleted_at);
+        CREATE INDEX idx_trash_playlist_id ON trash(playlist_id);
+        CREATE INDEX idx_trash_item_id ON trash(item_id);
+
+        -- Deletes recorded so far carry snapshots of rows that are really
+        -- gone; undo now works by restoring from the trash instead
+        DELETE FROM operation_journal WHERE kind IN ('delete_playlists', 'remove_item');",
+    )
+}
+
+fn enforce_unique_positions(tx: &Transaction) -> rusqlite::Result<()> {
+    // Older builds could leave gaps, duplicates or 0-based positions behind.
+    // Renumber every playlist to 1..=n in its current order first.
+    tx.execute_batch(
+        "UPDATE playlist_items SET position = r.rank
+         FROM (
+            SELECT id, ROW_NUMBER() OVER (PARTITION BY playlist_id ORDER BY position, id) AS rank
+            FROM playlist_items
+            WHERE deleted_at IS NULL
+         ) r
+         WHERE playlist_items.id = r.id;
+
+        CREATE UNIQUE INDEX idx_playlist_items_live_position
+            ON playlist_items(playlist_id, position) WHERE deleted_at IS NULL;",
+    )
+}
+
+fn add_playlist_views(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE playlist_views (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            playlist_id INTEGER NOT NULL REFERENCES playlists(id) ON DELETE CASCADE,
+            name TEXT NOT NULL,
+            sort_by TEXT NOT NULL,
+            descending INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            UNIQUE(playlist_id, name)
+        );",
+    )
+}
+
+fn add_smart_playlists(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch("ALTER TABLE playlists ADD COLUMN smart_rules TEXT;")
+}
+
+fn add_playlist_groups(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE playlist_groups (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            parent_id INTEGER REFERENCES playlist_groups(id) ON DELETE SET NULL,
+            name TEXT NOT NULL,
+            position INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE INDEX idx_playlist_groups_parent ON playlist_groups(parent_id, position);
+
+        ALTER TABLE playlists ADD COLUMN group_id INTEGER REFERENCES playlist_groups(id) ON DELETE SET NULL;
+        ALTER TABLE playlists ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0;
+
+        -- Keep the order playlists were listed in until now: newest first
+        UPDATE playlists SET sort_order = ranked.rank
+        FROM (
+            SELECT id, ROW_NUMBER() OVER (ORDER BY created_at DESC, id DESC) AS rank
+            FROM playlists
+        ) AS ranked
+        WHERE playlists.id = ranked.id;
+
+        CREATE INDEX idx_playlists_group_order ON playlists(group_id, sort_order);",
+    )
+}
+
+fn add_pins(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE pins (
+            video_id TEXT PRIMARY KEY REFERENCES videos(video_id) ON DELETE CASCADE,
+            is_priority INTEGER NOT NULL DEFAULT 0,
+            priority_order INTEGER,
+            pinned_at TEXT NOT NULL
+        );",
+    )
+}
+
+fn add_playback_queues(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE playback_queues (
+            playlist_id INTEGER PRIMARY KEY REFERENCES playlists(id) ON DELETE CASCADE,
+            current_item_id INTEGER,
+            shuffle_order TEXT,
+            repeat_mode TEXT NOT NULL DEFAULT 'off',
+            updated_at TEXT NOT NULL
+        );",
+    )
+}
+
+// Likes used to be the items of a playlist named "Likes"; carry them over. The
+// playlist itself is left alone.
+fn add_likes(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE likes (
+            video_id TEXT PRIMARY KEY REFERENCES videos(video_id) ON DELETE CASCADE,
+            liked_at TEXT NOT NULL
+        );
+        CREATE INDEX idx_likes_liked_at ON likes(liked_at);
+
+        INSERT INTO likes (video_id, liked_at)
+        SELECT pi.video_id, MIN(pi.added_at)
+        FROM playlist_items pi
+        INNER JOIN playlists p ON p.id = pi.playlist_id
+        WHERE p.name = 'Likes' AND p.deleted_at IS NULL AND pi.deleted_at IS NULL
+        GROUP BY pi.video_id;",
+    )
+}
+
+// Membership rows of a trashed playlist stay so restoring it puts it back in
+// its tabs; purging it removes them through the cascade.
+fn add_tabs(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE tabs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE tab_playlists (
+            tab_id INTEGER NOT NULL REFERENCES tabs(id) ON DELETE CASCADE,
+            playlist_id INTEGER NOT NULL REFERENCES playlists(id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            PRIMARY KEY (tab_id, playlist_id)
+        );
+        CREATE INDEX idx_tab_playlists_playlist ON tab_playlists(playlist_id);
+
+        CREATE TABLE tab_presets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE tab_preset_tabs (
+            preset_id INTEGER NOT NULL REFERENCES tab_presets(id) ON DELETE CASCADE,
+            tab_id INTEGER NOT NULL REFERENCES tabs(id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            PRIMARY KEY (preset_id, tab_id)
+        );
+        CREATE INDEX idx_tab_preset_tabs_tab ON tab_preset_tabs(tab_id);",
+    )
+}
+
+// A stored folder color in its canonical form: trimmed and lowercased, with a
+// builtin color's hex value replaced by its key. `x` is the table's alias.
+const NORMALIZED_FOLDER_COLOR: &str = "COALESCE(
+    (SELECT color_key FROM folders WHERE hex = LOWER(TRIM(x.folder_color))),
+    LOWER(TRIM(x.folder_color))
+)";
+
+// Folder colors used to be free-form strings checked only by the frontend.
+// Give them a table of their own, seeded with the 16 builtin colors, and
+// rebuild the three tables that use them to reference it. Colors that still
+// aren't known after normalizing abort the migration rather than being
+// dropped; rows that become duplicates keep the oldest.
+fn add_folders(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE folders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            color_key TEXT NOT NULL UNIQUE,
+            name TEXT NOT NULL,
+            hex TEXT NOT NULL,
+            is_builtin INTEGER NOT NULL DEFAULT 0,
+            position INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        INSERT INTO folders (color_key, name, hex, is_builtin, position, created_at, updated_at)
+        SELECT column1, column2, column3, 1, column4, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'), strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+        FROM (VALUES
+            ('red', 'Red', '#ef4444', 1),
+            ('orange', 'Orange', '#f97316', 2),
+            ('amber', 'Amber', '#f59e0b', 3),
+            ('yellow', 'Yellow', '#eab308', 4),
+            ('lime', 'Lime', '#84cc16', 5),
+            ('green', 'Green', '#22c55e', 6),
+            ('emerald', 'Emerald', '#10b981', 7),
+            ('teal', 'Teal', '#14b8a6', 8),
+            ('cyan', 'Cyan', '#06b6d4', 9),
+            ('sky', 'Sky', '#0ea5e9', 10),
+            ('blue', 'Blue', '#3b82f6', 11),
+            ('indigo', 'Indigo', '#6366f1', 12),
+            ('violet', 'Violet', '#8b5cf6', 13),
+            ('purple', 'Purple', '#a855f7', 14),
+            ('fuchsia', 'Fuchsia', '#d946ef', 15),
+            ('pink', 'Pink', '#ec4899', 16)
+        );",
+    )?;
+
+    // Name every offending row so the user can find and fix them; the
+    // database stays at the previous version until they do.
+    let unknown: Vec<String> = tx
+        .prepare(&format!(
+            "SELECT source || ' id ' || id || ' (playlist ' || playlist_id || '): ' || color FROM (
+                SELECT 'video_folder_assignments' AS source, x.id, x.playlist_id, {0} AS color
+                    FROM video_folder_assignments x
+                UNION ALL SELECT 'stuck_folders', x.id, x.playlist_id, {0} FROM stuck_folders x
+                UNION ALL SELECT 'folder_metadata', x.id, x.playlist_id, {0} FROM folder_metadata x
+             )
+             WHERE color NOT IN (SELECT color_key FROM folders)
+             ORDER BY color, source, id",
+            NORMALIZED_FOLDER_COLOR
+        ))?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    if !unknown.is_empty() {
+        return Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+            Some(format!("unknown folder colors: {}", unknown.join(", "))),
+        ));
+    }
+
+    tx.execute_batch(&format!(
+        "CREATE TABLE video_folder_assignments_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            playlist_id INTEGER NOT NULL,
+            item_id INTEGER NOT NULL,
+            folder_color TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
+            FOREIGN KEY (item_id) REFERENCES playlist_items(id) ON DELETE CASCADE,
+            FOREIGN KEY (folder_color) REFERENCES folders(color_key) ON UPDATE CASCADE ON DELETE CASCADE,
+            UNIQUE(playlist_id, item_id, folder_color)
+        );
+        INSERT OR IGNORE INTO video_folder_assignments_new (id, playlist_id, item_id, folder_color, created_at)
+            SELECT id, playlist_id, item_id, {0}, created_at FROM video_folder_assignments x ORDER BY id;
+        DROP TABLE video_folder_assignments;
+        ALTER TABLE video_folder_assignments_new RENAME TO video_folder_assignments;
+        CREATE INDEX idx_folder_assignments_playlist_color ON video_folder_assignments(playlist_id, folder_color);
+        CREATE INDEX idx_folder_assignments_item ON video_folder_assignments(item_id);
+        CREATE INDEX idx_folder_assignments_color ON video_folder_assignments(folder_color);
+
+        CREATE TABLE stuck_folders_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            playlist_id INTEGER NOT NULL,
+            folder_color TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
+            FOREIGN KEY (folder_color) REFERENCES folders(color_key) ON UPDATE CASCADE ON DELETE CASCADE,
+            UNIQUE(playlist_id, folder_color)
+        );
+        INSERT OR IGNORE INTO stuck_folders_new (id, playlist_id, folder_color, created_at)
+            SELECT id, playlist_id, {0}, created_at FROM stuck_folders x ORDER BY id;
+        DROP TABLE stuck_folders;
+        ALTER TABLE stuck_folders_new RENAME TO stuck_folders;
+        CREATE INDEX idx_stuck_folders_playlist_color ON stuck_folders(playlist_id, folder_color);
+
+        CREATE TABLE folder_metadata_new (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            playlist_id INTEGER NOT NULL,
+            folder_color TEXT NOT NULL,
+            custom_name TEXT,
+            description TEXT,
+            custom_ascii TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
+            FOREIGN KEY (folder_color) REFERENCES folders(color_key) ON UPDATE CASCADE ON DELETE CASCADE,
+            UNIQUE(playlist_id, folder_color)
+        );
+        INSERT OR IGNORE INTO folder_metadata_new (id, playlist_id, folder_color, custom_name, description, custom_ascii, created_at, updated_at)
+            SELECT id, playlist_id, {0}, custom_name, description, custom_ascii, created_at, updated_at
+            FROM folder_metadata x ORDER BY id;
+        DROP TABLE folder_metadata;
+        ALTER TABLE folder_metadata_new RENAME TO folder_metadata;
+        CREATE INDEX idx_folder_metadata_playlist_color ON folder_metadata(playlist_id, folder_color);
+
+        DELETE FROM library_search WHERE kind = 'folder';
+        INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            SELECT id * 4 + 3, custom_name, NULL, description, 'folder', id, playlist_id, folder_color FROM folder_metadata;
+
+        CREATE TRIGGER library_search_folder_insert AFTER INSERT ON folder_metadata BEGIN
+            INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            VALUES (new.id * 4 + 3, new.custom_name, NULL, new.description, 'folder', new.id, new.playlist_id, new.folder_color);
+        END;
+        CREATE TRIGGER library_search_folder_update AFTER UPDATE OF custom_name, description, playlist_id, folder_color ON folder_metadata BEGIN
+            DELETE FROM library_search WHERE rowid = old.id * 4 + 3;
+            INSERT INTO library_search (rowid, title, author, description, kind, source_id, playlist_id, folder_color)
+            VALUES (new.id * 4 + 3, new.custom_name, NULL, new.description, 'folder', new.id, new.playlist_id, new.folder_color);
+        END;
+        CREATE TRIGGER library_search_folder_delete AFTER DELETE ON folder_metadata BEGIN
+            DELETE FROM library_search WHERE rowid = old.id * 4 + 3;
+        END;",
+        NORMALIZED_FOLDER_COLOR
+    ))
+}
+
+// Stuck folders become pinned ones in a per-playlist visibility table, which
+// also tracks whether a folder is hidden or collapsed and where it sorts.
+// Folders without a row use the defaults.
+fn add_folder_visibility(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE folder_visibility (
+            playlist_id INTEGER NOT NULL,
+            folder_color TEXT NOT NULL,
+            pinned INTEGER NOT NULL DEFAULT 0,
+            hidden INTEGER NOT NULL DEFAULT 0,
+            collapsed INTEGER NOT NULL DEFAULT 0,
+            sort_order INTEGER,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (playlist_id, folder_color),
+            FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
+            FOREIGN KEY (folder_color) REFERENCES folders(color_key) ON UPDATE CASCADE ON DELETE CASCADE
+        );
+        CREATE INDEX idx_folder_visibility_color ON folder_visibility(folder_color);
+
+        INSERT INTO folder_visibility (playlist_id, folder_color, pinned, updated_at)
+            SELECT playlist_id, folder_color, 1, created_at FROM stuck_folders;
+        DROP TABLE stuck_folders;",
+    )
+}
+
+fn add_global_folders(tx: &Transaction) -> rusqlite::Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE global_folders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            description TEXT,
+            folder_color TEXT REFERENCES folders(color_key) ON UPDATE CASCADE ON DELETE SET NULL,
+            position INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE global_folder_items (
+            global_folder_id INTEGER NOT NULL REFERENCES global_folders(id) ON DELETE CASCADE,
+            item_id INTEGER NOT NULL REFERENCES playlist_items(id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            added_at TEXT NOT NULL,
+            PRIMARY KEY (global_folder_id, item_id)
+        );
+        CREATE INDEX idx_global_folder_items_item ON global_folder_items(item_id);",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // What the old ad-hoc init_schema created before any of the optional
+    // columns existed. Fixtures add whichever of those columns they need.
+    const LEGACY_SCHEMA: &str = "
+        CREATE TABLE playlists (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            description TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE TABLE playlist_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            playlist_id INTEGER NOT NULL,
+            video_url TEXT NOT NULL,
+            video_id TEXT NOT NULL,
+            title TEXT,
+            thumbnail_url TEXT,
+            position INTEGER NOT NULL,
+            added_at TEXT NOT NULL,
+            FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE
+        );
+        CREATE TABLE video_folder_assignments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            playlist_id INTEGER NOT NULL,
+            item_id INTEGER NOT NULL,
+            folder_color TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
+            FOREIGN KEY (item_id) REFERENCES playlist_items(id) ON DELETE CASCADE,
+            UNIQUE(playlist_id, item_id, folder_color)
+        );
+        CREATE TABLE watch_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            video_url TEXT NOT NULL,
+            video_id TEXT NOT NULL,
+            title TEXT,
+            thumbnail_url TEXT,
+            watched_at TEXT NOT NULL
+        );
+        CREATE TABLE video_progress (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            video_id TEXT NOT NULL UNIQUE,
+            video_url TEXT NOT NULL,
+            duration REAL,
+            last_progress REAL NOT NULL DEFAULT 0,
+            progress_percentage REAL NOT NULL DEFAULT 0,
+            last_updated TEXT NOT NULL
+        );
+        CREATE TABLE stuck_folders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            playlist_id INTEGER NOT NULL,
+            folder_color TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
+            UNIQUE(playlist_id, folder_color)
+        );
+        CREATE TABLE folder_metadata (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            playlist_id INTEGER NOT NULL,
+            folder_color TEXT NOT NULL,
+            custom_name TEXT,
+            description TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (playlist_id) REFERENCES playlists(id) ON DELETE CASCADE,
+            UNIQUE(playlist_id, folder_color)
+        );";
+
+    // (name, table, definition) for every column pre-versioning builds may or
+    // may not have added. custom_ascii arrived on two tables at once.
+    const OPTIONAL_COLUMNS: &[(&str, &[&str], &str)] = &[
+        (
+            "is_local",
+            &["playlist_items"],
+            "INTEGER NOT NULL DEFAULT 0",
+        ),
+        ("author", &["playlist_items"], "TEXT"),
+        ("view_count", &["playlist_items"], "TEXT"),
+        ("published_at", &["playlist_items"], "TEXT"),
+        (
+            "has_fully_watched",
+            &["video_progress"],
+            "INTEGER NOT NULL DEFAULT 0",
+        ),
+        ("custom_ascii", &["playlists", "folder_metadata"], "TEXT"),
+        ("custom_thumbnail_url", &["playlists"], "TEXT"),
+    ];
+
+    const ALL_COLUMNS: u32 = (1 << 7) - 1;
+
+    // playlist_id, folder_color, custom_name, description, custom_ascii
+    type MetadataRow = (i64, String, Option<String>, Option<String>, Option<String>);
+
+    struct Fixture {
+        columns: u32,
+        positions: [i64; 4],
+    }
+
+    impl Fixture {
+        fn has(&self, column: &str) -> bool {
+            OPTIONAL_COLUMNS
+                .iter()
+                .position(|(name, _, _)| *name == column)
+                .is_some_and(|i| self.columns & (1 << i) != 0)
+        }
+
+        // Build an unversioned database holding two playlists, items with the
+        // given positions in the first one, and folder rows whose colors are
+        // stored the way old frontends wrote them.
+        fn build(&self) -> Connection {
+            let conn = Connection::open_in_memory().unwrap();
+            conn.pragma_update(None, "foreign_keys", true).unwrap();
+            conn.execute_batch(LEGACY_SCHEMA).unwrap();
+            for (i, (column, tables, definition)) in OPTIONAL_COLUMNS.iter().enumerate() {
+                if self.columns & (1 << i) != 0 {
+                    for table in tables.iter() {
+                        conn.execute(
+                            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+                            [],
+                        )
+                        .unwrap();
+                    }
+                }
+            }
+
+            let now = "2024-01-01T00:00:00Z";
+            conn.execute(
+                "INSERT INTO playlists (id, name, description, created_at, updated_at) VALUES
+                    (1, 'Mix', 'first', ?1, ?1), (2, 'Other', NULL, ?1, ?1)",
+                params![now],
+            )
+            .unwrap();
+            let items = [
+                (1, 1, "a", "Alpha"),
+                (2, 1, "b", "Bravo"),
+                (3, 1, "c", "Charlie"),
+                (4, 1, "d", "Delta"),
+                (5, 2, "a", "Alpha again"),
+            ];
+            for (id, playlist_id, video_id, title) in items {
+                let position = match playlist_id {
+                    1 => self.positions[id as usize - 1],
+                    _ => 0,
+                };
+                conn.execute(
+                    "INSERT INTO playlist_items (id, playlist_id, video_url, video_id, title, position, added_at)
+                     VALUES (?1, ?2, 'https://youtu.be/' || ?3, ?3, ?4, ?5, ?6)",
+                    params![id, playlist_id, video_id, title, position, now],
+                )
+                .unwrap();
+            }
+            conn.execute_batch(
+                "INSERT INTO video_folder_assignments (playlist_id, item_id, folder_color, created_at) VALUES
+                    (1, 1, 'red', '1'),
+                    (1, 2, '#3B82F6', '2'),
+                    (1, 2, 'BLUE', '3'),
+                    (1, 3, ' Green ', '4'),
+                    (2, 5, 'Pink', '5');
+                INSERT INTO stuck_folders (playlist_id, folder_color, created_at) VALUES
+                    (1, 'RED', '1'),
+                    (2, '#ec4899', '2');
+                INSERT INTO folder_metadata (playlist_id, folder_color, custom_name, description, created_at, updated_at) VALUES
+                    (1, '#EF4444', 'Reds', 'warm', '1', '1'),
+                    (2, 'pink', 'Pinks', NULL, '2', '2');
+                INSERT INTO video_progress (video_id, video_url, duration, last_progress, progress_percentage, last_updated)
+                    VALUES ('a', 'https://youtu.be/a', 100, 50, 50, '1');",
+            )
+            .unwrap();
+
+            if self.has("is_local") {
+                conn.execute("UPDATE playlist_items SET is_local = 1 WHERE id = 4", [])
+                    .unwrap();
+            }
+            if self.has("author") {
+                conn.execute("UPDATE playlist_items SET author = 'Ann' WHERE id = 1", [])
+                    .unwrap();
+            }
+            if self.has("view_count") {
+                conn.execute(
+                    "UPDATE playlist_items SET view_count = '42' WHERE id = 2",
+                    [],
+                )
+                .unwrap();
+            }
+            if self.has("published_at") {
+                conn.execute(
+                    "UPDATE playlist_items SET published_at = '2020-02-02' WHERE id = 3",
+                    [],
+                )
+                .unwrap();
+            }
+            if self.has("has_fully_watched") {
+                conn.execute("UPDATE video_progress SET has_fully_watched = 1", [])
+                    .unwrap();
+            }
+            if self.has("custom_ascii") {
+                conn.execute_batch(
+                    "UPDATE playlists SET custom_ascii = '(^_^)' WHERE id = 1;
+                    UPDATE folder_metadata SET custom_ascii = '<3' WHERE playlist_id = 2;",
+                )
+                .unwrap();
+            }
+            if self.has("custom_thumbnail_url") {
+                conn.execute(
+                    "UPDATE playlists SET custom_thumbnail_url = 'thumb.png' WHERE id = 1",
+                    [],
+                )
+                .unwrap();
+            }
+            conn
+        }
+    }
+
+    fn rows<T: rusqlite::types::FromSql>(conn: &Connection, sql: &str) -> Vec<T> {
+        conn.prepare(sql)
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap()
+    }
+
+    fn assert_fully_migrated(conn: &Connection) {
+        assert_eq!(user_version(conn).unwrap(), latest_version());
+
+        let applied: Vec<(i64, String)> = conn
+            .prepare("SELECT version, description FROM schema_migrations ORDER BY version")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        let expected: Vec<(i64, String)> = MIGRATIONS
+            .iter()
+            .map(|m| (m.version, m.description.to_string()))
+            .collect();
+        assert_eq!(applied, expected);
+
+        let violations: Vec<String> = rows(conn, "SELECT \"table\" FROM pragma_foreign_key_check");
+        assert!(violations.is_empty(), "{:?}", violations);
+        let foreign_keys: bool = conn
+            .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+            .unwrap();
+        assert!(foreign_keys);
+    }
+
+    #[test]
+    fn upgrades_every_legacy_column_subset() {
+        for columns in 0..=ALL_COLUMNS {
+            let fixture = Fixture {
+                columns,
+                positions: [0, 1, 2, 3],
+            };
+            let mut conn = fixture.build();
+            run(&mut conn).unwrap_or_else(|e| panic!("columns {:#09b}: {}", columns, e));
+            assert_fully_migrated(&conn);
+
+            let items: Vec<(i64, i64, i64, String, String)> = conn
+                .prepare(
+                    "SELECT pi.id, pi.playlist_id, pi.position, v.video_url, v.title
+                     FROM playlist_items pi JOIN videos v ON v.video_id = pi.video_id
+                     ORDER BY pi.id",
+                )
+                .unwrap()
+                .query_map([], |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                })
+                .unwrap()
+                .collect::<rusqlite::Result<_>>()
+                .unwrap();
+            assert_eq!(items.len(), 5);
+            assert_eq!(
+                items.iter().map(|i| (i.0, i.1, i.2)).collect::<Vec<_>>(),
+                vec![(1, 1, 1), (2, 1, 2), (3, 1, 3), (4, 1, 4), (5, 2, 1)]
+            );
+            assert_eq!(items[0].3, "https://youtu.be/a");
+            assert_eq!(items[1].4, "Bravo");
+
+            let video = |column: &str, video_id: &str| -> Option<String> {
+                conn.query_row(
+                    &format!(
+                        "SELECT CAST({} AS TEXT) FROM videos WHERE video_id = ?1",
+                        column
+                    ),
+                    params![video_id],
+                    |row| row.get(0),
+                )
+                .unwrap()
+            };
+            let expect = |column: &str, value: &str| fixture.has(column).then(|| value.to_string());
+            assert_eq!(
+                video("is_local", "d").as_deref(),
+                Some(if fixture.has("is_local") { "1" } else { "0" })
+            );
+            assert_eq!(video("author", "a"), expect("author", "Ann"));
+            assert_eq!(video("view_count", "b"), expect("view_count", "42"));
+            assert_eq!(
+                video("published_at", "c"),
+                expect("published_at", "2020-02-02")
+            );
+
+            let fully_watched: bool = conn
+                .query_row(
+                    "SELECT has_fully_watched FROM video_progress WHERE video_id = 'a'",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(fully_watched, fixture.has("has_fully_watched"));
+
+            let playlist: (String, Option<String>, Option<String>, Option<String>) = conn
+                .query_row(
+                    "SELECT name, description, custom_ascii, custom_thumbnail_url FROM playlists WHERE id = 1",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+                )
+                .unwrap();
+            assert_eq!(
+                playlist,
+                (
+                    "Mix".to_string(),
+                    Some("first".to_string()),
+                    expect("custom_ascii", "(^_^)"),
+                    expect("custom_thumbnail_url", "thumb.png"),
+                )
+            );
+            let folder_ascii: Option<String> = conn
+                .query_row(
+                    "SELECT custom_ascii FROM folder_metadata WHERE playlist_id = 2",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(folder_ascii, expect("custom_ascii", "<3"));
+
+            let assignments: Vec<i64> = rows(
+                &conn,
+                "SELECT item_id FROM video_folder_assignments ORDER BY item_id",
+            );
+            assert_eq!(assignments, vec![1, 2, 3, 5]);
+        }
+    }
+
+    #[test]
+    fn renumbers_duplicated_positions() {
+        let fixture = Fixture {
+            columns: ALL_COLUMNS,
+            positions: [2, 1, 1, 3],
+        };
+        let mut conn = fixture.build();
+        run(&mut conn).unwrap();
+        assert_fully_migrated(&conn);
+
+        // Ties keep their id order
+        let order: Vec<i64> = rows(
+            &conn,
+            "SELECT id FROM playlist_items WHERE playlist_id = 1 ORDER BY position",
+        );
+        assert_eq!(order, vec![2, 3, 1, 4]);
+        let positions: Vec<i64> = rows(
+            &conn,
+            "SELECT position FROM playlist_items WHERE playlist_id = 1 ORDER BY position",
+        );
+        assert_eq!(positions, vec![1, 2, 3, 4]);
+
+        let duplicate = conn.execute("UPDATE playlist_items SET position = 1 WHERE id = 3", []);
+        assert!(duplicate.is_err());
+    }
+
+    #[test]
+    fn normalizes_hex_and_mixed_case_colors() {
+        let fixture = Fixture {
+            columns: ALL_COLUMNS,
+            positions: [0, 1, 2, 3],
+        };
+        let mut conn = fixture.build();
+        run(&mut conn).unwrap();
+        assert_fully_migrated(&conn);
+
+        let assignments: Vec<(i64, i64, String)> = conn
+            .prepare("SELECT playlist_id, item_id, folder_color FROM video_folder_assignments ORDER BY item_id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        // '#3B82F6' and 'BLUE' collapse into one assignment
+        assert_eq!(
+            assignments,
+            vec![
+                (1, 1, "red".to_string()),
+                (1, 2, "blue".to_string()),
+                (1, 3, "green".to_string()),
+                (2, 5, "pink".to_string()),
+            ]
+        );
+
+        let pinned: Vec<(i64, String, bool)> = conn
+            .prepare("SELECT playlist_id, folder_color, pinned FROM folder_visibility ORDER BY playlist_id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            pinned,
+            vec![(1, "red".to_string(), true), (2, "pink".to_string(), true)]
+        );
+
+        let metadata: Vec<MetadataRow> = conn
+            .prepare(
+                "SELECT playlist_id, folder_color, custom_name, description, custom_ascii
+                 FROM folder_metadata ORDER BY playlist_id",
+            )
+            .unwrap()
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            })
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            metadata,
+            vec![
+                (
+                    1,
+                    "red".to_string(),
+                    Some("Reds".to_string()),
+                    Some("warm".to_string()),
+                    None
+                ),
+                (
+                    2,
+                    "pink".to_string(),
+                    Some("Pinks".to_string()),
+                    None,
+                    Some("<3".to_string())
+                ),
+            ]
+        );
+
+        // Folder metadata stays searchable under its normalized color
+        let search: Vec<String> = rows(
+            &conn,
+            "SELECT folder_color FROM library_search WHERE kind = 'folder' AND library_search MATCH 'warm'",
+        );
+        assert_eq!(search, vec!["red".to_string()]);
+    }
+
+    #[test]
+    fn unknown_color_stops_at_the_previous_version() {
+        let fixture = Fixture {
+            columns: ALL_COLUMNS,
+            positions: [0, 1, 2, 3],
+        };
+        let mut conn = fixture.build();
+        conn.execute_batch(
+            "INSERT INTO video_folder_assignments (id, playlist_id, item_id, folder_color, created_at)
+                VALUES (9, 1, 4, 'Magenta', '9');
+            INSERT INTO folder_metadata (id, playlist_id, folder_color, custom_name, description, created_at, updated_at)
+                VALUES (7, 2, 'magenta', 'M', NULL, '7', '7');",
+        )
+        .unwrap();
+
+        let err = run(&mut conn).unwrap_err();
+        match &err {
+            MigrationError::Failed { version, .. } => assert_eq!(*version, 19),
+            other => panic!("unexpected error: {}", other),
+        }
+        let message = err.to_string();
+        assert!(
+            message.contains("video_folder_assignments id 9 (playlist 1): magenta"),
+            "{}",
+            message
+        );
+        assert!(
+            message.contains("folder_metadata id 7 (playlist 2): magenta"),
+            "{}",
+            message
+        );
+
+        // Migrations 1-18 stay applied and recorded; 19 left nothing behind
+        assert_eq!(user_version(&conn).unwrap(), 18);
+        let applied: Vec<i64> = rows(
+            &conn,
+            "SELECT version FROM schema_migrations ORDER BY version",
+        );
+        assert_eq!(applied, (1..=18).collect::<Vec<_>>());
+        let folders: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE name = 'folders'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(folders, 0);
+        let stored: Vec<String> = rows(
+            &conn,
+            "SELECT folder_color FROM video_folder_assignments WHERE id = 9",
+        );
+        assert_eq!(stored, vec!["Magenta".to_string()]);
+        let positions: Vec<i64> = rows(
+            &conn,
+            "SELECT position FROM playlist_items WHERE playlist_id = 1 ORDER BY id",
+        );
+        assert_eq!(positions, vec![1, 2, 3, 4]);
+        let foreign_keys: bool = conn
+            .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+            .unwrap();
+        assert!(foreign_keys);
+
+        // Fixing the rows lets the next launch finish the upgrade
+        conn.execute_batch(
+            "UPDATE video_folder_assignments SET folder_color = 'fuchsia' WHERE id = 9;
+            DELETE FROM folder_metadata WHERE id = 7;",
+        )
+        .unwrap();
+        run(&mut conn).unwrap();
+        assert_fully_migrated(&conn);
+    }
+
+    #[test]
+    fn refuses_newer_schema() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE playlists (id INTEGER PRIMARY KEY);")
+            .unwrap();
+        conn.pragma_update(None, "user_version", latest_version() + 1)
+            .unwrap();
+
+        match run(&mut conn) {
+            Err(MigrationError::NewerSchema { found, supported }) => {
+                assert_eq!(found, latest_version() + 1);
+                assert_eq!(supported, latest_version());
+            }
+            other => panic!("expected NewerSchema, got {:?}", other),
+        }
+        assert_eq!(user_version(&conn).unwrap(), latest_version() + 1);
+        let tables: Vec<String> =
+            rows(&conn, "SELECT name FROM sqlite_master WHERE type = 'table'");
+        assert_eq!(tables, vec!["playlists".to_string()]);
+    }
+
+    #[test]
+    fn fresh_database_reaches_latest_version() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run(&mut conn).unwrap();
+        assert_eq!(user_version(&conn).unwrap(), latest_version());
+        // A second run is a no-op
+        run(&mut conn).unwrap();
+        let applied: i64 = conn
+            .query_row("SELECT COUNT(*) FROM schema_migrations", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(applied, latest_version());
+    }
+}
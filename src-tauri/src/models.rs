@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Playlist {
@@ -9,6 +10,179 @@ pub struct Playlist {
     pub custom_thumbnail_url: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Set for smart playlists, whose items are computed from these rules
+    #[serde(default)]
+    pub smart_rules: Option<SmartRules>,
+    /// The group the playlist is in; `None` at the top level
+    #[serde(default)]
+    pub group_id: Option<i64>,
+    /// Order within the group; lower comes first
+    #[serde(default)]
+    pub sort_order: i32,
+}
+
+/// A folder of playlists. Groups nest to any depth.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaylistGroup {
+    pub id: i64,
+    /// `None` for top-level groups
+    pub parent_id: Option<i64>,
+    pub name: String,
+    /// Order among the groups sharing a parent
+    pub position: i32,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Live playlists in this group and every group nested in it
+    #[serde(default)]
+    pub playlist_count: i32,
+    /// Live items in those playlists, not counting smart playlists
+    #[serde(default)]
+    pub item_count: i32,
+}
+
+/// A tab: a named selection of playlists, shown in the order they were added.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tab {
+    pub id: i64,
+    pub name: String,
+    pub position: i32,
+    /// Live playlists in the tab, in order
+    pub playlist_ids: Vec<i64>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A named selection of tabs. No tabs means all of them.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TabPreset {
+    pub id: i64,
+    pub name: String,
+    pub position: i32,
+    pub tab_ids: Vec<i64>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// What importing tabs and presets from localStorage created.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TabImportReport {
+    pub tabs: Vec<Tab>,
+    pub presets: Vec<TabPreset>,
+    /// New id of each imported tab and preset, by its old localStorage id
+    pub tab_ids: HashMap<String, i64>,
+    pub preset_ids: HashMap<String, i64>,
+    /// Playlist references that were dropped because the playlist is gone
+    pub skipped_playlists: usize,
+}
+
+/// A folder items can be sorted into. The 16 builtin folders are the color
+/// palette; users can add their own after them.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Folder {
+    pub id: i64,
+    /// What assignments, stuck folders and folder metadata refer to it by
+    pub color_key: String,
+    pub name: String,
+    /// `#rrggbb`
+    pub hex: String,
+    pub is_builtin: bool,
+    pub position: i32,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// How a folder shows up in one playlist. Pinned folders are the ones the
+/// playlists page calls stuck.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct FolderVisibility {
+    pub pinned: bool,
+    pub hidden: bool,
+    pub collapsed: bool,
+    /// Place among the playlist's folders; unordered ones follow in folder order
+    pub sort_order: Option<i32>,
+}
+
+/// A folder's custom name, description and custom ASCII art in one playlist.
+pub type FolderMetadata = (Option<String>, Option<String>, Option<String>);
+
+/// Everything about a folder within one playlist.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaylistFolderState {
+    pub folder_color: String,
+    pub name: String,
+    pub hex: String,
+    /// Live items assigned to the folder
+    pub video_count: i64,
+    pub custom_name: Option<String>,
+    pub description: Option<String>,
+    pub custom_ascii: Option<String>,
+    #[serde(flatten)]
+    pub visibility: FolderVisibility,
+}
+
+/// A folder of items from any playlist, e.g. "Watch Later". Counts leave out
+/// trashed items and items in trashed playlists.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GlobalFolder {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    /// Folder whose color it's shown in, if any
+    pub folder_color: Option<String>,
+    pub position: i32,
+    pub item_count: i64,
+    /// How many playlists its items come from
+    pub playlist_count: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// What a smart playlist contains. Items from every regular playlist are
+/// matched against the rules; each video appears once.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SmartRules {
+    /// Whether an item must match every rule, or just one of them
+    #[serde(default = "default_match_all")]
+    pub match_all: bool,
+    pub rules: Vec<SmartRule>,
+}
+
+fn default_match_all() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SmartRule {
+    InPlaylist {
+        playlist_id: i64,
+    },
+    InFolder {
+        playlist_id: i64,
+        folder_color: String,
+    },
+    /// Case-insensitive exact match
+    Author {
+        author: String,
+    },
+    /// Case-insensitive substring match
+    TitleContains {
+        text: String,
+    },
+    FullyWatched {
+        watched: bool,
+    },
+    AddedWithinDays {
+        days: u32,
+    },
+    /// Inclusive range of watch progress in percent
+    ProgressBetween {
+        min: f64,
+        max: f64,
+    },
+    IsLocal {
+        local: bool,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -27,6 +201,94 @@ pub struct PlaylistItem {
     pub published_at: Option<String>,
 }
 
+/// Whether a video has been watched, going by its saved progress. Watched
+/// means fully watched once or at least 85% through.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchState {
+    Unwatched,
+    InProgress,
+    Watched,
+}
+
+/// One page of a playlist's items. Filters left `None` match everything.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ItemPageQuery {
+    /// Matching items to skip; ignored when `cursor` is given
+    #[serde(default)]
+    pub offset: u32,
+    /// The previous page's `next_cursor`
+    pub cursor: Option<i32>,
+    /// Items per page, 100 when omitted and at most 1000
+    pub limit: Option<u32>,
+    pub folder_color: Option<String>,
+    pub watch_state: Option<WatchState>,
+    pub is_local: Option<bool>,
+    /// `PlaylistItem` fields to return, besides `id`; all of them when omitted
+    pub fields: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ItemPage {
+    /// `PlaylistItem`s, cut down to the requested fields
+    pub items: Vec<serde_json::Map<String, serde_json::Value>>,
+    /// Items matching the filters across every page
+    pub total: i64,
+    /// How many matching items come before this page
+    pub offset: i64,
+    /// Pass as `cursor` to get the next page; `None` on the last one
+    pub next_cursor: Option<i32>,
+}
+
+/// A video's metadata, stored once and shared by every playlist item that
+/// references it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Video {
+    pub video_id: String,
+    pub video_url: String,
+    pub title: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub author: Option<String>,
+    pub view_count: Option<String>,
+    pub published_at: Option<String>,
+    pub is_local: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A pinned video. Normal pins expire 24 hours after they were pinned;
+/// priority pins stay until unpinned.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Pin {
+    pub video: Video,
+    pub is_priority: bool,
+    pub pinned_at: String,
+    /// `None` for priority pins
+    pub expires_at: Option<String>,
+}
+
+/// A video sent along with a pin or like, stored in `videos` if it isn't
+/// there yet.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VideoRequest {
+    pub video_id: String,
+    pub video_url: String,
+    pub title: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub author: Option<String>,
+    pub view_count: Option<String>,
+    pub published_at: Option<String>,
+    #[serde(default)]
+    pub is_local: bool,
+}
+
+/// A liked video.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Like {
+    pub video: Video,
+    pub liked_at: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreatePlaylistRequest {
     pub name: String,
@@ -54,6 +316,36 @@ pub struct AddVideoToPlaylistRequest {
     pub published_at: Option<String>,
 }
 
+/// One video in an `add_videos_to_playlist` batch.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NewPlaylistItem {
+    pub video_url: String,
+    pub video_id: String,
+    pub title: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub author: Option<String>,
+    pub view_count: Option<String>,
+    pub published_at: Option<String>,
+    #[serde(default)]
+    pub is_local: bool,
+    /// Folders to assign the new item to
+    #[serde(default)]
+    pub folder_colors: Vec<String>,
+}
+
+/// What happened to one item of an `add_videos_to_playlist` batch. Results
+/// come back in input order.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BulkAddResult {
+    pub index: usize,
+    pub video_id: String,
+    /// "added", "duplicate" or "invalid"
+    pub status: String,
+    pub item_id: Option<i64>,
+    pub position: Option<i32>,
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ReorderPlaylistItemRequest {
     pub playlist_id: i64,
@@ -108,4 +400,219 @@ pub struct PlaylistMetadata {
     pub count: i32,
     pub first_video: Option<PlaylistItem>,
     pub recent_video: Option<PlaylistItem>,
+    /// Group the playlist is in, `None` at the top level
+    #[serde(default)]
+    pub group_id: Option<i64>,
+    /// Live playlists in that group and every group nested in it
+    #[serde(default)]
+    pub group_playlist_count: i32,
+    /// Live items in those playlists, not counting smart playlists
+    #[serde(default)]
+    pub group_item_count: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchHit {
+    /// "item", "playlist" or "folder"
+    pub kind: String,
+    pub playlist_id: i64,
+    pub playlist_name: String,
+    /// Set for item hits
+    pub item: Option<PlaylistItem>,
+    /// Set for folder hits: the folder that matched
+    pub folder_color: Option<String>,
+    /// For item hits: every folder the item is assigned to
+    pub folder_colors: Vec<String>,
+    /// HTML-escaped title with matches wrapped in <mark></mark>
+    pub title_snippet: Option<String>,
+    /// HTML-escaped best-matching column with matches wrapped in <mark></mark>
+    pub snippet: Option<String>,
+    /// bm25 score; lower is a better match
+    pub rank: f64,
+}
+
+/// A playlist with everything needed to recreate it elsewhere, as stored in
+/// an export bundle.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaylistExport {
+    pub name: String,
+    pub description: Option<String>,
+    pub custom_ascii: Option<String>,
+    pub custom_thumbnail_url: Option<String>,
+    pub created_at: String,
+    /// Set for smart playlists, which are exported without items
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smart_rules: Option<SmartRules>,
+    /// Names of the playlists the smart rules refer to, keyed by their id in
+    /// the exporting library, so an import can point the rules at its own
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub rule_playlists: HashMap<i64, String>,
+    pub items: Vec<ExportedItem>,
+    #[serde(default)]
+    pub folders: Vec<ExportedFolder>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedItem {
+    pub video_id: String,
+    pub video_url: String,
+    pub title: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub author: Option<String>,
+    pub view_count: Option<String>,
+    pub published_at: Option<String>,
+    #[serde(default)]
+    pub is_local: bool,
+    pub position: i32,
+    pub added_at: String,
+    /// Colors of the folders this item is assigned to
+    #[serde(default)]
+    pub folders: Vec<String>,
+}
+
+/// Per-folder state that isn't implied by item assignments.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedFolder {
+    pub color: String,
+    pub custom_name: Option<String>,
+    pub description: Option<String>,
+    pub custom_ascii: Option<String>,
+    /// Pinned, under the name older bundles used
+    #[serde(default)]
+    pub stuck: bool,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default)]
+    pub collapsed: bool,
+    #[serde(default)]
+    pub sort_order: Option<i32>,
+}
+
+/// A like in a library bundle, with the video it's for.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedLike {
+    #[serde(flatten)]
+    pub video: VideoRequest,
+    pub liked_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportReport {
+    pub playlist_id: i64,
+    /// Name the playlist was created with; differs from `original_name` when
+    /// a playlist with that name already existed
+    pub name: String,
+    pub original_name: String,
+    pub imported: usize,
+    pub skipped: Vec<SkippedItem>,
+    /// Smart rules that couldn't be pointed at this library's playlists
+    #[serde(default)]
+    pub skipped_rules: Vec<SkippedRule>,
+    /// Folder colors this library has no folder for. Their assignments,
+    /// metadata and visibility settings weren't imported.
+    #[serde(default)]
+    pub skipped_folders: Vec<String>,
+}
+
+/// A smart rule left out of an import because it refers to a playlist or
+/// folder this library doesn't have.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkippedRule {
+    pub rule: SmartRule,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkippedItem {
+    pub position: i32,
+    pub video_id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub path: String,
+    /// "manual", "auto" or "pre-restore"
+    pub kind: String,
+    pub created_at: String,
+    pub size_bytes: u64,
+}
+
+/// Schedule and retention for automatic backups. Manual backups are never
+/// deleted automatically.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupSettings {
+    pub enabled: bool,
+    pub interval_hours: u32,
+    /// Keep at most this many automatic (and, separately, pre-restore) backups
+    pub keep_last: u32,
+    /// Also delete those older than this; the newest one is always kept
+    pub max_age_days: Option<u32>,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        BackupSettings {
+            enabled: true,
+            interval_hours: 24,
+            keep_last: 7,
+            max_age_days: Some(30),
+        }
+    }
+}
+
+/// An undoable operation recorded in the journal.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JournalEntry {
+    pub id: i64,
+    /// "trash_playlists", "trash_item", "unassign_folder", "reorder" or
+    /// "set_order"
+    pub kind: String,
+    /// Human-readable summary, e.g. "Delete playlist 'Mix'"
+    pub description: String,
+    /// True once undone; it can then be redone until a new operation is recorded
+    pub undone: bool,
+    pub created_at: String,
+}
+
+/// A deleted playlist or item waiting in the trash.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrashEntry {
+    pub id: i64,
+    /// "playlist" or "item"
+    pub kind: String,
+    pub playlist_id: i64,
+    pub playlist_name: String,
+    /// The trashed item, for "item" entries
+    pub item: Option<PlaylistItem>,
+    pub deleted_at: String,
+}
+
+/// A saved alternative ordering of a playlist.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaylistView {
+    pub id: i64,
+    pub playlist_id: i64,
+    pub name: String,
+    /// "title", "author", "published_at", "added_at", "view_count",
+    /// "duration" or "progress"
+    pub sort_by: String,
+    pub descending: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Where playback is in a playlist, and in what order it continues.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaybackQueue {
+    pub playlist_id: i64,
+    /// Item ids in play order
+    pub item_ids: Vec<i64>,
+    /// Index of the current item in `item_ids`; `None` when the playlist is empty
+    pub cursor: Option<usize>,
+    pub current_item: Option<PlaylistItem>,
+    pub shuffled: bool,
+    /// "off", "all" or "one"
+    pub repeat_mode: String,
 }
@@ -0,0 +1,241 @@
+use crate::database::{playlist_item_from_row, Database, ITEM_SELECT};
+use crate::error::{AppError, AppResult};
+use crate::models::{ItemPage, ItemPageQuery, PlaylistItem, WatchState};
+use rusqlite::params_from_iter;
+use rusqlite::types::Value;
+use std::collections::HashSet;
+
+const DEFAULT_PAGE_SIZE: u32 = 100;
+const MAX_PAGE_SIZE: u32 = 1000;
+
+// Progress that counts as watched, as in `get_watched_video_ids`
+const WATCHED_PERCENT: f64 = 85.0;
+
+// What a `PlaylistItem` serializes to, for validating projections
+const ITEM_FIELDS: &[&str] = &[
+    "id",
+    "playlist_id",
+    "video_url",
+    "video_id",
+    "title",
+    "thumbnail_url",
+    "position",
+    "added_at",
+    "is_local",
+    "author",
+    "view_count",
+    "published_at",
+];
+
+// The tables filters can refer to, aliased as in ITEM_SELECT
+const ITEM_FROM: &str = "FROM playlist_items pi
+     INNER JOIN videos v ON v.video_id = pi.video_id
+     LEFT JOIN video_progress vp ON vp.video_id = pi.video_id";
+
+fn watch_state_condition(state: WatchState) -> String {
+    match state {
+        WatchState::Unwatched => {
+            "COALESCE(vp.progress_percentage, 0) = 0 AND NOT COALESCE(vp.has_fully_watched, 0)"
+                .to_string()
+        }
+        WatchState::InProgress => format!(
+            "vp.progress_percentage > 0 AND vp.progress_percentage < {} AND NOT vp.has_fully_watched",
+            WATCHED_PERCENT
+        ),
+        WatchState::Watched => format!(
+            "(vp.progress_percentage >= {} OR vp.has_fully_watched)",
+            WATCHED_PERCENT
+        ),
+    }
+}
+
+fn validate_fields(fields: Option<&[String]>) -> AppResult<()> {
+    for field in fields.unwrap_or_default() {
+        if !ITEM_FIELDS.contains(&field.as_str()) {
+            return Err(AppError::validation(
+                "fields",
+                format!("unknown item field '{}'", field),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// `id` is always kept so the grid has something to key rows by
+fn project(
+    item: PlaylistItem,
+    fields: Option<&[String]>,
+) -> AppResult<serde_json::Map<String, serde_json::Value>> {
+    let mut map = match serde_json::to_value(item) {
+        Ok(serde_json::Value::Object(map)) => map,
+        Ok(_) => return Err(AppError::internal("playlist item is not an object")),
+        Err(e) => {
+            return Err(AppError::internal(format!(
+                "failed to serialize playlist item: {}",
+                e
+            )))
+        }
+    };
+    if let Some(fields) = fields {
+        map.retain(|key, _| key == "id" || fields.iter().any(|field| field == key));
+    }
+    Ok(map)
+}
+
+// Pages are ordered by position, so a cursor is the position of the last item
+// on the previous page; positions of live items in a playlist are unique.
+// Smart playlists are evaluated in full and paged in memory, since their
+// items come from every playlist.
+impl Database {
+    /// A page of a playlist's items, in position order, with the total
+    /// number matching the filters. Pass the page's `next_cursor` back as
+    /// `cursor` for the next one, or step through with `offset`.
+    pub fn get_playlist_items_page(
+        &self,
+        playlist_id: i64,
+        query: &ItemPageQuery,
+    ) -> AppResult<ItemPage> {
+        self.ensure_playlist_exists(playlist_id)?;
+        let fields = query.fields.as_deref();
+        validate_fields(fields)?;
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let (conditions, values) = self.item_filters(query)?;
+
+        let (items, total, offset) = match self.get_smart_rules(playlist_id)? {
+            Some(rules) => {
+                let mut items = self.evaluate_smart_rules(&rules)?;
+                if !conditions.is_empty() {
+                    let matching = self.matching_item_ids(&items, &conditions, values)?;
+                    items.retain(|item| matching.contains(&item.id));
+                }
+                let total = items.len();
+                let start = match query.cursor {
+                    Some(cursor) => items.iter().take_while(|i| i.position <= cursor).count(),
+                    None => (query.offset as usize).min(total),
+                };
+                let page: Vec<PlaylistItem> =
+                    items.into_iter().skip(start).take(limit as usize).collect();
+                (page, total as i64, start as i64)
+            }
+            None => self.regular_items_page(playlist_id, query, limit, conditions, values)?,
+        };
+
+        let next_cursor = match items.last() {
+            Some(last) if offset + (items.len() as i64) < total => Some(last.position),
+            _ => None,
+        };
+        let items = items
+            .into_iter()
+            .map(|item| project(item, fields))
+            .collect::<AppResult<Vec<_>>>()?;
+        Ok(ItemPage {
+            items,
+            total,
+            offset,
+            next_cursor,
+        })
+    }
+
+    // SQL conditions for the query's filters, with their parameters
+    fn item_filters(&self, query: &ItemPageQuery) -> AppResult<(Vec<String>, Vec<Value>)> {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+        if let Some(folder_color) = &query.folder_color {
+            conditions.push(
+                "EXISTS (SELECT 1 FROM video_folder_assignments vfa
+                         WHERE vfa.item_id = pi.id AND vfa.playlist_id = pi.playlist_id AND vfa.folder_color = ?)"
+                    .to_string(),
+            );
+            values.push(self.resolve_folder(folder_color)?.into());
+        }
+        if let Some(state) = query.watch_state {
+            conditions.push(format!("({})", watch_state_condition(state)));
+        }
+        if let Some(is_local) = query.is_local {
+            conditions.push("v.is_local = ?".to_string());
+            values.push(is_local.into());
+        }
+        Ok((conditions, values))
+    }
+
+    fn regular_items_page(
+        &self,
+        playlist_id: i64,
+        query: &ItemPageQuery,
+        limit: u32,
+        mut conditions: Vec<String>,
+        mut values: Vec<Value>,
+    ) -> AppResult<(Vec<PlaylistItem>, i64, i64)> {
+        conditions.insert(
+            0,
+            "pi.playlist_id = ? AND pi.deleted_at IS NULL".to_string(),
+        );
+        values.insert(0, playlist_id.into());
+        let filtered = conditions.join(" AND ");
+
+        let total: i64 = self.conn.query_row(
+            &format!("SELECT COUNT(*) {} WHERE {}", ITEM_FROM, filtered),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        )?;
+
+        // With a cursor, the offset reported is how many matching items it skips
+        let mut page_values = values.clone();
+        let (after_cursor, skip, offset) = match query.cursor {
+            Some(cursor) => {
+                let before: i64 = self.conn.query_row(
+                    &format!(
+                        "SELECT COUNT(*) {} WHERE {} AND pi.position <= ?",
+                        ITEM_FROM, filtered
+                    ),
+                    params_from_iter(values.iter().chain([&Value::from(cursor)])),
+                    |row| row.get(0),
+                )?;
+                page_values.push(cursor.into());
+                ("AND pi.position > ?", 0, before)
+            }
+            None => ("", query.offset as i64, query.offset as i64),
+        };
+        page_values.push(limit.into());
+        page_values.push(skip.into());
+
+        let mut stmt = self.conn.prepare(&format!(
+            "{}
+             LEFT JOIN video_progress vp ON vp.video_id = pi.video_id
+             WHERE {} {}
+             ORDER BY pi.position
+             LIMIT ? OFFSET ?",
+            ITEM_SELECT, filtered, after_cursor
+        ))?;
+        let items = stmt
+            .query_map(params_from_iter(page_values), playlist_item_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((items, total, offset.min(total)))
+    }
+
+    // Which of `items` pass the filters
+    fn matching_item_ids(
+        &self,
+        items: &[PlaylistItem],
+        conditions: &[String],
+        mut values: Vec<Value>,
+    ) -> AppResult<HashSet<i64>> {
+        let ids: Vec<i64> = items.iter().map(|item| item.id).collect();
+        let ids_json = serde_json::to_string(&ids)
+            .map_err(|e| AppError::internal(format!("failed to serialize item ids: {}", e)))?;
+        values.insert(0, ids_json.into());
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT pi.id {} WHERE pi.id IN (SELECT value FROM json_each(?)) AND {}",
+            ITEM_FROM,
+            conditions.join(" AND ")
+        ))?;
+        let matching = stmt
+            .query_map(params_from_iter(values), |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(matching)
+    }
+}
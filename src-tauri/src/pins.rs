@@ -0,0 +1,154 @@
+use crate::database::{video_from_row, Database, VIDEO_COLUMNS};
+use crate::error::AppResult;
+use crate::models::{Pin, VideoRequest};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::params;
+
+/// How long a normal pin lasts.
+const PIN_LIFETIME_HOURS: i64 = 24;
+
+// When a normal pin pinned at `pinned_at` expires. Unparseable timestamps
+// are treated as already expired.
+fn expiry(pinned_at: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(pinned_at)
+        .ok()
+        .map(|t| t.with_timezone(&Utc) + Duration::hours(PIN_LIFETIME_HOURS))
+}
+
+fn pin_from_row(row: &rusqlite::Row) -> rusqlite::Result<Pin> {
+    let is_priority: bool = row.get(10)?;
+    let pinned_at: String = row.get(11)?;
+    let expires_at = if is_priority {
+        None
+    } else {
+        expiry(&pinned_at).map(|at| at.to_rfc3339())
+    };
+
+    Ok(Pin {
+        video: video_from_row(row)?,
+        is_priority,
+        pinned_at,
+        expires_at,
+    })
+}
+
+// Pins are keyed by video, so a video pinned from one playlist shows as pinned
+// everywhere. Expired pins are left out of `get_pins` and deleted on the next
+// write.
+impl Database {
+    /// Live pins: priority pins first, most recently prioritized first, then
+    /// normal pins in the order they were pinned.
+    pub fn get_pins(&self) -> AppResult<Vec<Pin>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}, p.is_priority, p.pinned_at
+             FROM pins p
+             INNER JOIN videos v ON v.video_id = p.video_id
+             ORDER BY p.is_priority DESC, p.priority_order, p.pinned_at, p.video_id",
+            VIDEO_COLUMNS
+        ))?;
+
+        let now = Utc::now();
+        let pins = stmt
+            .query_map([], pin_from_row)?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|pin| pin.is_priority || expiry(&pin.pinned_at).is_some_and(|at| at > now))
+            .collect();
+
+        Ok(pins)
+    }
+
+    /// Pin a video as a normal pin, restarting its 24 hours. A priority pin
+    /// becomes a normal one.
+    pub fn pin_video(&self, video: &VideoRequest) -> AppResult<Pin> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.delete_expired_pins()?;
+        self.store_video_request(video)?;
+
+        self.conn.execute(
+            "INSERT INTO pins (video_id, is_priority, priority_order, pinned_at)
+             VALUES (?1, 0, NULL, ?2)
+             ON CONFLICT(video_id) DO UPDATE SET
+                is_priority = 0,
+                priority_order = NULL,
+                pinned_at = excluded.pinned_at",
+            params![video.video_id, Utc::now().to_rfc3339()],
+        )?;
+
+        tx.commit()?;
+        self.get_pin(&video.video_id)
+    }
+
+    /// Make a video a priority pin, ahead of the existing ones, or turn a
+    /// priority pin back into a normal pin with a fresh 24 hours.
+    pub fn set_priority_pin(&self, video: &VideoRequest, priority: bool) -> AppResult<Pin> {
+        if !priority {
+            return self.pin_video(video);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.delete_expired_pins()?;
+        self.store_video_request(video)?;
+
+        let order: i64 = self.conn.query_row(
+            "SELECT COALESCE(MIN(priority_order), 1) - 1 FROM pins WHERE is_priority = 1",
+            [],
+            |row| row.get(0),
+        )?;
+        self.conn.execute(
+            "INSERT INTO pins (video_id, is_priority, priority_order, pinned_at)
+             VALUES (?1, 1, ?2, ?3)
+             ON CONFLICT(video_id) DO UPDATE SET
+                is_priority = 1,
+                priority_order = excluded.priority_order",
+            params![video.video_id, order, Utc::now().to_rfc3339()],
+        )?;
+
+        tx.commit()?;
+        self.get_pin(&video.video_id)
+    }
+
+    pub fn unpin_video(&self, video_id: &str) -> AppResult<bool> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.delete_expired_pins()?;
+        let rows = self
+            .conn
+            .execute("DELETE FROM pins WHERE video_id = ?1", params![video_id])?;
+        tx.commit()?;
+        Ok(rows > 0)
+    }
+
+    fn get_pin(&self, video_id: &str) -> AppResult<Pin> {
+        let pin = self.conn.query_row(
+            &format!(
+                "SELECT {}, p.is_priority, p.pinned_at
+                 FROM pins p
+                 INNER JOIN videos v ON v.video_id = p.video_id
+                 WHERE p.video_id = ?1",
+                VIDEO_COLUMNS
+            ),
+            params![video_id],
+            pin_from_row,
+        )?;
+        Ok(pin)
+    }
+
+    fn delete_expired_pins(&self) -> AppResult<usize> {
+        let normal: Vec<(String, String)> = self
+            .conn
+            .prepare("SELECT video_id, pinned_at FROM pins WHERE is_priority = 0")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+
+        let now = Utc::now();
+        let mut deleted = 0;
+        for (video_id, pinned_at) in normal {
+            if expiry(&pinned_at).is_none_or(|at| at <= now) {
+                deleted += self
+                    .conn
+                    .execute("DELETE FROM pins WHERE video_id = ?1", params![video_id])?;
+            }
+        }
+        Ok(deleted)
+    }
+}
@@ -0,0 +1,358 @@
+use crate::database::Database;
+use crate::error::{AppError, AppResult};
+use crate::models::PlaybackQueue;
+use chrono::Utc;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepeatMode {
+    Off,
+    /// Wrap around at either end
+    All,
+    /// Stay on the current item when it finishes
+    One,
+}
+
+impl RepeatMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RepeatMode::Off => "off",
+            RepeatMode::All => "all",
+            RepeatMode::One => "one",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "off" => Some(RepeatMode::Off),
+            "all" => Some(RepeatMode::All),
+            "one" => Some(RepeatMode::One),
+            _ => None,
+        }
+    }
+}
+
+// What's stored for a playlist. The play order itself isn't: an unshuffled
+// queue follows the playlist, and a shuffle permutation is reconciled with the
+// live items every time it's read, so items added or removed since simply
+// show up at the end or drop out.
+struct QueueState {
+    current_item_id: Option<i64>,
+    shuffle_order: Option<Vec<i64>>,
+    repeat: RepeatMode,
+}
+
+impl Database {
+    fn queue_state(&self, playlist_id: i64) -> AppResult<QueueState> {
+        let row = match self.conn.query_row(
+            "SELECT current_item_id, shuffle_order, repeat_mode FROM playback_queues WHERE playlist_id = ?1",
+            params![playlist_id],
+            |row| {
+                Ok((
+                    row.get::<_, Option<i64>>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            },
+        ) {
+            Ok(row) => row,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Ok(QueueState {
+                    current_item_id: None,
+                    shuffle_order: None,
+                    repeat: RepeatMode::Off,
+                })
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let (current_item_id, shuffle_order, repeat) = row;
+        let shuffle_order = shuffle_order
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(|e| {
+                AppError::internal(format!(
+                    "playlist {} has an unreadable shuffle order: {}",
+                    playlist_id, e
+                ))
+            })?;
+
+        Ok(QueueState {
+            current_item_id,
+            shuffle_order,
+            repeat: RepeatMode::parse(&repeat).unwrap_or(RepeatMode::Off),
+        })
+    }
+
+    // Store `state`, with a shuffle order replaced by its reconciled form
+    fn save_queue_state(
+        &self,
+        playlist_id: i64,
+        mut state: QueueState,
+        item_ids: Vec<i64>,
+    ) -> AppResult<PlaybackQueue> {
+        if state.shuffle_order.is_some() {
+            state.shuffle_order = Some(item_ids);
+        }
+        let shuffle_order = state
+            .shuffle_order
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| AppError::internal(format!("failed to serialize shuffle order: {}", e)))?;
+
+        self.conn.execute(
+            "INSERT INTO playback_queues (playlist_id, current_item_id, shuffle_order, repeat_mode, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(playlist_id) DO UPDATE SET
+                current_item_id = excluded.current_item_id,
+                shuffle_order = excluded.shuffle_order,
+                repeat_mode = excluded.repeat_mode,
+                updated_at = excluded.updated_at",
+            params![
+                playlist_id,
+                state.current_item_id,
+                shuffle_order,
+                state.repeat.as_str(),
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+
+        self.resolve_queue(playlist_id, &state)
+    }
+
+    fn resolve_queue(&self, playlist_id: i64, state: &QueueState) -> AppResult<PlaybackQueue> {
+        let items = self.get_playlist_items(playlist_id)?;
+
+        let item_ids: Vec<i64> = match &state.shuffle_order {
+            Some(order) => {
+                let live: HashSet<i64> = items.iter().map(|item| item.id).collect();
+                let ordered: HashSet<i64> = order.iter().copied().collect();
+                order
+                    .iter()
+                    .copied()
+                    .filter(|id| live.contains(id))
+                    .chain(
+                        items
+                            .iter()
+                            .map(|item| item.id)
+                            .filter(|id| !ordered.contains(id)),
+                    )
+                    .collect()
+            }
+            None => items.iter().map(|item| item.id).collect(),
+        };
+
+        let cursor = match state.current_item_id {
+            _ if item_ids.is_empty() => None,
+            Some(id) => match item_ids.iter().position(|&other| other == id) {
+                Some(index) => Some(index),
+                None => Some(
+                    self.successor_of_removed(playlist_id, id, state, &item_ids)?
+                        .unwrap_or(0),
+                ),
+            },
+            None => Some(0),
+        };
+        let current_item = cursor.and_then(|index| {
+            items
+                .iter()
+                .find(|item| item.id == item_ids[index])
+                .cloned()
+        });
+
+        Ok(PlaybackQueue {
+            playlist_id,
+            item_ids,
+            cursor,
+            current_item,
+            shuffled: state.shuffle_order.is_some(),
+            repeat_mode: state.repeat.as_str().to_string(),
+        })
+    }
+
+    // Where the queue continues when its current item has been removed: the
+    // first surviving item that came after it.
+    fn successor_of_removed(
+        &self,
+        playlist_id: i64,
+        removed: i64,
+        state: &QueueState,
+        item_ids: &[i64],
+    ) -> AppResult<Option<usize>> {
+        if let Some(order) = &state.shuffle_order {
+            let successor = order
+                .iter()
+                .skip_while(|&&id| id != removed)
+                .skip(1)
+                .find_map(|id| item_ids.iter().position(|other| other == id));
+            return Ok(successor);
+        }
+
+        // A trashed item keeps its position, which the item after it now has
+        let position: i32 = match self.conn.query_row(
+            "SELECT position FROM playlist_items WHERE id = ?1 AND playlist_id = ?2",
+            params![removed, playlist_id],
+            |row| row.get(0),
+        ) {
+            Ok(position) => position,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let index = (position.max(1) - 1) as usize;
+        Ok((index < item_ids.len()).then_some(index))
+    }
+
+    /// Every queue that has been played, e.g. to refresh them all after an
+    /// item change, which can also reach smart playlists.
+    pub fn get_playback_queues(&self) -> AppResult<Vec<PlaybackQueue>> {
+        let playlist_ids: Vec<i64> = self
+            .conn
+            .prepare(
+                "SELECT q.playlist_id FROM playback_queues q
+                 INNER JOIN playlists p ON p.id = q.playlist_id
+                 WHERE p.deleted_at IS NULL
+                 ORDER BY q.playlist_id",
+            )?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+
+        playlist_ids
+            .into_iter()
+            .map(|playlist_id| {
+                let state = self.queue_state(playlist_id)?;
+                self.resolve_queue(playlist_id, &state)
+            })
+            .collect()
+    }
+
+    /// A playlist's queue. Playlists that were never played start at their
+    /// first item, unshuffled.
+    pub fn get_playback_queue(&self, playlist_id: i64) -> AppResult<PlaybackQueue> {
+        self.ensure_playlist_exists(playlist_id)?;
+        let state = self.queue_state(playlist_id)?;
+        self.resolve_queue(playlist_id, &state)
+    }
+
+    /// Advance to the next item. At the end, repeat mode "all" wraps around;
+    /// otherwise the queue stays on the last item. With `auto_advance` (the
+    /// current item finished playing) repeat mode "one" stays put.
+    pub fn queue_next(&self, playlist_id: i64, auto_advance: bool) -> AppResult<PlaybackQueue> {
+        self.step_queue(playlist_id, |state, cursor, len| {
+            if auto_advance && state.repeat == RepeatMode::One {
+                cursor
+            } else if cursor + 1 < len {
+                cursor + 1
+            } else if state.repeat == RepeatMode::All {
+                0
+            } else {
+                cursor
+            }
+        })
+    }
+
+    /// Go back one item, wrapping to the end only with repeat mode "all".
+    pub fn queue_previous(&self, playlist_id: i64) -> AppResult<PlaybackQueue> {
+        self.step_queue(playlist_id, |state, cursor, len| {
+            if cursor > 0 {
+                cursor - 1
+            } else if state.repeat == RepeatMode::All {
+                len - 1
+            } else {
+                cursor
+            }
+        })
+    }
+
+    fn step_queue(
+        &self,
+        playlist_id: i64,
+        step: impl FnOnce(&QueueState, usize, usize) -> usize,
+    ) -> AppResult<PlaybackQueue> {
+        self.ensure_playlist_exists(playlist_id)?;
+        let mut state = self.queue_state(playlist_id)?;
+        let queue = self.resolve_queue(playlist_id, &state)?;
+
+        if let Some(cursor) = queue.cursor {
+            let next = step(&state, cursor, queue.item_ids.len());
+            state.current_item_id = Some(queue.item_ids[next]);
+        }
+        self.save_queue_state(playlist_id, state, queue.item_ids)
+    }
+
+    /// Make `item_id` the current item.
+    pub fn queue_jump(&self, playlist_id: i64, item_id: i64) -> AppResult<PlaybackQueue> {
+        self.ensure_playlist_exists(playlist_id)?;
+        let mut state = self.queue_state(playlist_id)?;
+        let queue = self.resolve_queue(playlist_id, &state)?;
+
+        if !queue.item_ids.contains(&item_id) {
+            return Err(AppError::not_found("playlist item", item_id));
+        }
+        state.current_item_id = Some(item_id);
+        self.save_queue_state(playlist_id, state, queue.item_ids)
+    }
+
+    /// Shuffle the queue into a new random order, with the current item
+    /// first so playback carries on where it is.
+    pub fn reshuffle_queue(&self, playlist_id: i64) -> AppResult<PlaybackQueue> {
+        self.ensure_playlist_exists(playlist_id)?;
+        let mut state = self.queue_state(playlist_id)?;
+        let queue = self.resolve_queue(playlist_id, &state)?;
+
+        let current = queue.cursor.map(|index| queue.item_ids[index]);
+        let others: Vec<i64> = queue
+            .item_ids
+            .iter()
+            .copied()
+            .filter(|&id| Some(id) != current)
+            .collect();
+        let others_json = serde_json::to_string(&others)
+            .map_err(|e| AppError::internal(format!("failed to serialize item ids: {}", e)))?;
+        let shuffled: Vec<i64> = self
+            .conn
+            .prepare("SELECT value FROM json_each(?1) ORDER BY random()")?
+            .query_map(params![others_json], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+
+        let order: Vec<i64> = current.into_iter().chain(shuffled).collect();
+        state.current_item_id = current;
+        state.shuffle_order = Some(order.clone());
+        self.save_queue_state(playlist_id, state, order)
+    }
+
+    /// Turn shuffle on (a fresh shuffle, unless it's already on) or off. The
+    /// current item stays current either way.
+    pub fn set_queue_shuffle(&self, playlist_id: i64, shuffled: bool) -> AppResult<PlaybackQueue> {
+        self.ensure_playlist_exists(playlist_id)?;
+        let mut state = self.queue_state(playlist_id)?;
+        if shuffled {
+            if state.shuffle_order.is_none() {
+                return self.reshuffle_queue(playlist_id);
+            }
+            return self.resolve_queue(playlist_id, &state);
+        }
+
+        let queue = self.resolve_queue(playlist_id, &state)?;
+        state.current_item_id = queue.cursor.map(|index| queue.item_ids[index]);
+        state.shuffle_order = None;
+        self.save_queue_state(playlist_id, state, queue.item_ids)
+    }
+
+    pub fn set_queue_repeat(
+        &self,
+        playlist_id: i64,
+        repeat: RepeatMode,
+    ) -> AppResult<PlaybackQueue> {
+        self.ensure_playlist_exists(playlist_id)?;
+        let mut state = self.queue_state(playlist_id)?;
+        let queue = self.resolve_queue(playlist_id, &state)?;
+
+        state.repeat = repeat;
+        self.save_queue_state(playlist_id, state, queue.item_ids)
+    }
+}
@@ -0,0 +1,406 @@
+use crate::database::{playlist_item_from_row, Database, ITEM_SELECT};
+use crate::error::{AppError, AppResult};
+use crate::models::{PlaylistItem, PlaylistMetadata, SkippedRule, SmartRule, SmartRules};
+use chrono::{Duration, Utc};
+use rusqlite::types::{Type, Value};
+use rusqlite::{params, params_from_iter};
+use std::collections::{HashMap, HashSet};
+
+/// Read a nullable `smart_rules` JSON column.
+pub fn smart_rules_from_column(
+    row: &rusqlite::Row,
+    index: usize,
+) -> rusqlite::Result<Option<SmartRules>> {
+    row.get::<_, Option<String>>(index)?
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
+pub fn smart_rules_to_json(rules: &SmartRules) -> AppResult<String> {
+    serde_json::to_string(rules)
+        .map_err(|e| AppError::internal(format!("failed to serialize smart rules: {}", e)))
+}
+
+fn validate(rules: &SmartRules) -> AppResult<()> {
+    let invalid = |message: &str| Err(AppError::validation("rules", message));
+
+    if rules.rules.is_empty() {
+        return invalid("a smart playlist needs at least one rule");
+    }
+
+    for rule in &rules.rules {
+        match rule {
+            SmartRule::Author { author } if author.trim().is_empty() => {
+                return invalid("author cannot be empty");
+            }
+            SmartRule::TitleContains { text } if text.trim().is_empty() => {
+                return invalid("title text cannot be empty");
+            }
+            SmartRule::ProgressBetween { min, max }
+                if !(0.0..=100.0).contains(min) || !(0.0..=100.0).contains(max) || min > max =>
+            {
+                return invalid("progress range must be within 0-100, min first");
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+// The playlist a rule refers to, if any
+fn rule_playlist_id(rule: &SmartRule) -> Option<i64> {
+    match rule {
+        SmartRule::InPlaylist { playlist_id } | SmartRule::InFolder { playlist_id, .. } => {
+            Some(*playlist_id)
+        }
+        _ => None,
+    }
+}
+
+fn set_rule_playlist_id(rule: &mut SmartRule, id: i64) {
+    if let SmartRule::InPlaylist { playlist_id } | SmartRule::InFolder { playlist_id, .. } = rule {
+        *playlist_id = id;
+    }
+}
+
+// One rule as an SQL condition over playlist_items pi, videos v and
+// video_progress vp, with its parameters.
+fn rule_condition(rule: &SmartRule) -> (String, Vec<Value>) {
+    match rule {
+        SmartRule::InPlaylist { playlist_id } => (
+            "pi.playlist_id = ?".to_string(),
+            vec![(*playlist_id).into()],
+        ),
+        SmartRule::InFolder {
+            playlist_id,
+            folder_color,
+        } => {
+            // Rules saved before colors were resolved on save may hold a hex
+            // or mixed-case color, so resolve it the way `find_folder_key` does
+            let color = folder_color.trim().to_lowercase();
+            (
+                "EXISTS (SELECT 1 FROM video_folder_assignments vfa
+                         WHERE vfa.item_id = pi.id AND vfa.playlist_id = ? AND vfa.folder_color = (
+                            SELECT color_key FROM folders WHERE color_key = ? OR hex = ?
+                            ORDER BY color_key = ? DESC, position LIMIT 1
+                         ))"
+                .to_string(),
+                vec![
+                    (*playlist_id).into(),
+                    color.clone().into(),
+                    color.clone().into(),
+                    color.into(),
+                ],
+            )
+        }
+        SmartRule::Author { author } => (
+            "v.author = ? COLLATE NOCASE".to_string(),
+            vec![author.trim().to_string().into()],
+        ),
+        SmartRule::TitleContains { text } => (
+            "instr(lower(v.title), lower(?)) > 0".to_string(),
+            vec![text.trim().to_string().into()],
+        ),
+        SmartRule::FullyWatched { watched } => (
+            "COALESCE(vp.has_fully_watched, 0) = ?".to_string(),
+            vec![(*watched).into()],
+        ),
+        SmartRule::AddedWithinDays { days } => {
+            let cutoff = (Utc::now() - Duration::days((*days).into())).to_rfc3339();
+            ("pi.added_at >= ?".to_string(), vec![cutoff.into()])
+        }
+        SmartRule::ProgressBetween { min, max } => (
+            "COALESCE(vp.progress_percentage, 0) BETWEEN ? AND ?".to_string(),
+            vec![(*min).into(), (*max).into()],
+        ),
+        SmartRule::IsLocal { local } => ("v.is_local = ?".to_string(), vec![(*local).into()]),
+    }
+}
+
+impl Database {
+    pub fn create_smart_playlist(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        rules: &SmartRules,
+    ) -> AppResult<i64> {
+        if name.trim().is_empty() {
+            return Err(AppError::validation(
+                "name",
+                "playlist name cannot be empty",
+            ));
+        }
+        let rules = &self.resolve_rules(rules)?;
+
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO playlists (name, description, smart_rules, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?4)",
+            params![name, description, smart_rules_to_json(rules)?, now],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn update_smart_playlist_rules(
+        &self,
+        playlist_id: i64,
+        rules: &SmartRules,
+    ) -> AppResult<bool> {
+        let rules = &self.resolve_rules(rules)?;
+        if self.get_smart_rules(playlist_id)?.is_none() {
+            self.ensure_playlist_exists(playlist_id)?;
+            return Err(AppError::conflict(
+                "only smart playlists have rules; this playlist stores its items",
+            ));
+        }
+
+        let rows = self.conn.execute(
+            "UPDATE playlists SET smart_rules = ?1, updated_at = ?2 WHERE id = ?3",
+            params![
+                smart_rules_to_json(rules)?,
+                Utc::now().to_rfc3339(),
+                playlist_id
+            ],
+        )?;
+        Ok(rows > 0)
+    }
+
+    // `validate`, plus the checks that need the database: a folder rule has to
+    // name an existing folder in a live regular playlist. Folder colors come
+    // back as stored keys.
+    fn resolve_rules(&self, rules: &SmartRules) -> AppResult<SmartRules> {
+        validate(rules)?;
+
+        let mut resolved = rules.clone();
+        for rule in &mut resolved.rules {
+            self.resolve_rule(rule)?;
+        }
+
+        Ok(resolved)
+    }
+
+    fn resolve_rule(&self, rule: &mut SmartRule) -> AppResult<()> {
+        if let SmartRule::InFolder {
+            playlist_id,
+            folder_color,
+        } = rule
+        {
+            self.ensure_playlist_exists(*playlist_id)?;
+            if self.get_smart_rules(*playlist_id)?.is_some() {
+                return Err(AppError::validation(
+                    "rules",
+                    "folder rules must name a regular playlist, not a smart one",
+                ));
+            }
+            *folder_color = self.resolve_folder(folder_color)?;
+        }
+        Ok(())
+    }
+
+    /// Names of the playlists `rules` refer to, by id, for exporting them.
+    pub(crate) fn rule_playlist_names(
+        &self,
+        rules: &SmartRules,
+    ) -> AppResult<HashMap<i64, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name FROM playlists WHERE id = ?1")?;
+        let mut names = HashMap::new();
+        for rule in &rules.rules {
+            if let Some(id) = rule_playlist_id(rule) {
+                match stmt.query_row(params![id], |row| row.get(0)) {
+                    Ok(name) => {
+                        names.insert(id, name);
+                    }
+                    Err(rusqlite::Error::QueryReturnedNoRows) => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// Point imported rules at this library's playlists. Each playlist a rule
+    /// names is looked up in `imported_ids` (bundle name to new id) first, then
+    /// among the existing regular playlists. Rules that still don't resolve
+    /// are left out and returned; if none are left the import is refused.
+    pub(crate) fn import_smart_rules(
+        &self,
+        name: &str,
+        rules: &SmartRules,
+        rule_playlists: &HashMap<i64, String>,
+        imported_ids: &HashMap<String, i64>,
+    ) -> AppResult<(SmartRules, Vec<SkippedRule>)> {
+        let mut kept = Vec::with_capacity(rules.rules.len());
+        let mut skipped = Vec::new();
+        for rule in &rules.rules {
+            match self.import_rule(rule, rule_playlists, imported_ids) {
+                Ok(rule) => kept.push(rule),
+                Err(e @ (AppError::NotFound { .. } | AppError::Validation { .. })) => {
+                    skipped.push(SkippedRule {
+                        rule: rule.clone(),
+                        reason: e.to_string(),
+                    })
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if kept.is_empty() && !skipped.is_empty() {
+            return Err(AppError::validation(
+                "rules",
+                format!(
+                    "none of the rules of smart playlist '{}' apply to this library",
+                    name
+                ),
+            ));
+        }
+        let rules = SmartRules {
+            match_all: rules.match_all,
+            rules: kept,
+        };
+        validate(&rules)?;
+        Ok((rules, skipped))
+    }
+
+    // One imported rule, pointed at this library's playlist and resolved
+    fn import_rule(
+        &self,
+        rule: &SmartRule,
+        rule_playlists: &HashMap<i64, String>,
+        imported_ids: &HashMap<String, i64>,
+    ) -> AppResult<SmartRule> {
+        let mut rule = rule.clone();
+        if let Some(id) = rule_playlist_id(&rule) {
+            let name = rule_playlists.get(&id).ok_or_else(|| {
+                AppError::validation(
+                    "rules",
+                    format!("playlist {} isn't named in the bundle", id),
+                )
+            })?;
+            let new_id = match imported_ids.get(name) {
+                Some(&new_id) => new_id,
+                None => self
+                    .find_regular_playlist(name)?
+                    .ok_or_else(|| AppError::not_found("playlist", name))?,
+            };
+            set_rule_playlist_id(&mut rule, new_id);
+        }
+        self.resolve_rule(&mut rule)?;
+        Ok(rule)
+    }
+
+    // A live regular playlist by exact name, oldest first
+    fn find_regular_playlist(&self, name: &str) -> AppResult<Option<i64>> {
+        match self.conn.query_row(
+            "SELECT id FROM playlists
+             WHERE name = ?1 AND deleted_at IS NULL AND smart_rules IS NULL
+             ORDER BY id LIMIT 1",
+            params![name],
+            |row| row.get(0),
+        ) {
+            Ok(id) => Ok(Some(id)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The rules of a live smart playlist, or `None` for regular, trashed
+    /// and missing playlists.
+    pub(crate) fn get_smart_rules(&self, playlist_id: i64) -> AppResult<Option<SmartRules>> {
+        match self.conn.query_row(
+            "SELECT smart_rules FROM playlists WHERE id = ?1 AND deleted_at IS NULL",
+            params![playlist_id],
+            |row| smart_rules_from_column(row, 0),
+        ) {
+            Ok(rules) => Ok(rules),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Like `ensure_playlist_exists`, but also refuses smart playlists, whose
+    /// items can't be added, moved or reordered directly.
+    pub(crate) fn ensure_regular_playlist(&self, playlist_id: i64) -> AppResult<()> {
+        self.ensure_playlist_exists(playlist_id)?;
+        if self.get_smart_rules(playlist_id)?.is_some() {
+            return Err(AppError::conflict(
+                "smart playlists are computed from their rules and can't be edited directly",
+            ));
+        }
+        Ok(())
+    }
+
+    /// The items matching `rules`, newest first. Each video appears once,
+    /// from the playlist it was most recently added to; positions are
+    /// numbered within the result.
+    pub fn evaluate_smart_rules(&self, rules: &SmartRules) -> AppResult<Vec<PlaylistItem>> {
+        let mut conditions = Vec::with_capacity(rules.rules.len());
+        let mut values = Vec::new();
+        for rule in &rules.rules {
+            let (condition, params) = rule_condition(rule);
+            conditions.push(format!("({})", condition));
+            values.extend(params);
+        }
+        let joined = conditions.join(if rules.match_all { " AND " } else { " OR " });
+
+        let mut stmt = self.conn.prepare(&format!(
+            "{}
+             INNER JOIN playlists p ON p.id = pi.playlist_id
+             LEFT JOIN video_progress vp ON vp.video_id = pi.video_id
+             WHERE pi.deleted_at IS NULL AND p.deleted_at IS NULL AND p.smart_rules IS NULL
+               AND ({})
+             ORDER BY pi.added_at DESC, pi.id DESC",
+            ITEM_SELECT, joined
+        ))?;
+
+        let mut seen = HashSet::new();
+        let mut items = Vec::new();
+        for item in stmt.query_map(params_from_iter(values), playlist_item_from_row)? {
+            let mut item = item?;
+            if seen.insert(item.video_id.clone()) {
+                item.position = items.len() as i32 + 1;
+                items.push(item);
+            }
+        }
+
+        Ok(items)
+    }
+
+    pub(crate) fn smart_playlist_metadata(
+        &self,
+        playlist_id: i64,
+        rules: &SmartRules,
+    ) -> AppResult<PlaylistMetadata> {
+        let items = self.evaluate_smart_rules(rules)?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT last_updated FROM video_progress WHERE video_id = ?1")?;
+        let mut recent: Option<(String, &PlaylistItem)> = None;
+        for item in &items {
+            let last_updated = match stmt.query_row(params![item.video_id], |row| row.get(0)) {
+                Ok(last_updated) => last_updated,
+                Err(rusqlite::Error::QueryReturnedNoRows) => continue,
+                Err(e) => return Err(e.into()),
+            };
+            if recent
+                .as_ref()
+                .is_none_or(|(latest, _)| last_updated > *latest)
+            {
+                recent = Some((last_updated, item));
+            }
+        }
+
+        Ok(PlaylistMetadata {
+            playlist_id,
+            count: items.len() as i32,
+            first_video: items.first().cloned(),
+            recent_video: recent.map(|(_, item)| item.clone()),
+            group_id: None,
+            group_playlist_count: 0,
+            group_item_count: 0,
+        })
+    }
+}
@@ -0,0 +1,329 @@
+use crate::database::Database;
+use crate::error::{AppError, AppResult};
+use crate::journal::JournalOp;
+use crate::models::{PlaylistItem, PlaylistView};
+use chrono::Utc;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    Title,
+    Author,
+    PublishedAt,
+    AddedAt,
+    ViewCount,
+    Duration,
+    Progress,
+}
+
+impl SortField {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortField::Title => "title",
+            SortField::Author => "author",
+            SortField::PublishedAt => "published_at",
+            SortField::AddedAt => "added_at",
+            SortField::ViewCount => "view_count",
+            SortField::Duration => "duration",
+            SortField::Progress => "progress",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "title" => Some(SortField::Title),
+            "author" => Some(SortField::Author),
+            "published_at" => Some(SortField::PublishedAt),
+            "added_at" => Some(SortField::AddedAt),
+            "view_count" => Some(SortField::ViewCount),
+            "duration" => Some(SortField::Duration),
+            "progress" => Some(SortField::Progress),
+            _ => None,
+        }
+    }
+}
+
+/// View counts are stored as whatever text the source gave us: "1234567",
+/// "1,234,567", "1.2M views". Returns `None` for anything else.
+pub fn parse_view_count(text: &str) -> Option<f64> {
+    let text = text
+        .trim()
+        .trim_end_matches("views")
+        .trim_end_matches("view");
+    let text: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ',')
+        .collect();
+
+    let (number, multiplier) = match text.chars().last()?.to_ascii_uppercase() {
+        'K' => (&text[..text.len() - 1], 1e3),
+        'M' => (&text[..text.len() - 1], 1e6),
+        'B' => (&text[..text.len() - 1], 1e9),
+        _ => (text.as_str(), 1.0),
+    };
+
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite() && *n >= 0.0)
+        .map(|n| n * multiplier)
+}
+
+// Sort by a key that may be missing. Items without one always go last, and
+// ties keep their manual order.
+fn sort_by_optional_key<K: PartialOrd>(
+    items: Vec<PlaylistItem>,
+    descending: bool,
+    key: impl Fn(&PlaylistItem) -> Option<K>,
+) -> Vec<PlaylistItem> {
+    let mut keyed: Vec<(Option<K>, PlaylistItem)> =
+        items.into_iter().map(|item| (key(&item), item)).collect();
+
+    keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) => {
+            let ordering = a.partial_cmp(b).unwrap_or(Ordering::Equal);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+
+    keyed.into_iter().map(|(_, item)| item).collect()
+}
+
+fn view_from_row(row: &rusqlite::Row) -> rusqlite::Result<PlaylistView> {
+    Ok(PlaylistView {
+        id: row.get(0)?,
+        playlist_id: row.get(1)?,
+        name: row.get(2)?,
+        sort_by: row.get(3)?,
+        descending: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+impl Database {
+    /// The live items of a playlist ordered by `sort_by`, leaving the stored
+    /// manual order alone.
+    pub fn get_sorted_playlist_items(
+        &self,
+        playlist_id: i64,
+        sort_by: SortField,
+        descending: bool,
+    ) -> AppResult<Vec<PlaylistItem>> {
+        let items = self.get_playlist_items(playlist_id)?;
+
+        let sorted = match sort_by {
+            SortField::Title => sort_by_optional_key(items, descending, |item| {
+                item.title.as_ref().map(|t| t.to_lowercase())
+            }),
+            SortField::Author => sort_by_optional_key(items, descending, |item| {
+                item.author.as_ref().map(|a| a.to_lowercase())
+            }),
+            SortField::PublishedAt => {
+                sort_by_optional_key(items, descending, |item| item.published_at.clone())
+            }
+            SortField::AddedAt => {
+                sort_by_optional_key(items, descending, |item| Some(item.added_at.clone()))
+            }
+            SortField::ViewCount => sort_by_optional_key(items, descending, |item| {
+                item.view_count.as_deref().and_then(parse_view_count)
+            }),
+            SortField::Duration | SortField::Progress => {
+                let progress = self.progress_for_playlist(playlist_id)?;
+                sort_by_optional_key(items, descending, |item| {
+                    let (duration, percentage) = progress.get(&item.video_id)?;
+                    match sort_by {
+                        SortField::Duration => *duration,
+                        _ => Some(*percentage),
+                    }
+                })
+            }
+        };
+
+        Ok(sorted)
+    }
+
+    // video_id -> (duration, progress percentage) for a playlist's videos
+    fn progress_for_playlist(
+        &self,
+        playlist_id: i64,
+    ) -> AppResult<HashMap<String, (Option<f64>, f64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT vp.video_id, vp.duration, vp.progress_percentage
+             FROM video_progress vp
+             INNER JOIN playlist_items pi ON pi.video_id = vp.video_id
+             WHERE pi.playlist_id = ?1 AND pi.deleted_at IS NULL",
+        )?;
+
+        let progress = stmt
+            .query_map(params![playlist_id], |row| {
+                Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+            })?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        Ok(progress)
+    }
+
+    /// Sort a playlist and store the result as its manual order. The
+    /// previous order is recorded so the sort can be undone.
+    pub fn sort_playlist_items(
+        &self,
+        playlist_id: i64,
+        sort_by: SortField,
+        descending: bool,
+    ) -> AppResult<Vec<PlaylistItem>> {
+        self.ensure_regular_playlist(playlist_id)?;
+        let tx = self.conn.unchecked_transaction()?;
+
+        let before: Vec<i64> = self
+            .get_playlist_items(playlist_id)?
+            .iter()
+            .map(|item| item.id)
+            .collect();
+        let after: Vec<i64> = self
+            .get_sorted_playlist_items(playlist_id, sort_by, descending)?
+            .iter()
+            .map(|item| item.id)
+            .collect();
+
+        if before != after {
+            self.set_item_order(playlist_id, &after)?;
+            self.record_operation(
+                &JournalOp::SetOrder {
+                    playlist_id,
+                    before,
+                    after,
+                },
+                &format!("Sort playlist by {}", sort_by.as_str().replace('_', " ")),
+            )?;
+        }
+
+        tx.commit()?;
+        self.get_playlist_items(playlist_id)
+    }
+
+    /// Renumber a playlist to follow `item_ids`, which must be exactly its
+    /// live items.
+    pub(crate) fn set_item_order(&self, playlist_id: i64, item_ids: &[i64]) -> AppResult<()> {
+        let live: HashSet<i64> = self
+            .get_playlist_items(playlist_id)?
+            .iter()
+            .map(|item| item.id)
+            .collect();
+        let wanted: HashSet<i64> = item_ids.iter().copied().collect();
+        if live != wanted || wanted.len() != item_ids.len() {
+            return Err(AppError::conflict(
+                "the playlist's items have changed since this order was taken",
+            ));
+        }
+
+        // Negative first, then flipped, for the unique position index
+        for (index, id) in item_ids.iter().enumerate() {
+            self.conn.execute(
+                "UPDATE playlist_items SET position = ?1 WHERE id = ?2",
+                params![-(index as i32 + 1), id],
+            )?;
+        }
+        self.conn.execute(
+            "UPDATE playlist_items SET position = -position
+             WHERE playlist_id = ?1 AND position < 0 AND deleted_at IS NULL",
+            params![playlist_id],
+        )?;
+        Ok(())
+    }
+
+    /// Save a named sort for a playlist, replacing any view with the same
+    /// name. Views store the sort, not a snapshot, so items added later fall
+    /// into place.
+    pub fn save_playlist_view(
+        &self,
+        playlist_id: i64,
+        name: &str,
+        sort_by: SortField,
+        descending: bool,
+    ) -> AppResult<PlaylistView> {
+        if name.trim().is_empty() {
+            return Err(AppError::validation("name", "view name cannot be empty"));
+        }
+        self.ensure_playlist_exists(playlist_id)?;
+
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO playlist_views (playlist_id, name, sort_by, descending, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+             ON CONFLICT(playlist_id, name) DO UPDATE SET
+                sort_by = excluded.sort_by,
+                descending = excluded.descending,
+                updated_at = excluded.updated_at",
+            params![playlist_id, name.trim(), sort_by.as_str(), descending, now],
+        )?;
+
+        let view = self.conn.query_row(
+            "SELECT id, playlist_id, name, sort_by, descending, created_at, updated_at
+             FROM playlist_views WHERE playlist_id = ?1 AND name = ?2",
+            params![playlist_id, name.trim()],
+            view_from_row,
+        )?;
+        Ok(view)
+    }
+
+    pub fn get_playlist_views(&self, playlist_id: i64) -> AppResult<Vec<PlaylistView>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, playlist_id, name, sort_by, descending, created_at, updated_at
+             FROM playlist_views WHERE playlist_id = ?1 ORDER BY name",
+        )?;
+
+        let views = stmt
+            .query_map(params![playlist_id], view_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(views)
+    }
+
+    pub fn delete_playlist_view(&self, view_id: i64) -> AppResult<bool> {
+        let rows = self
+            .conn
+            .execute("DELETE FROM playlist_views WHERE id = ?1", params![view_id])?;
+        Ok(rows > 0)
+    }
+
+    /// A playlist's items in the order of one of its saved views.
+    pub fn get_playlist_view_items(
+        &self,
+        playlist_id: i64,
+        view_id: i64,
+    ) -> AppResult<Vec<PlaylistItem>> {
+        let view = match self.conn.query_row(
+            "SELECT id, playlist_id, name, sort_by, descending, created_at, updated_at
+             FROM playlist_views WHERE id = ?1 AND playlist_id = ?2",
+            params![view_id, playlist_id],
+            view_from_row,
+        ) {
+            Ok(view) => view,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Err(AppError::not_found("playlist view", view_id))
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let sort_by = SortField::parse(&view.sort_by).ok_or_else(|| {
+            AppError::internal(format!(
+                "view {} has an unknown sort '{}'",
+                view.id, view.sort_by
+            ))
+        })?;
+
+        self.get_sorted_playlist_items(playlist_id, sort_by, view.descending)
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error::{AppError, AppResult};
 use axum::{
     body::Body,
     extract::{Path, State},
@@ -40,7 +41,7 @@ impl StreamingServer {
         Arc::clone(&self.file_registry)
     }
 
-    pub async fn start(self: Arc<Self>) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn start(self: Arc<Self>) -> AppResult<()> {
         let file_registry = Arc::clone(&self.file_registry);
         let port = self.port;
 
@@ -55,27 +56,30 @@ impl StreamingServer {
             .with_state(file_registry);
 
         // Start server
-        let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{}", port)).await?;
+        let addr = format!("127.0.0.1:{}", port);
+        let listener = tokio::net::TcpListener::bind(&addr)
+            .await
+            .map_err(|e| AppError::io(addr.clone(), e))?;
         println!("Streaming server started on http://127.0.0.1:{}", port);
 
         axum::serve(listener, app).await?;
         Ok(())
     }
 
-    pub fn register_file(&self, file_path: PathBuf) -> String {
-        let mut registry = self.file_registry.lock().unwrap();
+    pub fn register_file(&self, file_path: PathBuf) -> AppResult<String> {
+        let mut registry = self.file_registry.lock()?;
         // Use a hash of the path as the file ID
         let mut hasher = DefaultHasher::new();
         file_path.to_string_lossy().hash(&mut hasher);
         let file_id = format!("{:x}", hasher.finish());
         registry.insert(file_id.clone(), file_path);
-        file_id
+        Ok(file_id)
     }
 
-    pub fn get_stream_url(&self, file_path: &str) -> String {
+    pub fn get_stream_url(&self, file_path: &str) -> AppResult<String> {
         let path_buf = PathBuf::from(file_path);
-        let file_id = self.register_file(path_buf);
-        format!("http://127.0.0.1:{}/stream/{}", self.port, file_id)
+        let file_id = self.register_file(path_buf)?;
+        Ok(format!("http://127.0.0.1:{}/stream/{}", self.port, file_id))
     }
 }
 
@@ -83,25 +87,30 @@ async fn stream_file(
     Path(file_id): Path<String>,
     headers: HeaderMap,
     State(file_registry): State<FileRegistry>,
-) -> Result<Response, StatusCode> {
+) -> AppResult<Response> {
     // Get file path from registry
     let file_path = {
-        let registry = file_registry.lock().unwrap();
+        let registry = file_registry.lock()?;
         registry.get(&file_id).cloned()
     };
 
-    let file_path = file_path.ok_or(StatusCode::NOT_FOUND)?;
+    let file_path = file_path.ok_or_else(|| AppError::not_found("stream", &file_id))?;
+    let path_str = file_path.to_string_lossy().to_string();
 
     // Open file
-    let mut file = File::open(&file_path)
-        .await
-        .map_err(|_| StatusCode::NOT_FOUND)?;
+    let mut file = File::open(&file_path).await.map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            AppError::not_found("file", &path_str)
+        } else {
+            AppError::io(path_str.clone(), e)
+        }
+    })?;
 
     // Get file size
     let file_size = file
         .metadata()
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|e| AppError::io(path_str.clone(), e))?
         .len();
 
     // Handle range requests for seeking
@@ -116,13 +125,13 @@ async fn stream_file(
                 // Seek to start position
                 file.seek(SeekFrom::Start(start))
                     .await
-                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+                    .map_err(|e| AppError::io(path_str.clone(), e))?;
 
                 // Read the requested range
                 let mut buffer = vec![0u8; content_length as usize];
                 file.read_exact(&mut buffer)
                     .await
-                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+                    .map_err(|e| AppError::io(path_str.clone(), e))?;
 
                 // Determine content type from file extension
                 let content_type = get_content_type(&file_path);
@@ -0,0 +1,505 @@
+use crate::database::Database;
+use crate::error::{AppError, AppResult};
+use crate::groups::insert_at;
+use crate::models::{Tab, TabImportReport, TabPreset};
+use chrono::Utc;
+use rusqlite::params;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+const TAB_SELECT: &str = "SELECT id, name, position, created_at, updated_at FROM tabs";
+const PRESET_SELECT: &str = "SELECT id, name, position, created_at, updated_at FROM tab_presets";
+
+// The "All" tab and preset only exist in the frontend, so they're skipped
+const LEGACY_ALL_ID: &str = "all";
+
+// Shapes of the `playlistTabs` and `tabPresets` localStorage entries
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyTab {
+    id: Value,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    playlist_ids: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyPreset {
+    id: Value,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    tab_ids: Vec<Value>,
+}
+
+// Old ids were strings like "tab-1712345678901", but numbers slipped in too
+fn legacy_id(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn legacy_playlist_id(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn tab_from_row(row: &rusqlite::Row) -> rusqlite::Result<Tab> {
+    Ok(Tab {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        position: row.get(2)?,
+        playlist_ids: Vec::new(),
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+fn preset_from_row(row: &rusqlite::Row) -> rusqlite::Result<TabPreset> {
+    Ok(TabPreset {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        position: row.get(2)?,
+        tab_ids: Vec::new(),
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+// Tabs, and presets of tabs, are both ordered by `position`, as are the
+// playlists within a tab and the tabs within a preset. A trashed playlist
+// drops out of its tabs but keeps its membership rows, so restoring it puts
+// it back; purging it deletes them.
+impl Database {
+    fn get_tab(&self, tab_id: i64) -> AppResult<Tab> {
+        let mut tab = match self.conn.query_row(
+            &format!("{} WHERE id = ?1", TAB_SELECT),
+            params![tab_id],
+            tab_from_row,
+        ) {
+            Ok(tab) => tab,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Err(AppError::not_found("tab", tab_id))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        tab.playlist_ids = self.tab_playlist_ids(tab_id, false)?;
+        Ok(tab)
+    }
+
+    fn tab_playlist_ids(&self, tab_id: i64, include_trashed: bool) -> AppResult<Vec<i64>> {
+        let ids = self
+            .conn
+            .prepare(
+                "SELECT tp.playlist_id FROM tab_playlists tp
+                 INNER JOIN playlists p ON p.id = tp.playlist_id
+                 WHERE tp.tab_id = ?1 AND (?2 OR p.deleted_at IS NULL)
+                 ORDER BY tp.position, tp.playlist_id",
+            )?
+            .query_map(params![tab_id, include_trashed], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(ids)
+    }
+
+    fn get_tab_preset(&self, preset_id: i64) -> AppResult<TabPreset> {
+        let mut preset = match self.conn.query_row(
+            &format!("{} WHERE id = ?1", PRESET_SELECT),
+            params![preset_id],
+            preset_from_row,
+        ) {
+            Ok(preset) => preset,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Err(AppError::not_found("tab preset", preset_id))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        preset.tab_ids = self.preset_tab_ids(preset_id)?;
+        Ok(preset)
+    }
+
+    fn preset_tab_ids(&self, preset_id: i64) -> AppResult<Vec<i64>> {
+        let ids = self
+            .conn
+            .prepare(
+                "SELECT tab_id FROM tab_preset_tabs WHERE preset_id = ?1
+                 ORDER BY position, tab_id",
+            )?
+            .query_map(params![preset_id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(ids)
+    }
+
+    pub fn get_tabs(&self) -> AppResult<Vec<Tab>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("{} ORDER BY position, id", TAB_SELECT))?;
+        let tabs = stmt
+            .query_map([], tab_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        tabs.into_iter()
+            .map(|mut tab| {
+                tab.playlist_ids = self.tab_playlist_ids(tab.id, false)?;
+                Ok(tab)
+            })
+            .collect()
+    }
+
+    /// Create an empty tab after the existing ones.
+    pub fn create_tab(&self, name: &str) -> AppResult<Tab> {
+        if name.trim().is_empty() {
+            return Err(AppError::validation("name", "tab name cannot be empty"));
+        }
+
+        let position: i32 = self.conn.query_row(
+            "SELECT COALESCE(MAX(position), 0) + 1 FROM tabs",
+            [],
+            |row| row.get(0),
+        )?;
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO tabs (name, position, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)",
+            params![name.trim(), position, now],
+        )?;
+
+        self.get_tab(self.conn.last_insert_rowid())
+    }
+
+    pub fn rename_tab(&self, tab_id: i64, name: &str) -> AppResult<bool> {
+        if name.trim().is_empty() {
+            return Err(AppError::validation("name", "tab name cannot be empty"));
+        }
+
+        let rows = self.conn.execute(
+            "UPDATE tabs SET name = ?1, updated_at = ?2 WHERE id = ?3",
+            params![name.trim(), Utc::now().to_rfc3339(), tab_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// Move a tab to the 1-based `position` among the tabs.
+    pub fn move_tab(&self, tab_id: i64, position: i32) -> AppResult<Tab> {
+        self.get_tab(tab_id)?;
+        let tx = self.conn.unchecked_transaction()?;
+
+        let mut tab_ids: Vec<i64> = self
+            .conn
+            .prepare("SELECT id FROM tabs WHERE id != ?1 ORDER BY position, id")?
+            .query_map(params![tab_id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        insert_at(&mut tab_ids, tab_id, Some(position));
+        for (index, id) in tab_ids.iter().enumerate() {
+            self.conn.execute(
+                "UPDATE tabs SET position = ?1 WHERE id = ?2",
+                params![index as i32 + 1, id],
+            )?;
+        }
+
+        tx.commit()?;
+        self.get_tab(tab_id)
+    }
+
+    /// Delete a tab. Its playlists are untouched; presets just lose the tab.
+    pub fn delete_tab(&self, tab_id: i64) -> AppResult<bool> {
+        let rows = self
+            .conn
+            .execute("DELETE FROM tabs WHERE id = ?1", params![tab_id])?;
+        Ok(rows > 0)
+    }
+
+    /// Add a playlist to a tab at `position`, or last when it's `None`. A
+    /// playlist already in the tab moves to `position`, or stays put when it's
+    /// `None`.
+    pub fn add_playlist_to_tab(
+        &self,
+        tab_id: i64,
+        playlist_id: i64,
+        position: Option<i32>,
+    ) -> AppResult<Tab> {
+        let tab = self.get_tab(tab_id)?;
+        self.ensure_playlist_exists(playlist_id)?;
+        if position.is_none() && tab.playlist_ids.contains(&playlist_id) {
+            return Ok(tab);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+
+        // Trashed members go after the live ones, ready to be restored
+        let mut playlist_ids: Vec<i64> = tab
+            .playlist_ids
+            .into_iter()
+            .filter(|&id| id != playlist_id)
+            .collect();
+        insert_at(&mut playlist_ids, playlist_id, position);
+        let trashed: Vec<i64> = self
+            .tab_playlist_ids(tab_id, true)?
+            .into_iter()
+            .filter(|id| !playlist_ids.contains(id))
+            .collect();
+        playlist_ids.extend(trashed);
+
+        for (index, id) in playlist_ids.iter().enumerate() {
+            self.conn.execute(
+                "INSERT INTO tab_playlists (tab_id, playlist_id, position) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(tab_id, playlist_id) DO UPDATE SET position = excluded.position",
+                params![tab_id, id, index as i32 + 1],
+            )?;
+        }
+        self.touch_tab(tab_id)?;
+
+        tx.commit()?;
+        self.get_tab(tab_id)
+    }
+
+    pub fn remove_playlist_from_tab(&self, tab_id: i64, playlist_id: i64) -> AppResult<bool> {
+        let rows = self.conn.execute(
+            "DELETE FROM tab_playlists WHERE tab_id = ?1 AND playlist_id = ?2",
+            params![tab_id, playlist_id],
+        )?;
+        if rows > 0 {
+            self.touch_tab(tab_id)?;
+        }
+        Ok(rows > 0)
+    }
+
+    fn touch_tab(&self, tab_id: i64) -> AppResult<()> {
+        self.conn.execute(
+            "UPDATE tabs SET updated_at = ?1 WHERE id = ?2",
+            params![Utc::now().to_rfc3339(), tab_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_tab_presets(&self) -> AppResult<Vec<TabPreset>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("{} ORDER BY position, id", PRESET_SELECT))?;
+        let presets = stmt
+            .query_map([], preset_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        presets
+            .into_iter()
+            .map(|mut preset| {
+                preset.tab_ids = self.preset_tab_ids(preset.id)?;
+                Ok(preset)
+            })
+            .collect()
+    }
+
+    /// Create a preset after the existing ones. No tabs means all tabs.
+    pub fn create_tab_preset(&self, name: &str, tab_ids: &[i64]) -> AppResult<TabPreset> {
+        if name.trim().is_empty() {
+            return Err(AppError::validation("name", "preset name cannot be empty"));
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let preset_id = self.insert_tab_preset(name.trim(), tab_ids)?;
+        tx.commit()?;
+        self.get_tab_preset(preset_id)
+    }
+
+    // Callers own the transaction
+    fn insert_tab_preset(&self, name: &str, tab_ids: &[i64]) -> AppResult<i64> {
+        let position: i32 = self.conn.query_row(
+            "SELECT COALESCE(MAX(position), 0) + 1 FROM tab_presets",
+            [],
+            |row| row.get(0),
+        )?;
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO tab_presets (name, position, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?3)",
+            params![name, position, now],
+        )?;
+        let preset_id = self.conn.last_insert_rowid();
+        self.set_preset_tabs(preset_id, tab_ids)?;
+        Ok(preset_id)
+    }
+
+    /// Rename a preset and/or replace its tabs; `None` leaves either as is.
+    pub fn update_tab_preset(
+        &self,
+        preset_id: i64,
+        name: Option<&str>,
+        tab_ids: Option<&[i64]>,
+    ) -> AppResult<TabPreset> {
+        self.get_tab_preset(preset_id)?;
+        if name.is_some_and(|name| name.trim().is_empty()) {
+            return Err(AppError::validation("name", "preset name cannot be empty"));
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute(
+            "UPDATE tab_presets SET name = COALESCE(?1, name), updated_at = ?2 WHERE id = ?3",
+            params![name.map(str::trim), Utc::now().to_rfc3339(), preset_id],
+        )?;
+        if let Some(tab_ids) = tab_ids {
+            self.set_preset_tabs(preset_id, tab_ids)?;
+        }
+
+        tx.commit()?;
+        self.get_tab_preset(preset_id)
+    }
+
+    // Callers own the transaction
+    fn set_preset_tabs(&self, preset_id: i64, tab_ids: &[i64]) -> AppResult<()> {
+        self.conn.execute(
+            "DELETE FROM tab_preset_tabs WHERE preset_id = ?1",
+            params![preset_id],
+        )?;
+
+        let mut position = 0;
+        for (index, &tab_id) in tab_ids.iter().enumerate() {
+            if tab_ids[..index].contains(&tab_id) {
+                continue;
+            }
+            self.get_tab(tab_id)?;
+            position += 1;
+            self.conn.execute(
+                "INSERT INTO tab_preset_tabs (preset_id, tab_id, position) VALUES (?1, ?2, ?3)",
+                params![preset_id, tab_id, position],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Move a preset to the 1-based `position` among the presets.
+    pub fn move_tab_preset(&self, preset_id: i64, position: i32) -> AppResult<TabPreset> {
+        self.get_tab_preset(preset_id)?;
+        let tx = self.conn.unchecked_transaction()?;
+
+        let mut preset_ids: Vec<i64> = self
+            .conn
+            .prepare("SELECT id FROM tab_presets WHERE id != ?1 ORDER BY position, id")?
+            .query_map(params![preset_id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        insert_at(&mut preset_ids, preset_id, Some(position));
+        for (index, id) in preset_ids.iter().enumerate() {
+            self.conn.execute(
+                "UPDATE tab_presets SET position = ?1 WHERE id = ?2",
+                params![index as i32 + 1, id],
+            )?;
+        }
+
+        tx.commit()?;
+        self.get_tab_preset(preset_id)
+    }
+
+    pub fn delete_tab_preset(&self, preset_id: i64) -> AppResult<bool> {
+        let rows = self
+            .conn
+            .execute("DELETE FROM tab_presets WHERE id = ?1", params![preset_id])?;
+        Ok(rows > 0)
+    }
+
+    /// Import the tabs and presets older builds kept in localStorage, given
+    /// the raw `playlistTabs` and `tabPresets` values. Only allowed while no
+    /// tabs or presets exist, so it can't run twice. References to playlists
+    /// that no longer exist are dropped.
+    pub fn import_local_tabs(
+        &self,
+        tabs_json: &str,
+        presets_json: Option<&str>,
+    ) -> AppResult<TabImportReport> {
+        let legacy_tabs: Vec<LegacyTab> = serde_json::from_str(tabs_json)
+            .map_err(|e| AppError::validation("tabs", format!("not a list of tabs: {}", e)))?;
+        let legacy_presets: Vec<LegacyPreset> = match presets_json {
+            Some(json) => serde_json::from_str(json).map_err(|e| {
+                AppError::validation("presets", format!("not a list of presets: {}", e))
+            })?,
+            None => Vec::new(),
+        };
+
+        let existing: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM tabs) OR EXISTS(SELECT 1 FROM tab_presets)",
+            [],
+            |row| row.get(0),
+        )?;
+        if existing {
+            return Err(AppError::conflict(
+                "tabs have already been set up; the localStorage import only runs once",
+            ));
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut tab_ids = HashMap::new();
+        let mut skipped_playlists = 0;
+
+        for legacy in &legacy_tabs {
+            let Some(old_id) = legacy_id(&legacy.id) else {
+                continue;
+            };
+            if old_id == LEGACY_ALL_ID || tab_ids.contains_key(&old_id) {
+                continue;
+            }
+
+            let name = match legacy.name.trim() {
+                "" => format!("Tab {}", tab_ids.len() + 1),
+                name => name.to_string(),
+            };
+            let tab = self.create_tab(&name)?;
+
+            let mut position = 0;
+            let mut seen = Vec::new();
+            for value in &legacy.playlist_ids {
+                let playlist_id = legacy_playlist_id(value);
+                match playlist_id {
+                    Some(id) if seen.contains(&id) => continue,
+                    Some(id) if self.ensure_playlist_exists(id).is_ok() => {
+                        seen.push(id);
+                        position += 1;
+                        self.conn.execute(
+                            "INSERT INTO tab_playlists (tab_id, playlist_id, position)
+                             VALUES (?1, ?2, ?3)",
+                            params![tab.id, id, position],
+                        )?;
+                    }
+                    _ => skipped_playlists += 1,
+                }
+            }
+            tab_ids.insert(old_id, tab.id);
+        }
+
+        let mut preset_ids = HashMap::new();
+        for legacy in &legacy_presets {
+            let Some(old_id) = legacy_id(&legacy.id) else {
+                continue;
+            };
+            if old_id == LEGACY_ALL_ID || preset_ids.contains_key(&old_id) {
+                continue;
+            }
+
+            let name = match legacy.name.trim() {
+                "" => format!("Preset {}", preset_ids.len() + 1),
+                name => name.to_string(),
+            };
+            // Tabs that weren't imported are dropped
+            let preset_tabs: Vec<i64> = legacy
+                .tab_ids
+                .iter()
+                .filter_map(legacy_id)
+                .filter_map(|id| tab_ids.get(&id).copied())
+                .collect();
+            let preset_id = self.insert_tab_preset(&name, &preset_tabs)?;
+            preset_ids.insert(old_id, preset_id);
+        }
+
+        tx.commit()?;
+        Ok(TabImportReport {
+            tabs: self.get_tabs()?,
+            presets: self.get_tab_presets()?,
+            tab_ids,
+            preset_ids,
+            skipped_playlists,
+        })
+    }
+}
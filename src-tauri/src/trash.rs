@@ -0,0 +1,203 @@
+use crate::database::{playlist_item_from_row, Database, ITEM_SELECT};
+use crate::error::{AppError, AppResult};
+use crate::models::TrashEntry;
+use chrono::{Duration, Utc};
+use rusqlite::params;
+
+// Deleted playlists and items stay in their tables with `deleted_at` set, so
+// their folder assignments and metadata survive untouched, and get a row in
+// `trash` to list and restore them by. A trashed item keeps its old position;
+// live items close the gap, and it is reopened on restore.
+impl Database {
+    pub(crate) fn trash_playlist_rows(&self, playlist_id: i64) -> AppResult<bool> {
+        let now = Utc::now().to_rfc3339();
+        let rows = self.conn.execute(
+            "UPDATE playlists SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+            params![now, playlist_id],
+        )?;
+        if rows == 0 {
+            return Ok(false);
+        }
+
+        self.conn.execute(
+            "INSERT INTO trash (kind, playlist_id, item_id, deleted_at) VALUES ('playlist', ?1, NULL, ?2)",
+            params![playlist_id, now],
+        )?;
+        Ok(true)
+    }
+
+    pub(crate) fn trash_item_rows(&self, playlist_id: i64, item_id: i64) -> AppResult<bool> {
+        let position: i32 = match self.conn.query_row(
+            "SELECT position FROM playlist_items WHERE id = ?1 AND playlist_id = ?2 AND deleted_at IS NULL",
+            params![item_id, playlist_id],
+            |row| row.get(0),
+        ) {
+            Ok(pos) => pos,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "UPDATE playlist_items SET deleted_at = ?1 WHERE id = ?2",
+            params![now, item_id],
+        )?;
+        self.shift_positions(playlist_id, position + 1, i32::MAX, -1)?;
+        self.conn.execute(
+            "INSERT INTO trash (kind, playlist_id, item_id, deleted_at) VALUES ('item', ?1, ?2, ?3)",
+            params![playlist_id, item_id, now],
+        )?;
+        Ok(true)
+    }
+
+    pub(crate) fn untrash_playlist_rows(&self, playlist_id: i64) -> AppResult<bool> {
+        let rows = self.conn.execute(
+            "UPDATE playlists SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+            params![playlist_id],
+        )?;
+        self.conn.execute(
+            "DELETE FROM trash WHERE kind = 'playlist' AND playlist_id = ?1",
+            params![playlist_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    pub(crate) fn untrash_item_rows(&self, playlist_id: i64, item_id: i64) -> AppResult<bool> {
+        let position: i32 = match self.conn.query_row(
+            "SELECT position FROM playlist_items WHERE id = ?1 AND playlist_id = ?2 AND deleted_at IS NOT NULL",
+            params![item_id, playlist_id],
+            |row| row.get(0),
+        ) {
+            Ok(pos) => pos,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+
+        let playlist_trashed: bool = self.conn.query_row(
+            "SELECT deleted_at IS NOT NULL FROM playlists WHERE id = ?1",
+            params![playlist_id],
+            |row| row.get(0),
+        )?;
+        if playlist_trashed {
+            return Err(AppError::conflict(
+                "the item's playlist is in the trash; restore the playlist first",
+            ));
+        }
+
+        // The playlist may have shrunk since; never leave a gap at the end
+        let position = position.clamp(1, self.live_item_count(playlist_id)? + 1);
+        self.shift_positions(playlist_id, position, i32::MAX, 1)?;
+        self.conn.execute(
+            "UPDATE playlist_items SET position = ?1, deleted_at = NULL WHERE id = ?2",
+            params![position, item_id],
+        )?;
+        self.conn.execute(
+            "DELETE FROM trash WHERE kind = 'item' AND item_id = ?1",
+            params![item_id],
+        )?;
+        Ok(true)
+    }
+
+    /// Everything in the trash, most recently deleted first.
+    pub fn list_trash(&self) -> AppResult<Vec<TrashEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT t.id, t.kind, t.playlist_id, p.name, t.item_id, t.deleted_at
+             FROM trash t
+             INNER JOIN playlists p ON p.id = t.playlist_id
+             ORDER BY t.deleted_at DESC, t.id DESC",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,         // id
+                    row.get::<_, String>(1)?,      // kind
+                    row.get::<_, i64>(2)?,         // playlist_id
+                    row.get::<_, String>(3)?,      // playlist_name
+                    row.get::<_, Option<i64>>(4)?, // item_id
+                    row.get::<_, String>(5)?,      // deleted_at
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Trashed items aren't visible through get_playlist_item
+        let mut item_stmt = self
+            .conn
+            .prepare(&format!("{} WHERE pi.id = ?1", ITEM_SELECT))?;
+
+        let mut entries = Vec::with_capacity(rows.len());
+        for (id, kind, playlist_id, playlist_name, item_id, deleted_at) in rows {
+            let item = match item_id {
+                Some(item_id) => {
+                    Some(item_stmt.query_row(params![item_id], playlist_item_from_row)?)
+                }
+                None => None,
+            };
+
+            entries.push(TrashEntry {
+                id,
+                kind,
+                playlist_id,
+                playlist_name,
+                item,
+                deleted_at,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// Put a trashed playlist or item back where it was.
+    pub fn restore_from_trash(&self, trash_id: i64) -> AppResult<bool> {
+        let entry = match self.conn.query_row(
+            "SELECT playlist_id, item_id FROM trash WHERE id = ?1",
+            params![trash_id],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?)),
+        ) {
+            Ok(entry) => entry,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        let restored = match entry {
+            (playlist_id, Some(item_id)) => self.untrash_item_rows(playlist_id, item_id)?,
+            (playlist_id, None) => self.untrash_playlist_rows(playlist_id)?,
+        };
+        tx.commit()?;
+
+        Ok(restored)
+    }
+
+    /// Permanently delete trash entries deleted more than `older_than_days`
+    /// ago (all of them for 0), along with everything that belongs to them.
+    /// Returns how many playlists and items were actually deleted.
+    pub fn purge_trash(&self, older_than_days: u32) -> AppResult<usize> {
+        let cutoff = (Utc::now() - Duration::days(older_than_days.into())).to_rfc3339();
+        let tx = self.conn.unchecked_transaction()?;
+
+        let entries = self
+            .conn
+            .prepare("SELECT playlist_id, item_id FROM trash WHERE deleted_at <= ?1")?
+            .query_map(params![cutoff], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Cascades take the trash rows, folder assignments and metadata with
+        // them, and an item entry finds nothing left once its playlist is gone
+        let mut purged = 0;
+        for (playlist_id, item_id) in &entries {
+            purged += match item_id {
+                Some(item_id) => self
+                    .conn
+                    .execute("DELETE FROM playlist_items WHERE id = ?1", params![item_id])?,
+                None => self
+                    .conn
+                    .execute("DELETE FROM playlists WHERE id = ?1", params![playlist_id])?,
+            };
+        }
+
+        tx.commit()?;
+        Ok(purged)
+    }
+}
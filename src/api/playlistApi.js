@@ -9,6 +9,103 @@ export const createPlaylist = async (name, description = null) => {
   return await invokeCommand('create_playlist', { name, description });
 };
 
+export const createPlaylistGroup = async (name, parentId = null) => {
+  return await invokeCommand('create_playlist_group', { name, parentId });
+};
+
+export const getPlaylistGroups = async () => {
+  return (await invokeCommand('get_playlist_groups')) || [];
+};
+
+export const renamePlaylistGroup = async (id, name) => {
+  return await invokeCommand('rename_playlist_group', { id, name });
+};
+
+export const movePlaylistGroup = async (id, parentId = null, position = null) => {
+  return await invokeCommand('move_playlist_group', { id, parentId, position });
+};
+
+export const deletePlaylistGroup = async (id) => {
+  return await invokeCommand('delete_playlist_group', { id });
+};
+
+export const movePlaylistToGroup = async (playlistId, groupId = null, position = null) => {
+  return await invokeCommand('move_playlist_to_group', { playlistId, groupId, position });
+};
+
+// Tabs resolve to { id, name, position, playlist_ids, ... } and presets to
+// { id, name, position, tab_ids, ... }. A preset with no tabs shows all of them.
+export const getTabs = async () => {
+  return (await invokeCommand('get_tabs')) || [];
+};
+
+export const createTab = async (name) => {
+  return await invokeCommand('create_tab', { name });
+};
+
+export const renameTab = async (tabId, name) => {
+  return await invokeCommand('rename_tab', { tabId, name });
+};
+
+export const moveTab = async (tabId, position) => {
+  return await invokeCommand('move_tab', { tabId, position });
+};
+
+export const deleteTab = async (tabId) => {
+  return await invokeCommand('delete_tab', { tabId });
+};
+
+export const addPlaylistToTab = async (tabId, playlistId, position = null) => {
+  return await invokeCommand('add_playlist_to_tab', { tabId, playlistId, position });
+};
+
+export const removePlaylistFromTab = async (tabId, playlistId) => {
+  return await invokeCommand('remove_playlist_from_tab', { tabId, playlistId });
+};
+
+export const getTabPresets = async () => {
+  return (await invokeCommand('get_tab_presets')) || [];
+};
+
+export const createTabPreset = async (name, tabIds = []) => {
+  return await invokeCommand('create_tab_preset', { name, tabIds });
+};
+
+export const updateTabPreset = async (presetId, name = null, tabIds = null) => {
+  return await invokeCommand('update_tab_preset', { presetId, name, tabIds });
+};
+
+export const moveTabPreset = async (presetId, position) => {
+  return await invokeCommand('move_tab_preset', { presetId, position });
+};
+
+export const deleteTabPreset = async (presetId) => {
+  return await invokeCommand('delete_tab_preset', { presetId });
+};
+
+/**
+ * One-time import of the tabs and presets older builds kept in localStorage
+ * @param {string} tabsJson - The raw `playlistTabs` value
+ * @param {string|null} presetsJson - The raw `tabPresets` value
+ * @returns {Promise<Object>} { tabs, presets, tab_ids, preset_ids, skipped_playlists },
+ *   where tab_ids and preset_ids map old localStorage ids to new ones
+ */
+export const importLocalTabs = async (tabsJson, presetsJson = null) => {
+  return await invokeCommand('import_local_tabs', { tabsJson, presetsJson });
+};
+
+/**
+ * Create a smart playlist. `rules` is { match_all, rules: [{ type, ... }] };
+ * see DATABASE_SETUP.md for the rule types.
+ */
+export const createSmartPlaylist = async (name, rules, description = null) => {
+  return await invokeCommand('create_smart_playlist', { name, description, rules });
+};
+
+export const updateSmartPlaylistRules = async (id, rules) => {
+  return await invokeCommand('update_smart_playlist_rules', { id, rules });
+};
+
 export const getAllPlaylists = async () => {
   try {
     const result = await invokeCommand('get_all_playlists');
@@ -67,9 +164,32 @@ export const addVideoToPlaylist = async (playlistId, videoUrl, videoId, title, t
   }
 };
 
-export const getPlaylistItems = async (playlistId) => {
+// Adds many videos in one transaction. Each video is
+// { videoUrl, videoId, title, thumbnailUrl, author, viewCount, publishedAt, isLocal, folderColors }.
+// Resolves to one result per video, in order: { index, video_id, status, item_id, position, reason }
+export const addVideosToPlaylist = async (playlistId, videos, skipDuplicates = false) => {
+  try {
+    const items = videos.map(v => ({
+      video_url: v.videoUrl,
+      video_id: v.videoId,
+      title: v.title ?? null,
+      thumbnail_url: v.thumbnailUrl ?? null,
+      author: v.author ?? null,
+      view_count: v.viewCount ?? null,
+      published_at: v.publishedAt ?? null,
+      is_local: v.isLocal || false,
+      folder_colors: v.folderColors || []
+    }));
+    return await invokeCommand('add_videos_to_playlist', { playlistId, items, skipDuplicates });
+  } catch (error) {
+    console.error('Failed to add videos to playlist:', error);
+    throw error;
+  }
+};
+
+export const getPlaylistItems = async (playlistId, viewId = null) => {
   try {
-    const result = await invokeCommand('get_playlist_items', { playlistId });
+    const result = await invokeCommand('get_playlist_items', { playlistId, viewId });
     console.log('getPlaylistItems API result:', result);
     return result || [];
   } catch (error) {
@@ -78,6 +198,11 @@ export const getPlaylistItems = async (playlistId) => {
   }
 };
 
+// query: { offset, cursor, limit, folder_color, watch_state, is_local, fields }, all optional
+export const getPlaylistItemsPage = async (playlistId, query = {}) => {
+  return await invokeCommand('get_playlist_items_page', { playlistId, query });
+};
+
 export const getPlaylistsForVideoIds = async (videoIds) => {
   try {
     const result = await invokeCommand('get_playlists_for_video_ids', { videoIds });
@@ -104,6 +229,61 @@ export const reorderPlaylistItem = async (playlistId, itemId, newPosition) => {
   });
 };
 
+// Folder operations
+export const getFolders = async () => {
+  return (await invokeCommand('get_folders')) || [];
+};
+
+export const createFolder = async (colorKey, name, hex) => {
+  return await invokeCommand('create_folder', { colorKey, name, hex });
+};
+
+export const updateFolder = async (colorKey, name = null, hex = null) => {
+  return await invokeCommand('update_folder', { colorKey, name, hex });
+};
+
+export const moveFolder = async (colorKey, position) => {
+  return await invokeCommand('move_folder', { colorKey, position });
+};
+
+export const deleteFolder = async (colorKey) => {
+  return await invokeCommand('delete_folder', { colorKey });
+};
+
+// Global folder operations
+export const getGlobalFolders = async () => {
+  return (await invokeCommand('get_global_folders')) || [];
+};
+
+export const createGlobalFolder = async (name, description = null, folderColor = null) => {
+  return await invokeCommand('create_global_folder', { name, description, folderColor });
+};
+
+// Omitted fields are unchanged; an empty description or color clears it
+export const updateGlobalFolder = async (globalFolderId, { name = null, description = null, folderColor = null } = {}) => {
+  return await invokeCommand('update_global_folder', { globalFolderId, name, description, folderColor });
+};
+
+export const deleteGlobalFolder = async (globalFolderId) => {
+  return await invokeCommand('delete_global_folder', { globalFolderId });
+};
+
+export const getGlobalFolderItems = async (globalFolderId) => {
+  return (await invokeCommand('get_global_folder_items', { globalFolderId })) || [];
+};
+
+export const addItemsToGlobalFolder = async (globalFolderId, itemIds) => {
+  return await invokeCommand('add_items_to_global_folder', { globalFolderId, itemIds });
+};
+
+export const removeItemsFromGlobalFolder = async (globalFolderId, itemIds) => {
+  return await invokeCommand('remove_items_from_global_folder', { globalFolderId, itemIds });
+};
+
+export const getGlobalFoldersForItems = async (itemIds) => {
+  return (await invokeCommand('get_global_folders_for_items', { itemIds })) || {};
+};
+
 // Folder assignment operations
 export const assignVideoToFolder = async (playlistId, itemId, folderColor) => {
   return await invokeCommand('assign_video_to_folder', {
@@ -181,11 +361,46 @@ export const getAllStuckFolders = async () => {
   }
 };
 
+// Folder visibility operations
+export const getPlaylistFolderStates = async (playlistId) => {
+  return (await invokeCommand('get_playlist_folder_states', { playlistId })) || [];
+};
+
+// Flags left null are unchanged
+export const setFolderVisibility = async (playlistId, folderColor, { pinned = null, hidden = null, collapsed = null } = {}) => {
+  return await invokeCommand('set_folder_visibility', { playlistId, folderColor, pinned, hidden, collapsed });
+};
+
+export const setFolderOrder = async (playlistId, folderColors) => {
+  return (await invokeCommand('set_folder_order', { playlistId, folderColors })) || [];
+};
+
+// Folder bulk operations
+export const moveFolderItems = async (playlistId, folderColor, targetPlaylistId, targetPosition = null) => {
+  return await invokeCommand('move_folder_items', { playlistId, folderColor, targetPlaylistId, targetPosition });
+};
+
+export const copyFolderToNewPlaylist = async (playlistId, folderColor, name = null) => {
+  return await invokeCommand('copy_folder_to_new_playlist', { playlistId, folderColor, name });
+};
+
+export const recolorFolder = async (playlistId, folderColor, newFolderColor) => {
+  return await invokeCommand('recolor_folder', { playlistId, folderColor, newFolderColor });
+};
+
+export const mergeFolders = async (playlistId, folderColor, intoFolderColor) => {
+  return await invokeCommand('merge_folders', { playlistId, folderColor, intoFolderColor });
+};
+
+export const clearFolder = async (playlistId, folderColor) => {
+  return await invokeCommand('clear_folder', { playlistId, folderColor });
+};
+
 // Folder Metadata operations
 export const getFolderMetadata = async (playlistId, folderColor) => {
   try {
     const result = await invokeCommand('get_folder_metadata', { playlistId, folderColor });
-    return result || null; // Returns [name, description] or null
+    return result || null; // Returns [name, description, customAscii] (each may be null) or null
   } catch (error) {
     console.error('Error in getFolderMetadata API:', error);
     throw error;
@@ -379,3 +594,88 @@ export const getAllVideoProgress = async () => {
   }
 };
 
+
+// The video sent with a pin or like. `video` is any object with the video's
+// snake_case fields, such as a playlist item.
+const toVideoRequest = (video) => ({
+  video_id: video.video_id,
+  video_url: video.video_url,
+  title: video.title ?? null,
+  thumbnail_url: video.thumbnail_url ?? null,
+  author: video.author ?? null,
+  view_count: video.view_count ?? null,
+  published_at: video.published_at ?? null,
+  is_local: video.is_local || false
+});
+
+// Pins resolve to { video, is_priority, pinned_at, expires_at }
+export const getPins = async () => {
+  return (await invokeCommand('get_pins')) || [];
+};
+
+export const pinVideo = async (video) => {
+  return await invokeCommand('pin_video', { video: toVideoRequest(video) });
+};
+
+export const setPriorityPin = async (video, priority = true) => {
+  return await invokeCommand('set_priority_pin', { video: toVideoRequest(video), priority });
+};
+
+export const unpinVideo = async (videoId) => {
+  return await invokeCommand('unpin_video', { videoId });
+};
+
+// Likes resolve to { video, liked_at }
+export const getLikes = async () => {
+  return (await invokeCommand('get_likes')) || [];
+};
+
+export const likeVideo = async (video) => {
+  return await invokeCommand('like_video', { video: toVideoRequest(video) });
+};
+
+export const unlikeVideo = async (videoId) => {
+  return await invokeCommand('unlike_video', { videoId });
+};
+
+/**
+ * When each video was liked
+ * @param {string[]} videoIds
+ * @returns {Promise<Object>} Map of video_id -> liked_at, for liked videos only
+ */
+export const getLikesForVideoIds = async (videoIds) => {
+  if (!videoIds || videoIds.length === 0) return {};
+  return (await invokeCommand('get_likes_for_video_ids', { videoIds })) || {};
+};
+
+// Playback queues. Each resolves to { playlist_id, item_ids, cursor,
+// current_item, shuffled, repeat_mode }; listen for 'playback-queue-changed'
+// to hear about changes made elsewhere, including item edits.
+export const getPlaybackQueue = async (playlistId) => {
+  return await invokeCommand('get_playback_queue', { playlistId });
+};
+
+export const queueNext = async (playlistId, autoAdvance = false) => {
+  return await invokeCommand('queue_next', { playlistId, autoAdvance });
+};
+
+export const queuePrevious = async (playlistId) => {
+  return await invokeCommand('queue_previous', { playlistId });
+};
+
+export const queueJump = async (playlistId, itemId) => {
+  return await invokeCommand('queue_jump', { playlistId, itemId });
+};
+
+export const reshuffleQueue = async (playlistId) => {
+  return await invokeCommand('reshuffle_queue', { playlistId });
+};
+
+export const setQueueShuffle = async (playlistId, shuffled) => {
+  return await invokeCommand('set_queue_shuffle', { playlistId, shuffled });
+};
+
+// repeatMode: 'off' | 'all' | 'one'
+export const setQueueRepeat = async (playlistId, repeatMode) => {
+  return await invokeCommand('set_queue_repeat', { playlistId, repeatMode });
+};
@@ -1,6 +1,6 @@
 import React, { useState, useEffect, useMemo } from 'react';
 import { usePlaylistStore } from '../store/playlistStore';
-import { getAllPlaylists, getPlaylistItems, createPlaylist, getPlaylistsForVideoIds } from '../api/playlistApi';
+import { getLikes, getPlaylistsForVideoIds } from '../api/playlistApi';
 import VideoCard from './VideoCard';
 import PageBanner from './PageBanner';
 import { useLayoutStore } from '../store/layoutStore';
@@ -25,7 +25,6 @@ const COLORS = [
 const ITEMS_PER_PAGE = 24;
 
 const LikesPage = ({ onVideoSelect }) => {
-    const [likesPlaylistId, setLikesPlaylistId] = useState(null);
     const [likedVideos, setLikedVideos] = useState([]);
     const [loading, setLoading] = useState(true);
     const [currentPage, setCurrentPage] = useState(1);
@@ -41,19 +40,9 @@ const LikesPage = ({ onVideoSelect }) => {
         const initLikes = async () => {
             setLoading(true);
             try {
-                const playlists = await getAllPlaylists();
-                let likesPlaylist = playlists.find(p => p.name === 'Likes');
-
-                // Create if doesn't exist (consistency with PlayerController)
-                if (!likesPlaylist) {
-                    const newId = await createPlaylist('Likes', 'Videos you have liked');
-                    setLikesPlaylistId(newId);
-                    setLikedVideos([]);
-                } else {
-                    setLikesPlaylistId(likesPlaylist.id);
-                    const items = await getPlaylistItems(likesPlaylist.id);
-                    setLikedVideos(items || []);
-                }
+                // Most recently liked first
+                const likes = await getLikes();
+                setLikedVideos(likes.map(like => ({ ...like.video, liked_at: like.liked_at })));
             } catch (error) {
                 console.error('Failed to load likes:', error);
             } finally {
@@ -86,7 +75,7 @@ const LikesPage = ({ onVideoSelect }) => {
                 // Process map to count playlist occurrences
                 const counts = {};
                 Object.values(map).flat().forEach(playlistName => {
-                    if (playlistName === 'Likes') return; // Exclude 'Likes' itself
+                    if (playlistName === 'Likes') return; // Exclude the old 'Likes' playlist
                     counts[playlistName] = (counts[playlistName] || 0) + 1;
                 });
 
@@ -150,12 +139,12 @@ const LikesPage = ({ onVideoSelect }) => {
         return (
             <div className="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 xl:grid-cols-4 gap-4">
                 {currentItems.map((video, index) => {
-                    const isCurrentlyPlaying = currentPlaylistItems?.[currentVideoIndex]?.id === video.id;
+                    const isCurrentlyPlaying = currentPlaylistItems?.[currentVideoIndex]?.video_id === video.video_id;
                     const displayIndex = (currentPage - 1) * ITEMS_PER_PAGE + index;
 
                     return (
                         <VideoCard
-                            key={video.id || `liked-${index}`}
+                            key={video.video_id || `liked-${index}`}
                             video={video}
                             index={displayIndex}
                             originalIndex={displayIndex}
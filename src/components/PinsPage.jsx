@@ -26,7 +26,7 @@ const PinsPage = ({ onVideoSelect }) => {
 
         // Distribute videos
         pinnedVideos.forEach(video => {
-            if (priorityIds.includes(video.id)) {
+            if (priorityIds.includes(video.video_id)) {
                 priority.push(video);
             } else {
                 regular.push(video);
@@ -35,7 +35,7 @@ const PinsPage = ({ onVideoSelect }) => {
 
         // Sort priority videos by their order in priorityPinIds (most recent first)
         priority.sort((a, b) => {
-            return priorityIds.indexOf(a.id) - priorityIds.indexOf(b.id);
+            return priorityIds.indexOf(a.video_id) - priorityIds.indexOf(b.video_id);
         });
 
         // Sort regular videos by pinnedAt timestamp (newest first)
@@ -70,10 +70,10 @@ const PinsPage = ({ onVideoSelect }) => {
                 {carouselVideos.length > 0 && (
                     <StickyVideoCarousel title="Priority Pins - History">
                         {carouselVideos.map((video, index) => {
-                            const isCurrentlyPlaying = currentPlaylistItems?.[currentVideoIndex]?.id === video.id;
+                            const isCurrentlyPlaying = currentPlaylistItems?.[currentVideoIndex]?.video_id === video.video_id;
                             return (
                                 <VideoCard
-                                    key={video.id || `priority-${index}`}
+                                    key={video.video_id || `priority-${index}`}
                                     video={video}
                                     index={index}
                                     originalIndex={index}
@@ -92,10 +92,10 @@ const PinsPage = ({ onVideoSelect }) => {
                 {regularVideos.length > 0 && (
                     <div className="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-4">
                         {regularVideos.map((video, index) => {
-                            const isCurrentlyPlaying = currentPlaylistItems?.[currentVideoIndex]?.id === video.id;
+                            const isCurrentlyPlaying = currentPlaylistItems?.[currentVideoIndex]?.video_id === video.video_id;
                             return (
                                 <VideoCard
-                                    key={video.id || `pinned-${index}`}
+                                    key={video.video_id || `pinned-${index}`}
                                     video={video}
                                     index={index}
                                     originalIndex={index}
@@ -61,7 +61,7 @@ import { useTabStore } from '../store/tabStore';
 import { useConfigStore } from '../store/configStore';
 import { useTabPresetStore } from '../store/tabPresetStore';
 import { useInspectLabel } from '../utils/inspectLabels';
-import { getAllPlaylists, getPlaylistItems, getAllFoldersWithVideos, getVideosInFolder, getAllStuckFolders, assignVideoToFolder, unassignVideoFromFolder, getVideoFolderAssignments, createPlaylist, addVideoToPlaylist, removeVideoFromPlaylist, getFolderMetadata } from '../api/playlistApi';
+import { getAllPlaylists, getPlaylistItems, getAllFoldersWithVideos, getVideosInFolder, getAllStuckFolders, assignVideoToFolder, unassignVideoFromFolder, getVideoFolderAssignments, getFolderMetadata, getLikesForVideoIds, likeVideo, unlikeVideo } from '../api/playlistApi';
 import { getThumbnailUrl } from '../utils/youtubeUtils';
 import { getFolderColorById, FOLDER_COLORS } from '../utils/folderColors';
 import { THEMES } from '../utils/themes';
@@ -274,7 +274,6 @@ export default function PlayerController({ onPlaylistSelect, onVideoSelect, acti
   const [shuffleColor, setShuffleColor] = useState('#6366f1');
 
   const [likeColor, setLikeColor] = useState('#0ea5e9');
-  const [likesPlaylistId, setLikesPlaylistId] = useState(null); // ID of the special "Likes" playlist
   const [isVideoLiked, setIsVideoLiked] = useState(false); // Whether current video is liked
   const [isEditMode, setIsEditMode] = useState(false);
   const [isConfigOnRight, setIsConfigOnRight] = useState(false);
@@ -407,27 +406,6 @@ export default function PlayerController({ onPlaylistSelect, onVideoSelect, acti
     loadVideoFolders();
   }, [currentVideo, currentPlaylistId, isModeLeft, secondPlayerVideoUrl, secondPlayerPlaylistItems, secondPlayerVideoIndex, secondPlayerPlaylistId, currentPlaylistItems]);
 
-  // Initialize or get "Likes" playlist
-  useEffect(() => {
-    const initLikesPlaylist = async () => {
-      try {
-        const playlists = await getAllPlaylists();
-        let likesPlaylist = playlists.find(p => p.name === 'Likes');
-
-        if (!likesPlaylist) {
-          // Create "Likes" playlist if it doesn't exist
-          const newPlaylistId = await createPlaylist('Likes', 'Videos you have liked');
-          setLikesPlaylistId(newPlaylistId);
-        } else {
-          setLikesPlaylistId(likesPlaylist.id);
-        }
-      } catch (error) {
-        console.error('Failed to initialize Likes playlist:', error);
-      }
-    };
-    initLikesPlaylist();
-  }, []);
-
   // Check if current video is liked - use active video (main or second player)
   useEffect(() => {
     const checkIfLiked = async () => {
@@ -457,22 +435,21 @@ export default function PlayerController({ onPlaylistSelect, onVideoSelect, acti
         }
       }
 
-      if (!targetVideo || !likesPlaylistId) {
+      if (!targetVideo) {
         setIsVideoLiked(false);
         return;
       }
 
       try {
-        const likesItems = await getPlaylistItems(likesPlaylistId);
-        const isLiked = likesItems.some(item => item.video_id === targetVideo.video_id);
-        setIsVideoLiked(isLiked);
+        const likes = await getLikesForVideoIds([targetVideo.video_id]);
+        setIsVideoLiked(targetVideo.video_id in likes);
       } catch (error) {
         console.error('Failed to check if video is liked:', error);
         setIsVideoLiked(false);
       }
     };
     checkIfLiked();
-  }, [currentVideo, likesPlaylistId, isModeLeft, secondPlayerVideoUrl, secondPlayerPlaylistItems, secondPlayerVideoIndex, currentPlaylistItems]);
+  }, [currentVideo, isModeLeft, secondPlayerVideoUrl, secondPlayerPlaylistItems, secondPlayerVideoIndex, currentPlaylistItems]);
 
   // Use store preview if available, otherwise use local preview, otherwise current video
   const activePreviewItems = storePreviewItems || previewPlaylistItems;
@@ -866,7 +843,7 @@ export default function PlayerController({ onPlaylistSelect, onVideoSelect, acti
 
   // Handle pin click - switch to that video
   const handlePinClick = async (pinnedVideo) => {
-    const videoIndex = currentPlaylistItems.findIndex(v => v.id === pinnedVideo.id);
+    const videoIndex = currentPlaylistItems.findIndex(v => v.video_id === pinnedVideo.video_id);
 
     if (videoIndex >= 0) {
       setCurrentVideoIndex(videoIndex);
@@ -878,7 +855,7 @@ export default function PlayerController({ onPlaylistSelect, onVideoSelect, acti
       try {
         for (const playlist of allPlaylists) {
           const items = await getPlaylistItems(playlist.id);
-          const foundIndex = items.findIndex(v => v.id === pinnedVideo.id);
+          const foundIndex = items.findIndex(v => v.video_id === pinnedVideo.video_id);
           if (foundIndex >= 0) {
             setPlaylistItems(items, playlist.id);
             if (onPlaylistSelect) {
@@ -901,7 +878,7 @@ export default function PlayerController({ onPlaylistSelect, onVideoSelect, acti
   // Handle unpin - remove pin from track
   const handleUnpin = (e, pinnedVideo) => {
     e.stopPropagation();
-    removePin(pinnedVideo.id);
+    removePin(pinnedVideo.video_id);
   };
 
   const navigateTabs = (dir) => {
@@ -1177,7 +1154,7 @@ export default function PlayerController({ onPlaylistSelect, onVideoSelect, acti
     pinLongPressTimerRef.current = setTimeout(() => {
       // Long Press Action: Set Priority Pin
       // Ensure we don't toggle it OFF if it's already ON
-      if (!isPriorityPin(targetVideo.id)) {
+      if (!isPriorityPin(targetVideo.video_id)) {
         togglePriorityPin(targetVideo);
       }
       pinLongPressTimerRef.current = null; // Mark as handled
@@ -1298,41 +1275,25 @@ export default function PlayerController({ onPlaylistSelect, onVideoSelect, acti
     }
   };
 
-  // Handle like button click - add/remove video from Likes playlist
+  // Handle like button click - like or unlike the active video
   const handleLikeClick = async () => {
     // Get the active video (main or second player) - use activeVideoItem which is computed above
     const targetVideo = activeVideoItem || currentVideo;
 
-    if (!targetVideo || !likesPlaylistId) return;
+    if (!targetVideo) return;
 
     try {
-      // Check if target video is liked
-      const likesItems = await getPlaylistItems(likesPlaylistId);
-      const targetIsLiked = likesItems.some(item => item.video_id === targetVideo.video_id);
+      const likes = await getLikesForVideoIds([targetVideo.video_id]);
+      const targetIsLiked = targetVideo.video_id in likes;
 
       if (targetIsLiked) {
-        // Remove from Likes playlist
-        const likeItem = likesItems.find(item => item.video_id === targetVideo.video_id);
-        if (likeItem) {
-          await removeVideoFromPlaylist(likesPlaylistId, likeItem.id);
-          // Update local state if this is the current video being displayed
-          if (targetVideo.id === activeVideoItem?.id) {
-            setIsVideoLiked(false);
-          }
-        }
+        await unlikeVideo(targetVideo.video_id);
       } else {
-        // Add to Likes playlist
-        await addVideoToPlaylist(
-          likesPlaylistId,
-          targetVideo.video_url,
-          targetVideo.video_id,
-          targetVideo.title || null,
-          targetVideo.thumbnail_url || null
-        );
-        // Update local state if this is the current video being displayed
-        if (targetVideo.id === activeVideoItem?.id) {
-          setIsVideoLiked(true);
-        }
+        await likeVideo(targetVideo);
+      }
+      // Update local state if this is the current video being displayed
+      if (targetVideo.id === activeVideoItem?.id) {
+        setIsVideoLiked(!targetIsLiked);
       }
     } catch (error) {
       console.error('Failed to toggle like:', error);
@@ -1487,7 +1448,7 @@ export default function PlayerController({ onPlaylistSelect, onVideoSelect, acti
 
   // Convert pinned videos to pin format expected by component
   const pins = pinnedVideos.map((video, idx) => ({
-    id: `pin-${video.id}`,
+    id: `pin-${video.video_id}`,
     icon: Pin,
     video: video,
     index: idx
@@ -1624,7 +1585,7 @@ export default function PlayerController({ onPlaylistSelect, onVideoSelect, acti
 
                 {/* Priority Pin (Top Right) */}
                 {(() => {
-                  const priorityPinData = pins.find(pin => isPriorityPin(pin.video.id));
+                  const priorityPinData = pins.find(pin => isPriorityPin(pin.video.video_id));
                   if (!priorityPinData) return null;
                   const thumbnailUrl = getThumbnailUrl(priorityPinData.video.video_id, 'default');
                   return (
@@ -2011,8 +1972,8 @@ export default function PlayerController({ onPlaylistSelect, onVideoSelect, acti
                       >
                         {(() => {
                           const targetVideo = activeVideoItem || currentVideo;
-                          const isPriority = targetVideo && isPriorityPin(targetVideo.id);
-                          const isNormalPinned = targetVideo && isPinned(targetVideo.id) && !isPriority;
+                          const isPriority = targetVideo && isPriorityPin(targetVideo.video_id);
+                          const isNormalPinned = targetVideo && isPinned(targetVideo.video_id) && !isPriority;
 
                           // Visual Logic
                           // Priority: Amber border (#fbbf24), Amber fill
@@ -1,5 +1,5 @@
 import React, { useState, useRef, useEffect } from 'react';
-import { createPlaylist, addVideoToPlaylist, assignVideoToFolder, getAllPlaylists, getPlaylistItems, getVideosInFolder } from '../api/playlistApi';
+import { createPlaylist, addVideosToPlaylist, getAllPlaylists, getPlaylistItems, getVideosInFolder } from '../api/playlistApi';
 import { extractPlaylistId, extractVideoId } from '../utils/youtubeUtils';
 import { FOLDER_COLORS } from '../utils/folderColors';
 import PlaylistFolderSelector from './PlaylistFolderSelector';
@@ -353,24 +353,15 @@ const PlaylistUploader = ({ onUploadComplete, onCancel, initialPlaylistId }) =>
       // 4. Insert into DB
       setProgress({ current: 0, total: allVideosToInsert.length, message: `Adding ${allVideosToInsert.length} videos to "${targetName}"...` });
 
-      let addedCount = 0;
-      for (let i = 0; i < allVideosToInsert.length; i++) {
-        const v = allVideosToInsert[i];
-        try {
-          const itemId = await addVideoToPlaylist(dbPlaylistId, v.videoUrl, v.videoId, v.title, v.thumbnailUrl, v.author, v.viewCount, v.publishedAt, false);
-          addedCount++;
-
-          if (v.folderColor) {
-            await assignVideoToFolder(dbPlaylistId, itemId, v.folderColor);
-          }
-
-          if (i % 5 === 0) {
-            setProgress({ current: i + 1, total: allVideosToInsert.length, message: `Adding videos... ${i + 1}/${allVideosToInsert.length}` });
-          }
-        } catch (e) {
-          console.error('Insert failed', e);
-        }
-      }
+      // One transaction for the whole batch: either every video lands or none do
+      const results = await addVideosToPlaylist(
+        dbPlaylistId,
+        allVideosToInsert.map(v => ({ ...v, isLocal: false, folderColors: v.folderColor ? [v.folderColor] : [] }))
+      );
+      const addedCount = results.filter(r => r.status === 'added').length;
+      results
+        .filter(r => r.status !== 'added')
+        .forEach(r => console.warn(`Skipped ${r.video_id || `item ${r.index}`}: ${r.reason}`));
 
       setProgress({ current: addedCount, total: addedCount, message: `Complete! Added ${addedCount} videos.` });
 
@@ -425,25 +416,30 @@ const PlaylistUploader = ({ onUploadComplete, onCancel, initialPlaylistId }) =>
 
       setProgress({ current: 0, total: videos.length, message: `Importing ${videos.length} videos...` });
 
-      for (let i = 0; i < videos.length; i++) {
-        const v = videos[i];
+      const batch = [];
+      for (const v of videos) {
         // Support various formats
         const u = v.url || v.video_url || v.videoUrl;
         const vid = v.videoId || v.video_id || extractVideoId(u);
         if (!vid) continue;
 
-        const itemId = await addVideoToPlaylist(dbId, u || `https://youtube.com/watch?v=${vid}`, vid, v.title, v.thumbnailUrl || v.thumbnail_url, v.author, v.viewCount, v.publishedAt || v.published_at, v.isLocal || false);
-
-        // Folder assignments
-        if (v.folder_assignments && Array.isArray(v.folder_assignments)) {
-          for (const c of v.folder_assignments) {
-            await assignVideoToFolder(dbId, itemId, c);
-          }
-        }
-
-        if (i % 10 === 0) setProgress({ current: i + 1, total: videos.length, message: `Importing... ${i + 1}` });
+        batch.push({
+          videoUrl: u || `https://youtube.com/watch?v=${vid}`,
+          videoId: vid,
+          title: v.title,
+          thumbnailUrl: v.thumbnailUrl || v.thumbnail_url,
+          author: v.author,
+          viewCount: v.viewCount,
+          publishedAt: v.publishedAt || v.published_at,
+          isLocal: v.isLocal || false,
+          folderColors: Array.isArray(v.folder_assignments) ? v.folder_assignments : []
+        });
       }
 
+      const results = await addVideosToPlaylist(dbId, batch);
+      const imported = results.filter(r => r.status === 'added').length;
+      setProgress({ current: imported, total: videos.length, message: `Imported ${imported} videos.` });
+
       setTimeout(() => { if (onUploadComplete) onUploadComplete(); }, 1000);
 
     } catch (e) {
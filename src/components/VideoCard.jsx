@@ -57,10 +57,10 @@ const VideoCard = ({
   // FIX: Split selectors to prevent "Maximum update depth exceeded" error
   // FIX: Split selectors to prevent "Maximum update depth exceeded" error
   const isPinnedVideo = usePinStore(state =>
-    state.pinnedVideos.some(v => v.id === video.id) && !state.priorityPinIds.includes(video.id)
+    state.pinnedVideos.some(v => v.video_id === video.video_id) && !state.priorityPinIds.includes(video.video_id)
   );
 
-  const isPriority = usePinStore(state => state.priorityPinIds.includes(video.id));
+  const isPriority = usePinStore(state => state.priorityPinIds.includes(video.video_id));
   const { togglePin, togglePriorityPin } = usePinStore();
   const pinLongPressTimerRef = useRef(null); // Timer for long press logic
   const [activePin, setActivePin] = useState(null); // For visual feedback
@@ -100,7 +100,7 @@ const VideoCard = ({
 
 
   const pinnedAt = usePinStore(state => {
-    const pin = state.pinnedVideos.find(v => v.id === video.id);
+    const pin = state.pinnedVideos.find(v => v.video_id === video.video_id);
     return pin ? pin.pinnedAt : null;
   });
 
@@ -1,233 +1,169 @@
 import { create } from 'zustand';
-import { persist } from 'zustand/middleware';
+import { getPins, pinVideo, setPriorityPin, unpinVideo } from '../api/playlistApi';
+
+// Where pins were kept before they moved to the database
+const LEGACY_STORAGE_KEY = 'pin-storage';
 
 /**
- * Pin Store - Persistent video pinning with expiration
- * Pins persist across sessions
+ * Pin Store - video pinning backed by the `pins` table
+ * Pins are keyed by video_id, so a video pinned in one playlist is pinned everywhere
  * Supports priority pins (always first/leftmost) set via yellow pin button
- * Normal pins expire after 24 hours
+ * Normal pins expire after 24 hours; the backend decides which have expired
  * Priority pins do not expire
  */
-export const usePinStore = create(
-  persist(
-    (set, get) => {
-      // access initial state if needed for reset, but persist middleware handles hydration
-      const initialState = {
-        pinnedVideos: [], // Array of video objects: { id, video_id, video_url, title, ..., pinnedAt: timestamp }
-        priorityPinIds: [], // Array of priority pin IDs, order matters (most recent first)
-      };
+export const usePinStore = create((set, get) => {
+  // Turn the backend's pins into the flat video objects components expect
+  const applyPins = (pins) => {
+    set({
+      pinnedVideos: pins.map(pin => ({
+        ...pin.video,
+        pinnedAt: pin.is_priority ? null : Date.parse(pin.pinned_at),
+        expiresAt: pin.expires_at ? Date.parse(pin.expires_at) : null,
+      })),
+      // Already ordered most recent first
+      priorityPinIds: pins.filter(pin => pin.is_priority).map(pin => pin.video.video_id),
+    });
+  };
+
+  const reload = async () => {
+    try {
+      applyPins(await getPins());
+    } catch (error) {
+      console.error('[PinStore] Failed to load pins:', error);
+    }
+  };
+
+  // Run a pin command, then refresh from the database either way
+  const update = async (action) => {
+    try {
+      await action();
+    } catch (error) {
+      console.error('[PinStore] Pin update failed:', error);
+    }
+    await reload();
+  };
+
+  // One-time move of pins saved by older builds in localStorage. Normal pins
+  // start a fresh 24 hours.
+  const importLegacyPins = async () => {
+    const raw = localStorage.getItem(LEGACY_STORAGE_KEY);
+    if (!raw) return;
+
+    try {
+      const { state } = JSON.parse(raw);
+      const videos = (state?.pinnedVideos || []).filter(v => v.video_id);
+      const priorityIds = state?.priorityPinIds || [];
+      const isPriority = (v) => priorityIds.includes(v.id);
+
+      for (const video of videos.filter(v => !isPriority(v))) {
+        await pinVideo(video);
+      }
+      // Oldest first, so the most recent ends up first
+      const priority = videos.filter(isPriority)
+        .sort((a, b) => priorityIds.indexOf(b.id) - priorityIds.indexOf(a.id));
+      for (const video of priority) {
+        await setPriorityPin(video, true);
+      }
+      localStorage.removeItem(LEGACY_STORAGE_KEY);
+    } catch (error) {
+      console.error('[PinStore] Failed to import pins from localStorage:', error);
+    }
+  };
+
+  let legacyImport = null;
+
+  return {
+    pinnedVideos: [], // Video objects: { video_id, video_url, title, ..., pinnedAt, expiresAt }
+    priorityPinIds: [], // video_ids of priority pins, most recent first
+
+    /**
+     * Get full pin info for a video
+     * @param {string} videoId - The video's video_id
+     */
+    getPinInfo: (videoId) => {
+      const state = get();
+      const isPriority = state.priorityPinIds.includes(videoId);
+      const normalPin = isPriority ? null : state.pinnedVideos.find(v => v.video_id === videoId);
 
       return {
-        ...initialState,
-
-        /**
-         * Helper to ensure priority pins are always first in the array
-         * @param {Array} videos - Array of pinned videos
-         * @param {Array} priorityIds - Array of priority pin IDs
-         * @returns {Array} Sorted array with priority pins first
-         */
-        _sortPinsWithPriority: (videos, priorityIds) => {
-          if (!priorityIds || priorityIds.length === 0) return videos;
-
-          const priorityPins = [];
-          const otherPins = [];
-
-          videos.forEach(v => {
-            if (priorityIds.includes(v.id)) {
-              priorityPins.push(v);
-            } else {
-              otherPins.push(v);
-            }
-          });
-
-          // Sort priority pins by their order in priorityIds
-          priorityPins.sort((a, b) => {
-            return priorityIds.indexOf(a.id) - priorityIds.indexOf(b.id);
-          });
-
-          return [...priorityPins, ...otherPins];
-        },
-
-        /**
-         * Get full pin info for a video
-         * @param {number} videoId 
-         */
-        getPinInfo: (videoId) => {
-          const state = get();
-          const normalPin = state.pinnedVideos.find(v => v.id === videoId && !state.priorityPinIds.includes(v.id));
-          const isPriority = state.priorityPinIds.includes(videoId);
-
-          return {
-            isPinned: !!normalPin,
-            isPriority: isPriority,
-            pinnedAt: normalPin ? normalPin.pinnedAt : null
-          };
-        },
-
-        /**
-         * Toggle pin status for a video (normal pin from video card)
-         * - If currently Priority: Unpin completely (remove from priority and pinned).
-         * - If currently Normal: Unpin.
-         * - If Not Pinned: Pin as Normal (add timer).
-         * This enforces "Cannot be both" by ensuring check is exclusive and actions clear other state.
-         * @param {Object} video - Video object to pin/unpin
-         */
-        togglePin: (video) => {
-          const state = get();
-          const isPriority = state.priorityPinIds.includes(video.id);
-          const isNormalPin = state.pinnedVideos.some(v => v.id === video.id) && !isPriority;
-
-          if (isPriority) {
-            // Unpin completely
-            const newPriorityIds = state.priorityPinIds.filter(id => id !== video.id);
-            const newPins = state.pinnedVideos.filter(v => v.id !== video.id);
-
-            set({
-              pinnedVideos: newPins,
-              priorityPinIds: newPriorityIds,
-            });
-          } else if (isNormalPin) {
-            // Unpin Normal
-            const newPins = state.pinnedVideos.filter(v => v.id !== video.id);
-            set({ pinnedVideos: newPins });
-          } else {
-            // Pin as Normal
-            // Remove from priority if it somehow was there (fail-safe)
-            const newPriorityIds = state.priorityPinIds.filter(id => id !== video.id);
-
-            const pinWithTimestamp = { ...video, pinnedAt: Date.now() };
-            const newPins = [...state.pinnedVideos, pinWithTimestamp];
-
-            set({
-              pinnedVideos: get()._sortPinsWithPriority(newPins, newPriorityIds),
-              priorityPinIds: newPriorityIds,
-            });
-          }
-        },
-
-        /**
-         * Check if a video is pinned (NORMAL PIN ONLY)
-         * @param {number} videoId - Video ID to check
-         * @returns {boolean}
-         */
-        isPinned: (videoId) => {
-          const state = get();
-          // It is pinned if in pinnedVideos AND NOT in priorityPinIds
-          return state.pinnedVideos.some(v => v.id === videoId) && !state.priorityPinIds.includes(videoId);
-        },
-
-        /**
-         * Check if a video is a priority pin
-         * @param {number} videoId - Video ID to check
-         * @returns {boolean}
-         */
-        isPriorityPin: (videoId) => {
-          const state = get();
-          return state.priorityPinIds.includes(videoId);
-        },
-
-        /**
-         * Remove a pin by video ID (removes from both)
-         * @param {number} videoId - Video ID to unpin
-         */
-        removePin: (videoId) => {
-          const state = get();
-          const newPins = state.pinnedVideos.filter(v => v.id !== videoId);
-          const newPriorityIds = state.priorityPinIds.filter(id => id !== videoId);
-
-          set({
-            pinnedVideos: newPins,
-            priorityPinIds: newPriorityIds,
-          });
-        },
-
-        /**
-         * Clear all pins
-         */
-        clearAllPins: () => {
-          set({ pinnedVideos: [], priorityPinIds: [] });
-        },
-
-        /**
-         * Toggle a video as a priority pin (via yellow button)
-         * - If currently Priority: Unpin completely (remove from priority and pinned).
-         * - If currently Normal: Promote to Priority (remove timer implicitly by becoming priority).
-         * - If Not Pinned: Pin as Priority.
-         * @param {Object} video - Video object to toggle as priority pin
-         */
-        togglePriorityPin: (video) => {
-          const state = get();
-          const isPriority = state.priorityPinIds.includes(video.id);
-
-          if (isPriority) {
-            // Remove from Priority AND Pinned (Unpin completely)
-            const newPriorityIds = state.priorityPinIds.filter(id => id !== video.id);
-            const newPins = state.pinnedVideos.filter(v => v.id !== video.id);
-            set({
-              priorityPinIds: newPriorityIds,
-              pinnedVideos: newPins
-            });
-          } else {
-            // Add to Priority (Promote)
-            const newPriorityIds = [video.id, ...state.priorityPinIds];
-
-            let newPins = state.pinnedVideos;
-            const existingPin = state.pinnedVideos.find(v => v.id === video.id);
-
-            if (!existingPin) {
-              // Add to pinnedVideos if not present
-              newPins = [video, ...state.pinnedVideos];
-            }
-            // If it was already pinned, it stays in pinnedVideos.
-            // But since isPriority=true, isPinned() will return false, making it exclusive.
-
-            set({
-              priorityPinIds: newPriorityIds,
-              pinnedVideos: get()._sortPinsWithPriority(newPins, newPriorityIds),
-            });
-          }
-        },
-
-        /**
-         * Check and remove expired pins
-         * Standard pins expire after 24 hours
-         * Priority pins never expire
-         */
-        checkExpiration: () => {
-          const state = get();
-          const now = Date.now();
-          const TWENTY_FOUR_HOURS = 24 * 60 * 60 * 1000;
-
-          const validPins = state.pinnedVideos.filter(pin => {
-            // Priority pins never expire
-            if (state.priorityPinIds.includes(pin.id)) {
-              return true;
-            }
-
-            // Check if pin has timestamp and is older than 24 hours
-            if (pin.pinnedAt && (now - pin.pinnedAt > TWENTY_FOUR_HOURS)) {
-              return false; // Remove expired
-            }
-
-            // Legacy pins without timestamp: Keep them? Or remove?
-            // Let's keep them safe.
-            return true;
-          });
-
-          // Only update if changes occurred ({pinnedVideos} array reference change triggers re-renders)
-          if (validPins.length !== state.pinnedVideos.length) {
-            set({ pinnedVideos: validPins });
-            console.log(`[PinStore] Removed ${state.pinnedVideos.length - validPins.length} expired pins.`);
-          }
-        },
+        isPinned: !!normalPin,
+        isPriority: isPriority,
+        pinnedAt: normalPin ? normalPin.pinnedAt : null
       };
     },
-    {
-      name: 'pin-storage', // localStorage key
-      partialize: (state) => ({
-        pinnedVideos: state.pinnedVideos,
-        priorityPinIds: state.priorityPinIds
-      }), // Persist both lists
-    }
-  )
-);
+
+    /**
+     * Toggle pin status for a video (normal pin from video card)
+     * - If pinned (normal or priority): Unpin completely.
+     * - If Not Pinned: Pin as Normal (starts the 24 hour timer).
+     * @param {Object} video - Video object to pin/unpin
+     */
+    togglePin: (video) => {
+      const pinned = get().pinnedVideos.some(v => v.video_id === video.video_id);
+      return update(() => (pinned ? unpinVideo(video.video_id) : pinVideo(video)));
+    },
+
+    /**
+     * Check if a video is pinned (NORMAL PIN ONLY)
+     * @param {string} videoId - video_id to check
+     * @returns {boolean}
+     */
+    isPinned: (videoId) => {
+      const state = get();
+      return state.pinnedVideos.some(v => v.video_id === videoId) && !state.priorityPinIds.includes(videoId);
+    },
+
+    /**
+     * Check if a video is a priority pin
+     * @param {string} videoId - video_id to check
+     * @returns {boolean}
+     */
+    isPriorityPin: (videoId) => {
+      return get().priorityPinIds.includes(videoId);
+    },
+
+    /**
+     * Remove a pin, normal or priority
+     * @param {string} videoId - video_id to unpin
+     */
+    removePin: (videoId) => {
+      return update(() => unpinVideo(videoId));
+    },
+
+    /**
+     * Clear all pins
+     */
+    clearAllPins: () => {
+      const videoIds = get().pinnedVideos.map(v => v.video_id);
+      return update(async () => {
+        for (const videoId of videoIds) {
+          await unpinVideo(videoId);
+        }
+      });
+    },
+
+    /**
+     * Toggle a video as a priority pin (via yellow button)
+     * - If currently Priority: Unpin completely.
+     * - Otherwise: Pin as Priority, ahead of the other priority pins.
+     * @param {Object} video - Video object to toggle as priority pin
+     */
+    togglePriorityPin: (video) => {
+      const isPriority = get().priorityPinIds.includes(video.video_id);
+      return update(() => (isPriority ? unpinVideo(video.video_id) : setPriorityPin(video, true)));
+    },
+
+    /**
+     * Reload pins from the database, which leaves out expired ones.
+     * Called on startup and every minute. The first call also imports pins
+     * left in localStorage by older builds.
+     */
+    checkExpiration: async () => {
+      if (!legacyImport) {
+        legacyImport = importLegacyPins();
+      }
+      await legacyImport;
+      await reload();
+    },
+  };
+});
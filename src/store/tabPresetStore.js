@@ -1,97 +1,112 @@
 import { create } from 'zustand';
+import {
+  getTabPresets,
+  createTabPreset,
+  updateTabPreset,
+  deleteTabPreset,
+} from '../api/playlistApi';
+import { importLegacyTabs, useTabStore } from './tabStore';
 
-// Load presets from localStorage
-const loadPresets = () => {
-  try {
-    const stored = localStorage.getItem('tabPresets');
-    if (stored) {
-      return JSON.parse(stored);
-    }
-    // Default: "All" preset that includes all tabs
-    return [{ id: 'all', name: 'All', tabIds: [] }]; // Empty tabIds means "all tabs"
-  } catch {
-    return [{ id: 'all', name: 'All', tabIds: [] }];
-  }
-};
+// The "All" preset isn't stored; empty tabIds means "all tabs"
+const ALL_PRESET = { id: 'all', name: 'All', tabIds: [] };
+
+// Ids are strings, matching the tab store
+const toPreset = (preset) => ({
+  id: String(preset.id),
+  name: preset.name,
+  tabIds: preset.tab_ids.map(String),
+});
 
-// Save presets to localStorage
-const savePresets = (presets) => {
+const loadActivePreset = () => {
   try {
-    localStorage.setItem('tabPresets', JSON.stringify(presets));
-  } catch (error) {
-    console.error('Failed to save presets:', error);
+    return localStorage.getItem('activePresetId') || 'all';
+  } catch {
+    return 'all';
   }
 };
 
-const useTabPresetStore = create((set, get) => ({
-  presets: loadPresets(),
-  activePresetId: 'all',
-  
-  setActivePreset: (presetId) => {
-    set({ activePresetId: presetId });
+const useTabPresetStore = create((set, get) => {
+  const reload = async () => {
     try {
-      localStorage.setItem('activePresetId', presetId);
+      const presets = [ALL_PRESET, ...(await getTabPresets()).map(toPreset)];
+      const activePresetId = presets.some(preset => preset.id === get().activePresetId)
+        ? get().activePresetId
+        : 'all';
+      set({ presets, activePresetId });
     } catch (error) {
-      console.error('Failed to save active preset:', error);
+      console.error('[TabPresetStore] Failed to load presets:', error);
     }
-  },
-  
-  createPreset: (name, tabIds) => {
-    const state = get();
-    const newPreset = {
-      id: `preset-${Date.now()}`,
-      name: name || `Preset ${state.presets.length}`,
-      tabIds: tabIds || [],
-    };
-    const updatedPresets = [...state.presets, newPreset];
-    savePresets(updatedPresets);
-    set({ presets: updatedPresets, activePresetId: newPreset.id });
-    return newPreset.id;
-  },
-  
-  deletePreset: (presetId) => {
-    if (presetId === 'all') return; // Can't delete "All" preset
-    const state = get();
-    const updatedPresets = state.presets.filter(preset => preset.id !== presetId);
-    savePresets(updatedPresets);
-    const newActivePresetId = state.activePresetId === presetId ? 'all' : state.activePresetId;
-    set({ presets: updatedPresets, activePresetId: newActivePresetId });
+  };
+
+  // Run a preset command, then refresh from the database either way
+  const update = async (action) => {
+    let result;
     try {
-      localStorage.setItem('activePresetId', newActivePresetId);
+      result = await action();
     } catch (error) {
-      console.error('Failed to save active preset:', error);
+      console.error('[TabPresetStore] Preset update failed:', error);
     }
-  },
-  
-  updatePreset: (presetId, name, tabIds) => {
-    if (presetId === 'all') return; // Can't update "All" preset
-    const state = get();
-    const updatedPresets = state.presets.map(preset => {
-      if (preset.id === presetId) {
-        return { ...preset, name: name || preset.name, tabIds: tabIds !== undefined ? tabIds : preset.tabIds };
+    await reload();
+    return result;
+  };
+
+  return {
+    presets: [ALL_PRESET],
+    activePresetId: loadActivePreset(),
+
+    /**
+     * Load presets from the database, once any localStorage import is done
+     */
+    loadPresets: async () => {
+      await importLegacyTabs();
+      set({ activePresetId: loadActivePreset() });
+      await reload();
+    },
+
+    setActivePreset: (presetId) => {
+      set({ activePresetId: presetId });
+      try {
+        localStorage.setItem('activePresetId', presetId);
+      } catch (error) {
+        console.error('Failed to save active preset:', error);
       }
-      return preset;
-    });
-    savePresets(updatedPresets);
-    set({ presets: updatedPresets });
-  },
-}));
+    },
 
-// Load active preset from localStorage on init
-const loadActivePreset = () => {
-  try {
-    const stored = localStorage.getItem('activePresetId');
-    return stored || 'all';
-  } catch {
-    return 'all';
-  }
-};
+    createPreset: async (name, tabIds) => {
+      const preset = await update(() =>
+        createTabPreset(name || `Preset ${get().presets.length}`, (tabIds || []).map(Number))
+      );
+      if (preset) {
+        get().setActivePreset(String(preset.id));
+        return String(preset.id);
+      }
+      return null;
+    },
 
-// Initialize active preset
-const initialActivePreset = loadActivePreset();
-if (initialActivePreset !== 'all') {
-  useTabPresetStore.getState().setActivePreset(initialActivePreset);
-}
+    deletePreset: async (presetId) => {
+      if (presetId === 'all') return; // Can't delete "All" preset
+      if (get().activePresetId === presetId) {
+        get().setActivePreset('all');
+      }
+      await update(() => deleteTabPreset(Number(presetId)));
+    },
 
-export { useTabPresetStore };
+    updatePreset: async (presetId, name, tabIds) => {
+      if (presetId === 'all') return; // Can't update "All" preset
+      await update(() =>
+        updateTabPreset(Number(presetId), name || null, tabIds !== undefined ? tabIds.map(Number) : null)
+      );
+    },
+  };
+});
+
+useTabPresetStore.getState().loadPresets();
 
+// Deleting a tab removes it from presets in the database too
+useTabStore.subscribe((state, previous) => {
+  if (state.tabs !== previous.tabs) {
+    useTabPresetStore.getState().loadPresets();
+  }
+});
+
+export { useTabPresetStore };
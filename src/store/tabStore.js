@@ -1,123 +1,149 @@
 import { create } from 'zustand';
+import {
+  getTabs,
+  createTab as createTabApi,
+  renameTab as renameTabApi,
+  deleteTab as deleteTabApi,
+  addPlaylistToTab as addPlaylistToTabApi,
+  removePlaylistFromTab as removePlaylistFromTabApi,
+  importLocalTabs,
+} from '../api/playlistApi';
 
-// Load tabs from localStorage
-const loadTabs = () => {
+// The "All" tab isn't stored; it always shows every playlist
+const ALL_TAB = { id: 'all', name: 'All', playlistIds: [] };
+
+// Where tabs and presets were kept before they moved to the database
+const LEGACY_TABS_KEY = 'playlistTabs';
+const LEGACY_PRESETS_KEY = 'tabPresets';
+
+// Tab ids are kept as strings so they compare equal to 'all' and to the
+// active tab id saved in localStorage
+const toTab = (tab) => ({ id: String(tab.id), name: tab.name, playlistIds: tab.playlist_ids });
+
+const loadActiveTab = () => {
   try {
-    const stored = localStorage.getItem('playlistTabs');
-    if (stored) {
-      return JSON.parse(stored);
-    }
-    // Default: just "All" tab
-    return [{ id: 'all', name: 'All', playlistIds: [] }];
+    return localStorage.getItem('activeTabId') || 'all';
   } catch {
-    return [{ id: 'all', name: 'All', playlistIds: [] }];
+    return 'all';
   }
 };
 
-// Save tabs to localStorage
-const saveTabs = (tabs) => {
-  try {
-    localStorage.setItem('playlistTabs', JSON.stringify(tabs));
-  } catch (error) {
-    console.error('Failed to save tabs:', error);
+let legacyImport = null;
+
+/**
+ * One-time move of tabs and presets saved by older builds in localStorage.
+ * Shared with the preset store, which waits for it before loading presets.
+ */
+export const importLegacyTabs = () => {
+  if (!legacyImport) {
+    legacyImport = (async () => {
+      const tabsJson = localStorage.getItem(LEGACY_TABS_KEY);
+      const presetsJson = localStorage.getItem(LEGACY_PRESETS_KEY);
+      if (!tabsJson && !presetsJson) return;
+
+      try {
+        const report = await importLocalTabs(tabsJson || '[]', presetsJson);
+
+        // Point the saved selections at the new ids
+        const activeTabId = localStorage.getItem('activeTabId');
+        if (activeTabId && report.tab_ids[activeTabId]) {
+          localStorage.setItem('activeTabId', String(report.tab_ids[activeTabId]));
+        }
+        const activePresetId = localStorage.getItem('activePresetId');
+        if (activePresetId && report.preset_ids[activePresetId]) {
+          localStorage.setItem('activePresetId', String(report.preset_ids[activePresetId]));
+        }
+
+        localStorage.removeItem(LEGACY_TABS_KEY);
+        localStorage.removeItem(LEGACY_PRESETS_KEY);
+      } catch (error) {
+        console.error('[TabStore] Failed to import tabs from localStorage:', error);
+      }
+    })();
   }
+  return legacyImport;
 };
 
-const useTabStore = create((set, get) => ({
-  tabs: loadTabs(),
-  activeTabId: 'all',
-
-  setActiveTab: (tabId) => {
-    set({ activeTabId: tabId });
+const useTabStore = create((set, get) => {
+  const reload = async () => {
     try {
-      localStorage.setItem('activeTabId', tabId);
+      const tabs = [ALL_TAB, ...(await getTabs()).map(toTab)];
+      // A tab deleted elsewhere can't stay active
+      const activeTabId = tabs.some(tab => tab.id === get().activeTabId) ? get().activeTabId : 'all';
+      set({ tabs, activeTabId });
     } catch (error) {
-      console.error('Failed to save active tab:', error);
+      console.error('[TabStore] Failed to load tabs:', error);
     }
-  },
-
-  createTab: (name) => {
-    const state = get();
-    const newTab = {
-      id: `tab-${Date.now()}`,
-      name: name || `Tab ${state.tabs.length}`,
-      playlistIds: [],
-    };
-    const updatedTabs = [...state.tabs, newTab];
-    saveTabs(updatedTabs);
-    set({ tabs: updatedTabs, activeTabId: newTab.id });
-    return newTab.id;
-  },
-
-  deleteTab: (tabId) => {
-    if (tabId === 'all') return; // Can't delete "All" tab
-    const state = get();
-    const updatedTabs = state.tabs.filter(tab => tab.id !== tabId);
-    saveTabs(updatedTabs);
-    const newActiveTabId = state.activeTabId === tabId ? 'all' : state.activeTabId;
-    set({ tabs: updatedTabs, activeTabId: newActiveTabId });
+  };
+
+  // Run a tab command, then refresh from the database either way
+  const update = async (action) => {
+    let result;
     try {
-      localStorage.setItem('activeTabId', newActiveTabId);
+      result = await action();
     } catch (error) {
-      console.error('Failed to save active tab:', error);
+      console.error('[TabStore] Tab update failed:', error);
     }
-  },
-
-  addPlaylistToTab: (tabId, playlistId) => {
-    if (tabId === 'all') return; // Can't add to "All" tab
-    const state = get();
-    const updatedTabs = state.tabs.map(tab => {
-      if (tab.id === tabId && !tab.playlistIds.some(id => String(id) === String(playlistId))) {
-        return { ...tab, playlistIds: [...tab.playlistIds, playlistId] };
+    await reload();
+    return result;
+  };
+
+  return {
+    tabs: [ALL_TAB],
+    activeTabId: loadActiveTab(),
+
+    /**
+     * Load tabs from the database, importing any left in localStorage first
+     */
+    loadTabs: async () => {
+      await importLegacyTabs();
+      set({ activeTabId: loadActiveTab() });
+      await reload();
+    },
+
+    setActiveTab: (tabId) => {
+      set({ activeTabId: tabId });
+      try {
+        localStorage.setItem('activeTabId', tabId);
+      } catch (error) {
+        console.error('Failed to save active tab:', error);
       }
-      return tab;
-    });
-    saveTabs(updatedTabs);
-    set({ tabs: updatedTabs });
-  },
-
-  removePlaylistFromTab: (tabId, playlistId) => {
-    if (tabId === 'all') return; // Can't remove from "All" tab
-    const state = get();
-    const updatedTabs = state.tabs.map(tab => {
-      if (tab.id === tabId) {
-        return { ...tab, playlistIds: tab.playlistIds.filter(id => String(id) !== String(playlistId)) };
+    },
+
+    createTab: async (name) => {
+      const tab = await update(() => createTabApi(name || `Tab ${get().tabs.length}`));
+      if (tab) {
+        get().setActiveTab(String(tab.id));
+        return String(tab.id);
       }
-      return tab;
-    });
-    saveTabs(updatedTabs);
-    set({ tabs: updatedTabs });
-  },
-
-  renameTab: (tabId, newName) => {
-    if (tabId === 'all') return; // Can't rename "All" tab
-    const state = get();
-    const updatedTabs = state.tabs.map(tab => {
-      if (tab.id === tabId) {
-        return { ...tab, name: newName };
+      return null;
+    },
+
+    deleteTab: async (tabId) => {
+      if (tabId === 'all') return; // Can't delete "All" tab
+      if (get().activeTabId === tabId) {
+        get().setActiveTab('all');
       }
-      return tab;
-    });
-    saveTabs(updatedTabs);
-    set({ tabs: updatedTabs });
-  },
-}));
-
-// Load active tab from localStorage on init
-const loadActiveTab = () => {
-  try {
-    const stored = localStorage.getItem('activeTabId');
-    return stored || 'all';
-  } catch {
-    return 'all';
-  }
-};
+      await update(() => deleteTabApi(Number(tabId)));
+    },
 
-// Initialize active tab
-const initialActiveTab = loadActiveTab();
-if (initialActiveTab !== 'all') {
-  useTabStore.getState().setActiveTab(initialActiveTab);
-}
+    addPlaylistToTab: async (tabId, playlistId) => {
+      if (tabId === 'all') return; // Can't add to "All" tab
+      await update(() => addPlaylistToTabApi(Number(tabId), Number(playlistId)));
+    },
 
-export { useTabStore };
+    removePlaylistFromTab: async (tabId, playlistId) => {
+      if (tabId === 'all') return; // Can't remove from "All" tab
+      await update(() => removePlaylistFromTabApi(Number(tabId), Number(playlistId)));
+    },
 
+    renameTab: async (tabId, newName) => {
+      if (tabId === 'all') return; // Can't rename "All" tab
+      await update(() => renameTabApi(Number(tabId), newName));
+    },
+  };
+});
+
+useTabStore.getState().loadTabs();
+
+export { useTabStore };